@@ -19,6 +19,8 @@ importScripts(
   '../core/taskQueue.js',
   '../core/scheduler.js',
   '../strategy/gameData.js',
+  '../strategy/travelTime.js',     // TravianTravelTime — troop travel times (wrapped map, TS, artifacts)
+  '../strategy/combat.js',         // TravianCombat — battle simulator (losses, siege, loot capacity)
   '../strategy/buildOptimizer.js',
   '../strategy/militaryPlanner.js',
   '../strategy/strategyEngine.js',
@@ -46,7 +48,20 @@ importScripts(
   '../core/safety/safeModeController.js',  // Safety: restricted operation mode
   '../core/safety/safetyEngine.js',        // Safety: central orchestrator
   '../core/botEngine.js',
-  '../core/instanceManager.js'
+  '../core/instanceManager.js',
+  '../core/telegram.js',           // TravianTelegram — remote alerts + commands
+  '../core/discord.js',            // TravianDiscord — webhook alerts
+  '../core/webhook.js',            // TravianWebhook — generic JSON webhook
+  '../core/notifier.js',           // TravianNotifier — alert routing across channels
+  '../core/historyStore.js',       // TravianHistoryStore — IndexedDB log history
+  '../core/serverClock.js',        // TravianServerClock — server time offset + drift
+  '../core/schedules.js',          // TravianSchedules — cron-style jobs
+  '../core/humanizer.js',          // TravianHumanizer — daily awake/asleep windows
+  '../core/actionIcon.js',         // TravianActionIcon — toolbar icon reflects bot state
+  '../core/alertCenter.js',        // TravianAlertCenter — recent urgent alerts + acknowledgement
+  '../core/mapStore.js',           // TravianMapStore — daily map.sql snapshots + population history
+  '../core/farmFinder.js',         // TravianFarmFinder — inactive villages ranked as raid targets
+  '../core/cropperFinder.js'       // TravianCropperFinder — free 9c/15c valleys + oasis bonus
 );
 
 // ---------------------------------------------------------------------------
@@ -56,6 +71,9 @@ const LOG_TAG = '[ServiceWorker]';
 const logger = self.TravianLogger;
 const manager = new self.TravianInstanceManager();
 
+// Persist every log entry beyond the logger's 500-entry window
+logger.subscribe(self.TravianHistoryStore.appendLog);
+
 // ---------------------------------------------------------------------------
 // 3. Helper — find ALL open Travian tabs
 // ---------------------------------------------------------------------------
@@ -207,244 +225,2162 @@ async function buildCookieHeader(hostname) {
   return cookies.map(function (c) { return c.name + '=' + c.value; }).join('; ');
 }
 
-// ---------------------------------------------------------------------------
-// 6. Message Handler (from popup, content scripts, etc.)
-// ---------------------------------------------------------------------------
-chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
-  (async function () {
-    try {
-      var type = message ? message.type : null;
-      var data = message ? message.data : null;
-      var msgConfig = message ? message.config : null;
-      var serverKey = message ? message.serverKey : null;
-      logger.debug(LOG_TAG + ' onMessage: ' + type + (serverKey ? ' [' + serverKey + ']' : ''));
+/**
+ * Find the server's game tab (discovering it if the instance has none yet,
+ * same pattern as START_BOT) and return its origin.
+ * @param {object} inst - Bot instance; tabId is set when a tab is found
+ * @param {string} serverKey
+ * @returns {Promise<string|null>} e.g. "https://ts4.x1.asia.travian.com", or null without a tab
+ */
+async function resolveServerOrigin(inst, serverKey) {
+  if (!inst.tabId) {
+    var tabs = await findAllTravianTabs();
+    for (var i = 0; i < tabs.length; i++) {
+      if (self.TravianStorage.extractServerKey(tabs[i].url) === serverKey) {
+        inst.tabId = tabs[i].id;
+        inst.engine.activeTabId = tabs[i].id;
+        break;
+      }
+    }
+  }
+  if (!inst.tabId) return null;
+  var tab = await chrome.tabs.get(inst.tabId).catch(function() { return null; });
+  var m = tab && tab.url ? tab.url.match(/^https?:\/\/[^\/]+/) : null;
+  return m ? m[0] : null;
+}
+
+/**
+ * Where map searches center by default: the farm scanner's configured
+ * coordinates, else the active village (else the first one).
+ * @param {object|null} inst
+ * @param {string} serverKey
+ * @returns {Promise<{x: number, y: number}|null>}
+ */
+async function defaultMapCenter(inst, serverKey) {
+  var cfg = (inst && inst.engine.config) || await self.TravianStorage.getServerConfig(serverKey) || {};
+  var farmCfg = cfg.farmConfig || {};
+  if (farmCfg.scanMyX != null && farmCfg.scanMyY != null) return { x: farmCfg.scanMyX, y: farmCfg.scanMyY };
+  var gs = inst && inst.engine.gameState;
+  if (!gs || !gs.villages || gs.villages.length === 0) return null;
+  return gs.villages.find(function(v) { return String(v.id) === String(cfg.activeVillage); }) || gs.villages[0];
+}
+
+/** Slots per farm list (game limit) */
+var FARM_LIST_MAX_SLOTS = 100;
+
+/**
+ * Add targets to the first farm list through the game UI: navigates the tab
+ * to the rally point's farm list page, counts the slots already used and
+ * adds as many targets as fit, one by one with human-like delays.
+ * @param {number} farmTabId - The server's game tab
+ * @param {string} serverOrigin - e.g. "https://ts4.x1.asia.travian.com"
+ * @param {Array<{x: number, y: number}>} targets - In the order to add them
+ * @param {Object} troops - Per target, e.g. {t1: 5}
+ * @returns {Promise<{ added: number, failed: number, existing: number, full: boolean }>}
+ *   full = the list had no free slot (nothing was tried)
+ * @throws {Error} If the farm list page doesn't load
+ */
+async function addFarmListTargets(farmTabId, serverOrigin, targets, troops) {
+  var farmListUrl = serverOrigin + '/build.php?id=39&tt=99';
+
+  await new Promise(function(resolve, reject) {
+    var navTimeout = setTimeout(function() {
+      chrome.tabs.onUpdated.removeListener(navListener);
+      reject(new Error('Navigation timeout'));
+    }, 15000);
+    function navListener(updatedTabId, changeInfo) {
+      if (updatedTabId === farmTabId && changeInfo.status === 'complete') {
+        chrome.tabs.onUpdated.removeListener(navListener);
+        clearTimeout(navTimeout);
+        setTimeout(function() { resolve(); }, 1000);
+      }
+    }
+    chrome.tabs.onUpdated.addListener(navListener);
+    chrome.tabs.update(farmTabId, { url: farmListUrl });
+  });
+
+  // Wait for content script and get existing slots
+  await new Promise(function(r) { setTimeout(r, 2000); });
+
+  var existingSlots = [];
+  for (var scanRetry = 0; scanRetry < 3; scanRetry++) {
+    try {
+      var slotResp = await new Promise(function(resolve, reject) {
+        chrome.tabs.sendMessage(farmTabId, {
+          type: 'EXECUTE', action: 'scanFarmListSlots', params: {}
+        }, function(r) {
+          if (chrome.runtime.lastError) reject(new Error(chrome.runtime.lastError.message));
+          else resolve(r);
+        });
+      });
+      // Unwrap bridge response: slots may be in .data.slots or .slots
+      var slotData = (slotResp && slotResp.data && typeof slotResp.data === 'object')
+        ? slotResp.data : slotResp;
+      if (slotData && slotData.slots) {
+        existingSlots = slotData.slots;
+        break;
+      }
+    } catch (slotErr) {
+      if (scanRetry === 2) logger.warn('[MapScanner] Could not scan existing slots: ' + slotErr.message);
+      await new Promise(function(r) { setTimeout(r, 1500); });
+    }
+  }
+
+  // Check farm list capacity
+  var existingCount = existingSlots.length;
+  var available = FARM_LIST_MAX_SLOTS - existingCount;
+  if (available <= 0) return { added: 0, failed: 0, existing: existingCount, full: true };
+
+  var toAdd = targets.slice(0, available);
+  var addedCount = 0;
+  var failedCount = 0;
+
+  for (var ci = 0; ci < toAdd.length; ci++) {
+    var target = toAdd[ci];
+    try {
+      var addResp = await new Promise(function(resolve, reject) {
+        chrome.tabs.sendMessage(farmTabId, {
+          type: 'EXECUTE', action: 'addToFarmList', params: {
+            x: target.x, y: target.y, troops: troops, listIndex: 0
+          }
+        }, function(r) {
+          if (chrome.runtime.lastError) reject(new Error(chrome.runtime.lastError.message));
+          else resolve(r);
+        });
+      });
+
+      if (addResp && addResp.success) {
+        addedCount++;
+      } else {
+        failedCount++;
+        logger.warn('[MapScanner] Failed to add (' + target.x + '|' + target.y + '): ' + (addResp ? (addResp.error || addResp.message || JSON.stringify(addResp)) : 'no response'));
+        // If we get 'no_input' or 'button_not_found', the selectors may be wrong — stop trying
+        if (addResp && (addResp.reason === 'no_input' || addResp.reason === 'button_not_found')) {
+          logger.error('[MapScanner] Stopping: farm list add UI not found. Selectors may need updating.');
+          break;
+        }
+      }
+    } catch (addErr) {
+      failedCount++;
+      logger.warn('[MapScanner] Error adding target: ' + addErr.message);
+    }
+
+    // Human-like delay between adds (1-3 seconds)
+    if (ci < toAdd.length - 1) {
+      await new Promise(function(r) { setTimeout(r, 1000 + Math.random() * 2000); });
+    }
+  }
+
+  return { added: addedCount, failed: failedCount, existing: existingCount, full: false };
+}
+
+/**
+ * Summarize login/session health for a server: the engine's logged-in
+ * signal plus the expiry of the server's persistent cookies.
+ * @param {string} serverKey
+ * @param {object|null} inst
+ * @returns {Promise<{ serverKey: string, status: string, loggedIn: boolean|null,
+ *   notLoggedInCount: number, maxNotLoggedIn: number,
+ *   cookies: { count: number, sessionOnly: number, earliestExpiry: number|null } }>}
+ *   status: 'ok' | 'degraded' (missed login checks) | 'expired' | 'unknown'
+ */
+async function buildSessionHealth(serverKey, inst) {
+  var cookies = [];
+  try {
+    cookies = await chrome.cookies.getAll({ domain: serverKey });
+  } catch (e) { console.warn(LOG_TAG, 'Cookie lookup failed for ' + serverKey + ':', e); }
+
+  var earliest = null;
+  var sessionOnly = 0;
+  cookies.forEach(function (c) {
+    if (c.session || !c.expirationDate) { sessionOnly++; return; }
+    var ms = c.expirationDate * 1000;
+    if (earliest === null || ms < earliest) earliest = ms;
+  });
+
+  var engine = inst && inst.engine;
+  var loggedIn = engine && engine.gameState ? !!engine.gameState.loggedIn : null;
+  var missed = engine ? engine._notLoggedInCount : 0;
+  var status = 'unknown';
+  if (engine && engine._emergencyReason && engine._emergencyReason.indexOf('Session expired') === 0) {
+    status = 'expired';
+  } else if (missed > 0) {
+    status = 'degraded';
+  } else if (loggedIn) {
+    status = 'ok';
+  }
+
+  return {
+    serverKey: serverKey,
+    status: status,
+    loggedIn: loggedIn,
+    notLoggedInCount: missed,
+    maxNotLoggedIn: engine ? engine._notLoggedInMaxCount : 0,
+    cookies: { count: cookies.length, sessionOnly: sessionOnly, earliestExpiry: earliest }
+  };
+}
+
+// ---------------------------------------------------------------------------
+// 5c. Status / strategy payload builders (shared by GET_STATUS, GET_DASHBOARD)
+// ---------------------------------------------------------------------------
+
+/**
+ * Build the status payload for a server. Falls back to an idle status
+ * (with saved config) when no instance exists yet.
+ * @param {object|null} inst - Instance from resolveInstance()
+ * @param {string|null} serverKey
+ * @returns {Promise<object>}
+ */
+async function buildStatus(inst, serverKey) {
+  if (inst) {
+    var status = inst.engine.getStatus();
+    status.activeTabId = inst.tabId;
+    status.resyncing = !!inst.resyncingSince;
+    status.networkPaused = isNetworkPaused(inst.serverKey);
+    return status;
+  }
+
+  // No instance for this server yet — load saved config from storage
+  var savedConfig = null;
+  if (serverKey) {
+    try {
+      savedConfig = await self.TravianStorage.getServerConfig(serverKey);
+    } catch (e) { console.warn(LOG_TAG, 'Failed to load config for idle status:', e); }
+  }
+  return {
+    running: false, paused: false, emergencyStopped: false,
+    activeTabId: null, serverKey: serverKey,
+    stats: { tasksCompleted: 0, tasksFailed: 0, startTime: null, lastAction: null, farmRaidsSent: 0 },
+    actionsThisHour: 0, taskQueue: { total: 0, pending: 0, tasks: [] },
+    gameState: null, config: savedConfig, nextActionTime: null
+  };
+}
+
+/**
+ * Build the strategy analysis payload for a server.
+ * @param {object|null} inst - Instance from resolveInstance()
+ * @returns {{ analysis: object|null, phase: string, planner: object|null }}
+ */
+function buildStrategy(inst) {
+  var de = inst && inst.engine.decisionEngine;
+  return {
+    analysis: de ? de.getLastAnalysis() : null,
+    phase: de ? de.getPhase() : 'unknown',
+    planner: de ? de.getPlannerState() : null
+  };
+}
+
+/**
+ * Build one compact overview row for a server.
+ * @param {string} serverKey
+ * @param {object|null} inst - Instance from manager.get(), or null if never started
+ * @param {object} [regEntry] - Registry entry ({ label, lastUsed })
+ * @returns {{ serverKey: string, label: string, tabId: number|null, state: string,
+ *   botState: string|null, queuePending: number, queueTotal: number,
+ *   nextActionTime: number|null, lastError: string|null, actionsThisHour: number,
+ *   incomingAttacks: number, manualMode: boolean, lastUsed: number|null }}
+ */
+function buildOverviewEntry(serverKey, inst, regEntry) {
+  var entry = {
+    serverKey: serverKey,
+    label: (regEntry && regEntry.label) || serverKey,
+    tabId: null,
+    state: 'stopped',
+    botState: null,
+    queuePending: 0,
+    queueTotal: 0,
+    nextActionTime: null,
+    lastError: null,
+    actionsThisHour: 0,
+    incomingAttacks: 0,
+    manualMode: false,
+    lastUsed: (regEntry && regEntry.lastUsed) || null
+  };
+  if (!inst) return entry;
+
+  var engine = inst.engine;
+  entry.tabId = inst.tabId;
+  entry.botState = engine._botState;
+  if (engine.emergencyStopped) entry.state = 'emergency';
+  else if (engine.running) entry.state = engine.paused ? 'paused' : 'running';
+
+  var tasks = engine.taskQueue ? engine.taskQueue.getAll() : [];
+  entry.queueTotal = tasks.length;
+  entry.queuePending = engine.taskQueue ? engine.taskQueue.size() : 0;
+  entry.nextActionTime = engine.running ? engine.nextActionTime : null;
+  entry.actionsThisHour = engine.actionsThisHour || 0;
+  entry.manualMode = !!engine.manualMode;
+
+  // Last error: emergency reason wins, else the most recent task error
+  if (engine._emergencyReason) {
+    entry.lastError = engine._emergencyReason;
+  } else {
+    for (var i = tasks.length - 1; i >= 0; i--) {
+      if (tasks[i].error) { entry.lastError = tasks[i].error; break; }
+    }
+  }
+
+  var gs = engine.gameState;
+  if (gs && Array.isArray(gs.incomingAttacks)) entry.incomingAttacks = gs.incomingAttacks.length;
+  return entry;
+}
+
+// ---------------------------------------------------------------------------
+// 5d. Bot control helpers (shared by per-server and fan-out messages)
+// ---------------------------------------------------------------------------
+
+/**
+ * Start (or resume) the bot for one server, binding it to that server's tab.
+ * @param {string} serverKey
+ * @returns {Promise<{success: boolean, error?: string, data?: object}>}
+ */
+async function startServer(serverKey) {
+  var startInst = manager.getOrCreate(serverKey);
+
+  if (startInst.engine.running && !startInst.engine.paused) {
+    return { success: false, error: 'Bot is already running for ' + serverKey };
+  }
+
+  // Find the tab for this server
+  var startTab = null;
+  if (startInst.tabId) {
+    startTab = await chrome.tabs.get(startInst.tabId).catch(function () { return null; });
+  }
+  if (!startTab) {
+    // Search all Travian tabs for one matching this server
+    var allTabs = await findAllTravianTabs();
+    for (var t = 0; t < allTabs.length; t++) {
+      var tabKey = self.TravianStorage.extractServerKey(allTabs[t].url);
+      if (tabKey === serverKey) {
+        startTab = allTabs[t];
+        break;
+      }
+    }
+  }
+  if (!startTab) {
+    return { success: false, error: 'No tab found for server ' + serverKey + '. Open it first.' };
+  }
+
+  startInst.tabId = startTab.id;
+  startInst.engine.activeTabId = startTab.id;
+
+  // Resume if paused, start fresh otherwise
+  if (startInst.engine.running && startInst.engine.paused) {
+    if (startInst.engine.captchaBlocked) {
+      return { success: false, error: 'Paused for a captcha on ' + serverKey + ' — solve it, then resume after captcha' };
+    }
+    if (startInst.engine.manualMode) {
+      return { success: false, error: 'Manual control is on for ' + serverKey + ' — hand control back to resume' };
+    }
+    clearNetworkPause(serverKey);
+    startInst.engine.resume();
+  } else {
+    await startInst.engine.start(startTab.id);
+  }
+
+  notify('Started', 'Bot running on ' + serverKey);
+  return { success: true, data: startInst.engine.getStatus() };
+}
+
+/** Max servers acted on concurrently by fan-out messages. */
+const FAN_OUT_CONCURRENCY = 3;
+
+/**
+ * Run fn(serverKey) for each server with bounded parallelism.
+ * Errors are captured per server rather than failing the whole batch.
+ * @param {string[]} serverKeys
+ * @param {function(string): (object|Promise<object>)} fn - Returns { success, error? }
+ * @returns {Promise<Object.<string, object>>} serverKey → result
+ */
+async function fanOut(serverKeys, fn) {
+  var results = {};
+  var next = 0;
+
+  async function worker() {
+    while (next < serverKeys.length) {
+      var key = serverKeys[next++];
+      try {
+        results[key] = await fn(key);
+      } catch (err) {
+        results[key] = { success: false, error: err.message || String(err) };
+      }
+    }
+  }
+
+  var workers = [];
+  for (var w = 0; w < Math.min(FAN_OUT_CONCURRENCY, serverKeys.length); w++) {
+    workers.push(worker());
+  }
+  await Promise.all(workers);
+  return results;
+}
+
+/**
+ * Pause every running bot so no new task starts, then wait until none is in
+ * the middle of an action (or timeoutMs passes).
+ * @param {number} timeoutMs
+ * @returns {Promise<{ engines: Array<BotEngine>, drained: boolean }>}
+ *   engines = the bots that were running (now paused, running flag kept)
+ */
+async function pauseAndDrain(timeoutMs) {
+  var engines = [];
+  manager.listActive().forEach(function (r) {
+    if (!r.running) return;
+    var engine = manager.get(r.serverKey).engine;
+    if (!r.paused) engine.pause();
+    engines.push(engine);
+  });
+
+  var deadline = Date.now() + timeoutMs;
+  while (engines.some(function (e) { return e._executionLocked; })) {
+    if (Date.now() >= deadline) return { engines: engines, drained: false };
+    await self.TravianDelay.wait(1000);
+  }
+  return { engines: engines, drained: true };
+}
+
+/**
+ * Stop every bot after its current action finishes (e.g. before closing
+ * the browser), instead of cutting a raid or build off mid-way.
+ * @returns {Promise<void>}
+ */
+var GRACEFUL_STOP_MS = 2 * 60000;
+async function stopAllGracefully() {
+  var drain = await pauseAndDrain(GRACEFUL_STOP_MS);
+  await Promise.allSettled(drain.engines.map(function (e) { return e.stop(); }));
+  await self.TravianHistoryStore.flush();
+  await self.TravianLogger.flush();
+  logger.info('Graceful stop: stopped ' + drain.engines.length + ' bot(s)' +
+    (drain.drained ? '' : ' (timed out waiting for running actions)'));
+  notify('Stopped', drain.engines.length + ' bot(s) stopped — safe to close the browser');
+}
+
+// ---------------------------------------------------------------------------
+// 5e. Notification channels and Telegram commands
+// ---------------------------------------------------------------------------
+// InstanceManager dispatches alerts through TravianNotifier; routing rules
+// live in each server's notificationConfig, channel settings are global.
+
+self.TravianNotifier.register('desktop', {
+  send: function (alert, nc) { return manager.showDesktopAlert(alert, nc); },
+  test: function () {
+    self.TravianInstanceManager.notify('test', '🔔 Test notification', 'Desktop notifications are working.', false);
+    return Promise.resolve(true);
+  }
+});
+
+self.TravianNotifier.register('telegram', {
+  send: async function (alert) {
+    var cfg = await self.TravianTelegram.getConfig();
+    if (!self.TravianTelegram.isReady(cfg)) return false;
+    await self.TravianTelegram.send(cfg, alert.title + '\n' + alert.message);
+    return true;
+  },
+  test: async function () {
+    var cfg = await self.TravianTelegram.getConfig();
+    if (!cfg.botToken || !cfg.chatId) throw new Error('Bot token and chat ID required');
+    await self.TravianTelegram.send(cfg, '🔔 Travian Assistant connected.');
+    return true;
+  }
+});
+
+self.TravianNotifier.register('discord', {
+  send: async function (alert) {
+    return self.TravianDiscord.sendAlert(await self.TravianDiscord.getConfig(), alert.kind, alert.title, alert.message);
+  },
+  test: async function () {
+    return self.TravianDiscord.sendTest(await self.TravianDiscord.getConfig());
+  }
+});
+
+self.TravianNotifier.register('webhook', {
+  send: async function (alert) {
+    return self.TravianWebhook.sendAlert(await self.TravianWebhook.getConfig(), alert);
+  },
+  test: async function () {
+    return self.TravianWebhook.sendTest(await self.TravianWebhook.getConfig());
+  }
+});
+
+/** Create or clear the Telegram command poll alarm to match the config. */
+function syncTelegramPollAlarm(cfg) {
+  if (self.TravianTelegram.isReady(cfg) && cfg.commandsEnabled) {
+    chrome.alarms.create('telegramPoll', { periodInMinutes: 1 });
+  } else {
+    chrome.alarms.clear('telegramPoll');
+  }
+}
+
+/**
+ * Resolve a command's server argument ('ts5', full hostname, 'all' or none).
+ * @param {string|undefined} arg
+ * @returns {string[]|null} Matching server keys, or null if arg matched nothing
+ */
+function resolveTelegramTargets(arg) {
+  var keys = manager.listActive().map(function (i) { return i.serverKey; });
+  if (!arg || arg.toLowerCase() === 'all') return keys;
+  var needle = arg.toLowerCase();
+  var hits = keys.filter(function (k) { return k === needle || k.split('.')[0] === needle; });
+  return hits.length > 0 ? hits : null;
+}
+
+/**
+ * Run one Telegram command through the normal message handler.
+ * @param {{command: string, args: string[]}} cmd
+ * @param {object} cfg - Telegram config (for sendPhoto)
+ * @returns {Promise<string|null>} Reply text, or null if already replied
+ */
+async function runTelegramCommand(cmd, cfg) {
+  if (cmd.command === 'help') return self.TravianTelegram.HELP_TEXT;
+
+  if (cmd.command === 'status') {
+    var ov = await dispatchInternal({ type: 'GET_OVERVIEW' });
+    var rows = (ov.data && ov.data.servers) || [];
+    if (rows.length === 0) return 'No servers known yet.';
+    return rows.map(function (r) {
+      return r.label + ': ' + r.state + (r.botState ? ' (' + r.botState + ')' : '') +
+        ' | queue ' + r.queuePending + ' | ' + r.actionsThisHour + ' actions/h' +
+        (r.incomingAttacks ? ' | ⚔️ ' + r.incomingAttacks : '') +
+        (r.lastError ? '\n  ⚠ ' + r.lastError : '');
+    }).join('\n');
+  }
+
+  var targets = resolveTelegramTargets(cmd.args[0]);
+  if (!targets) return 'Unknown server: ' + cmd.args[0];
+  if (targets.length === 0) return 'No active bots.';
+
+  if (cmd.command === 'screenshot') {
+    // captureVisibleTab needs <all_urls>, an optional permission granted when screenshots are enabled
+    var shotAllowed = cfg.screenshots && await chrome.permissions.contains({ origins: ['<all_urls>'] });
+    if (!shotAllowed) return 'Screenshots are off. Enable them in the Telegram settings.';
+    var shotInst = manager.get(targets[0]);
+    if (!shotInst || !shotInst.tabId) return 'No tab for ' + targets[0];
+    var shotTab = await chrome.tabs.get(shotInst.tabId).catch(function () { return null; });
+    if (!shotTab || !shotTab.active) return 'The game tab for ' + targets[0] + ' is not the visible tab in its window.';
+    var dataUrl = await chrome.tabs.captureVisibleTab(shotTab.windowId, { format: 'png' });
+    await self.TravianTelegram.sendPhoto(cfg, dataUrl, targets[0]);
+    return null;
+  }
+
+  var lines = [];
+  for (var i = 0; i < targets.length; i++) {
+    var key = targets[i];
+    var inst = manager.get(key);
+    var res;
+    if (cmd.command === 'stop') {
+      res = await dispatchInternal({ type: 'STOP_BOT', serverKey: key });
+    } else if (cmd.command === 'pause' || cmd.command === 'resume') {
+      // PAUSE_BOT toggles — only send it when the state actually needs to change
+      var wantPaused = cmd.command === 'pause';
+      if (!inst || !inst.engine.running || inst.engine.paused === wantPaused) {
+        lines.push(key + ': unchanged');
+        continue;
+      }
+      res = await dispatchInternal({ type: 'PAUSE_BOT', serverKey: key });
+    }
+    lines.push(key + ': ' + (res && res.success ? cmd.command + ' ok' : 'failed' + (res && res.error ? ' — ' + res.error : '')));
+  }
+  return lines.join('\n');
+}
+
+/**
+ * Measure the clock offset of every active server; warn and emit
+ * 'clock:drift' when the local clock is off or jumped.
+ */
+async function syncServerClocks() {
+  var active = manager.listActive();
+  for (var i = 0; i < active.length; i++) {
+    var key = active[i].serverKey;
+    var inst = manager.get(key);
+    try {
+      var gs = inst && inst.engine.gameState;
+      if (gs && gs.serverTime) {
+        await self.TravianServerClock.observeWallClock(key, gs.serverTime, gs.timestamp);
+      }
+      var measured = await self.TravianServerClock.measure(key);
+      measured.warnings.forEach(function (w) {
+        logger.warn(w);
+        if (inst && inst.engine.eventBus) {
+          inst.engine.eventBus.emit(self.TravianEventBus.Events.CLOCK_DRIFT, {
+            serverKey: key, offsetMs: measured.offsetMs, rttMs: measured.rttMs, message: w
+          });
+        }
+      });
+    } catch (err) {
+      logger.debug('Clock sync failed for ' + key + ': ' + err.message);
+    }
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Sleep/wake detection. Alarms don't fire while the computer sleeps, so a
+// gap far longer than the 1-minute heartbeat period between two alarms
+// means the system was asleep. The last alarm time lives in session
+// storage because the service worker itself is restarted freely. A bot
+// started since that alarm counts as one: its heartbeat was only just
+// armed, so the first beat is not a wake-up even if nothing else fired
+// for a while (e.g. only the 30-minute clock sync).
+// ---------------------------------------------------------------------------
+var WAKE_GAP_MS = 3 * 60000;
+var _wakeCheck = Promise.resolve();
+
+/** Record this alarm and resync if the previous one was long ago. Serialized: several alarms fire together on wake. */
+function checkForWake() {
+  _wakeCheck = _wakeCheck.then(async function () {
+    var now = Date.now();
+    var stored = await chrome.storage.session.get('lastAlarmAt');
+    await chrome.storage.session.set({ lastAlarmAt: now });
+    var last = stored.lastAlarmAt;
+    manager.listActive().forEach(function (r) {
+      var started = r.running && manager.get(r.serverKey).engine.stats.startTime;
+      if (last && started > last) last = started;
+    });
+    if (last && now - last >= WAKE_GAP_MS) await resyncAfterWake(now - last, last);
+  }).catch(function (err) {
+    logger.warn('Wake check failed: ' + err.message);
+  });
+  return _wakeCheck;
+}
+
+/**
+ * After system sleep: fail content-script requests issued before the
+ * sleep, mark running servers as re-syncing, rescan them, re-measure clocks
+ * and emit 'system:resumed'.
+ * @param {number} sleptMs
+ * @param {number} lastAlarmAt - Epoch ms of the last alarm before the sleep
+ */
+async function resyncAfterWake(sleptMs, lastAlarmAt) {
+  var running = manager.listActive().filter(function (r) { return r.running; });
+  if (running.length === 0) return;
+  logger.info('System resumed after ' + Math.round(sleptMs / 60000) + ' min asleep — re-syncing ' + running.length + ' server(s)');
+
+  // Requests sent before the sleep won't be answered in any useful way
+  var cutoff = Math.min(lastAlarmAt + 60000, Date.now() - 1000);
+  var Events = self.TravianEventBus.Events;
+  for (var i = 0; i < running.length; i++) {
+    var inst = manager.get(running[i].serverKey);
+    if (inst.engine._bridge) inst.engine._bridge.failPending('Interrupted by system sleep', cutoff);
+    inst.resyncingSince = Date.now();
+    inst.engine.eventBus.once(Events.SCAN_COMPLETE, (function (target) {
+      return function () { target.resyncingSince = null; };
+    })(inst));
+    inst.engine.eventBus.emit(Events.SYSTEM_RESUMED, { sleptMs: sleptMs, serverKey: inst.serverKey });
+    // Fresh state now; if this scan fails, the engine's own next scan clears the flag
+    var scanRes = await dispatchInternal({ type: 'REQUEST_SCAN', serverKey: inst.serverKey });
+    if (scanRes.success) inst.resyncingSince = null;
+    else logger.warn('Post-wake scan failed for ' + inst.serverKey + ': ' + scanRes.error);
+  }
+
+  await syncServerClocks();
+}
+
+// ---------------------------------------------------------------------------
+// Offline mode. While the connection is down every action fails, so running
+// bots are paused (once, not per failed task) and resumed with a
+// verification scan when it returns. Driven by the online/offline events,
+// with navigator.onLine re-checked on every alarm in case an event was
+// missed while the service worker was stopped. The outage start and the
+// bots paused for it live in session storage for the same reason.
+// Pausing, resuming or starting a bot by hand hands it back to the user:
+// it is then left alone when the connection returns.
+// ---------------------------------------------------------------------------
+var _offlineSince = null;
+var _networkPaused = [];  // server keys paused by checkNetwork()
+var _networkRestored = chrome.storage.session.get('networkState').then(function (stored) {
+  var st = stored.networkState;
+  if (st) {
+    _offlineSince = st.offlineSince;
+    _networkPaused = st.paused || [];
+  }
+}).catch(function (err) {
+  logger.warn('Could not restore network state: ' + err.message);
+});
+
+function saveNetworkState() {
+  return chrome.storage.session.set({ networkState: { offlineSince: _offlineSince, paused: _networkPaused } })
+    .catch(function (err) { logger.warn('Could not save network state: ' + err.message); });
+}
+
+/** @returns {boolean} True if the bot is paused only because the network is down */
+function isNetworkPaused(serverKey) {
+  return _networkPaused.indexOf(serverKey) !== -1;
+}
+
+/** The user paused/resumed/started this bot: don't auto-resume it when the network returns. */
+function clearNetworkPause(serverKey) {
+  return _networkRestored.then(function () {
+    if (!isNetworkPaused(serverKey)) return;
+    _networkPaused = _networkPaused.filter(function (k) { return k !== serverKey; });
+    return saveNetworkState();
+  });
+}
+
+/** Apply the current connectivity (navigator.onLine) to all instances. */
+async function checkNetwork() {
+  await _networkRestored;
+  var online = navigator.onLine !== false;
+  var Events = self.TravianEventBus.Events;
+
+  if (!online && _offlineSince === null) {
+    _offlineSince = Date.now();
+    manager.listActive().forEach(function (r) {
+      var inst = manager.get(r.serverKey);
+      if (r.running && !r.paused) {
+        inst.engine.pause();
+        _networkPaused.push(r.serverKey);
+      }
+      inst.engine.eventBus.emit(Events.NETWORK_OFFLINE, { serverKey: r.serverKey, since: _offlineSince });
+    });
+    await saveNetworkState();
+    logger.warn('Network offline — paused ' + _networkPaused.length + ' bot(s)');
+    return;
+  }
+
+  if (online && _offlineSince !== null) {
+    var offlineMs = Date.now() - _offlineSince;
+    var resumeKeys = _networkPaused;
+    _offlineSince = null;
+    _networkPaused = [];
+    await saveNetworkState();
+    logger.info('Network back after ' + Math.round(offlineMs / 1000) + 's — resuming up to ' + resumeKeys.length + ' bot(s)');
+    for (var i = 0; i < resumeKeys.length; i++) {
+      var inst = manager.get(resumeKeys[i]);
+      // Stopped, already resumed, waiting for a captcha or handed to the user meanwhile: leave it be
+      if (!inst || !inst.engine.running || !inst.engine.paused || inst.engine.captchaBlocked ||
+          inst.engine.manualMode) continue;
+      // Verify the game state first: the page may have logged out or moved on
+      var scanRes = await dispatchInternal({ type: 'REQUEST_SCAN', serverKey: inst.serverKey });
+      if (!scanRes.success) logger.warn('Verification scan failed for ' + inst.serverKey + ': ' + scanRes.error);
+      if (inst.engine.paused) inst.engine.resume();
+    }
+    manager.listActive().forEach(function (r) {
+      manager.get(r.serverKey).engine.eventBus.emit(Events.NETWORK_ONLINE, { serverKey: r.serverKey, offlineMs: offlineMs });
+    });
+  }
+}
+
+self.addEventListener('offline', function () { checkNetwork(); });
+self.addEventListener('online', function () { checkNetwork(); });
+
+/** @returns {Promise<{enabled: boolean, startMinimized: boolean}>} Global autostart settings */
+async function getAutostartConfig() {
+  var stored = await self.TravianStorage.get('autostart_config', {});
+  return { enabled: !!stored.enabled, startMinimized: stored.startMinimized !== false };
+}
+
+/**
+ * Open tabs for servers that were running before the browser closed.
+ * With startMinimized they go into one minimized window instead of
+ * background tabs in the current one.
+ * @returns {Promise<string[]>} Server keys opened
+ */
+async function autostartServers() {
+  var cfg = await getAutostartConfig();
+  if (!cfg.enabled) return [];
+
+  var openKeys = (await findAllTravianTabs()).map(function (t) {
+    return self.TravianStorage.extractServerKey(t.url);
+  });
+  var registry = await self.TravianStorage.getServerRegistry();
+  var toOpen = [];
+  for (var key in (registry.servers || {})) {
+    if (openKeys.indexOf(key) !== -1) continue;
+    var state = await self.TravianStorage.getServerState(key);
+    if (state && state.wasRunning) toOpen.push(key);
+  }
+  if (toOpen.length === 0) return [];
+
+  var urls = toOpen.map(function (k) { return 'https://' + k + '/dorf1.php'; });
+  if (cfg.startMinimized) {
+    await chrome.windows.create({ url: urls, state: 'minimized', focused: false });
+  } else {
+    for (var i = 0; i < urls.length; i++) await chrome.tabs.create({ url: urls[i], active: false });
+  }
+  toOpen.forEach(function (k) {
+    chrome.alarms.create('botHeartbeat__' + k, { periodInMinutes: 1 });
+  });
+  logger.info('Autostart: opened ' + toOpen.join(', '));
+  return toOpen;
+}
+
+/**
+ * Redraw the toolbar icon from the combined state of all bots. Debounced:
+ * called after every message and alarm, redraws at most once per second
+ * (and only when the state changed).
+ */
+var _iconTimer = null;
+function scheduleIconRefresh() {
+  if (_iconTimer) return;
+  _iconTimer = setTimeout(function () {
+    _iconTimer = null;
+    var rows = manager.listActive().map(function (r) {
+      var engine = manager.get(r.serverKey).engine;
+      var gs = engine.gameState;
+      return {
+        label: r.serverKey.split('.')[0],
+        running: r.running,
+        paused: r.paused,
+        manualMode: r.manualMode,
+        emergencyStopped: !!engine.emergencyStopped,
+        incomingAttacks: gs && Array.isArray(gs.incomingAttacks) ? gs.incomingAttacks.length : 0,
+        nextActionTime: engine.nextActionTime
+      };
+    });
+    syncCountdown().catch(function () {});
+    syncServerMenu();
+    manager.getAttackAlarms().then(function (alarms) {
+      return self.TravianActionIcon.show(self.TravianActionIcon.aggregate(rows, alarms.length), 'summary');
+    }).catch(function (err) {
+      logger.debug('Toolbar icon update failed: ' + err.message);
+    });
+  }, 1000);
+}
+
+/**
+ * Toolbar settings: { countdown: boolean, menu: { <section>: boolean } }
+ * countdown = badge countdown (see actionIcon.js); menu = which toolbar
+ * menu sections to show (see ACTION_MENU / MENU_DEFAULTS).
+ */
+async function getToolbarConfig() {
+  var stored = await self.TravianStorage.get('toolbar_config', {});
+  return {
+    countdown: !!stored.countdown,
+    menu: Object.assign({}, MENU_DEFAULTS, stored.menu || {})
+  };
+}
+
+/**
+ * What the badge countdown points at: the soonest incoming attack on any
+ * server, else the soonest next action of a running bot.
+ * @returns {{ at: number, attack: boolean }|null}
+ */
+function countdownTarget() {
+  var now = Date.now();
+  var attackAt = null, actionAt = null;
+  manager.listActive().forEach(function (r) {
+    var engine = manager.get(r.serverKey).engine;
+    var attacks = engine.gameState && Array.isArray(engine.gameState.incomingAttacks) ? engine.gameState.incomingAttacks : [];
+    attacks.forEach(function (a) {
+      if (a.arrivalTime > now && (attackAt === null || a.arrivalTime < attackAt)) attackAt = a.arrivalTime;
+    });
+    if (r.running && !r.paused && engine.nextActionTime > now && (actionAt === null || engine.nextActionTime < actionAt)) {
+      actionAt = engine.nextActionTime;
+    }
+  });
+  if (attackAt !== null) return { at: attackAt, attack: true };
+  if (actionAt !== null) return { at: actionAt, attack: false };
+  return null;
+}
+
+/**
+ * Start or stop the 1s badge countdown. The timer only runs while the
+ * setting is on and there is a target; it stops itself otherwise and is
+ * re-armed from scheduleIconRefresh().
+ */
+var _countdownTimer = null;
+var _countdownEnabled = null;   // cached toolbar_config.countdown
+async function syncCountdown() {
+  if (_countdownEnabled === null) _countdownEnabled = (await getToolbarConfig()).countdown;
+  if (!_countdownEnabled || _countdownTimer || !countdownTarget()) return;
+  _countdownTimer = setInterval(function () {
+    var target = _countdownEnabled ? countdownTarget() : null;
+    if (!target) {
+      clearInterval(_countdownTimer);
+      _countdownTimer = null;
+      manager._updateAlarmBadge(); // give the badge back to the alert count
+      return;
+    }
+    self.TravianActionIcon.showCountdown(target);
+  }, 1000);
+}
+
+/**
+ * Windows holding Travian tabs, and whether any of them is on screen.
+ * @returns {Promise<{ visible: boolean, windows: Array<{ id: number, state: string, tabs: number }> }>}
+ */
+async function getGameWindows() {
+  var gwTabs = await findAllTravianTabs();
+  var byWindow = {};
+  gwTabs.forEach(function (t) { byWindow[t.windowId] = (byWindow[t.windowId] || 0) + 1; });
+  var windows = [];
+  for (var id in byWindow) {
+    var win = await chrome.windows.get(Number(id)).catch(function () { return null; });
+    if (win) windows.push({ id: win.id, state: win.state, tabs: byWindow[id] });
+  }
+  return {
+    visible: windows.some(function (w) { return w.state !== 'minimized'; }),
+    windows: windows
+  };
+}
+
+/**
+ * Minimize or restore every window holding a Travian tab — e.g. bring the
+ * game up to solve a captcha, then tuck it away again. Bots keep running
+ * either way.
+ * @param {boolean} visible
+ * @returns {Promise<Object>} getGameWindows() afterwards
+ */
+async function setGameWindowsVisible(visible) {
+  var gw = await getGameWindows();
+  for (var i = 0; i < gw.windows.length; i++) {
+    await chrome.windows.update(gw.windows[i].id, visible ? { state: 'normal', focused: i === 0 } : { state: 'minimized' });
+  }
+  var after = await getGameWindows();
+  syncGameWindowsItem(after.visible);
+  return after;
+}
+
+/** Keep the "Show game windows" menu checkbox in line with reality. */
+function syncGameWindowsItem(visible) {
+  chrome.contextMenus.update('showGameWindows', { checked: visible }, function () {
+    void chrome.runtime.lastError; // menu not built yet
+  });
+}
+
+/**
+ * Open the mini status window (popup/hud.html), or close it if it is open.
+ * @returns {Promise<boolean>} True if the HUD is now open
+ */
+var HUD_URL = chrome.runtime.getURL('popup/hud.html');
+async function toggleHud() {
+  var hudTabs = await chrome.tabs.query({ url: HUD_URL });
+  if (hudTabs.length > 0) {
+    await chrome.windows.remove(hudTabs[0].windowId);
+    return false;
+  }
+  await chrome.windows.create({ url: HUD_URL, type: 'popup', width: 280, height: 180, focused: false });
+  return true;
+}
+
+/**
+ * Focus the detached logs window (popup/logs.html), opening it if needed.
+ * @param {string} [serverKey] - Preselect this server's logs
+ * @returns {Promise<number>} Window id
+ */
+var LOGS_URL = chrome.runtime.getURL('popup/logs.html');
+async function openLogsWindow(serverKey) {
+  var url = LOGS_URL + (serverKey ? '?server=' + encodeURIComponent(serverKey) : '');
+  var logsTabs = await chrome.tabs.query({ url: LOGS_URL + '*' });
+  if (logsTabs.length > 0) {
+    await chrome.tabs.update(logsTabs[0].id, { url: url });
+    await chrome.windows.update(logsTabs[0].windowId, { focused: true });
+    return logsTabs[0].windowId;
+  }
+  var logsWin = await chrome.windows.create({ url: url, type: 'popup', width: 760, height: 520 });
+  return logsWin.id;
+}
+
+/** Update downloaded by Chrome and waiting to be applied: { version, since } */
+var pendingUpdate = null;
+
+/**
+ * Apply a downloaded update cleanly: pause bots so no new task starts, let
+ * running actions finish (up to UPDATE_DRAIN_MS), save state with
+ * wasRunning intact, flush logs and history, then reload. Heartbeat
+ * recovery in init() restarts the bots on the new version. Bots the user
+ * had paused come back paused; captcha and manual control pauses are
+ * saved by saveState() itself. Sleep and network pauses are not kept —
+ * the heartbeat and the online check put those back if they still apply.
+ */
+var UPDATE_DRAIN_MS = 2 * 60000;
+async function applyPendingUpdate() {
+  if (!pendingUpdate) return;
+  var version = pendingUpdate.version;
+  notify('Updating', 'Installing version ' + version + ' — bots restart automatically');
+
+  // Which pauses were the user's, before the drain pauses everything
+  var userPaused = {};
+  manager.listActive().forEach(function (r) {
+    var inst = manager.get(r.serverKey);
+    userPaused[r.serverKey] = r.running && r.paused && !inst.humanizerPaused && !isNetworkPaused(r.serverKey) &&
+      !inst.engine.captchaBlocked && !inst.engine.manualMode;
+  });
+
+  var engines = (await pauseAndDrain(UPDATE_DRAIN_MS)).engines;
+  await Promise.allSettled(engines.map(function (e) {
+    return e.saveState({ paused: !!userPaused[e.serverKey] });
+  }));
+  await self.TravianHistoryStore.flush();
+  logger.info('Applying update ' + version + ' (' + engines.length + ' bot(s) will restart)');
+  await self.TravianLogger.flush();
+  chrome.runtime.reload();
+}
+
+/** Point the 'schedules' alarm at the earliest due job (or clear it). */
+async function syncScheduleAlarm() {
+  var next = await self.TravianSchedules.nextWake();
+  if (next) {
+    chrome.alarms.create('schedules', { when: Math.max(next, Date.now() + 1000) });
+  } else {
+    chrome.alarms.clear('schedules');
+  }
+}
+
+/**
+ * Execute one scheduled job.
+ * @param {Object} job - From TravianSchedules
+ * @returns {Promise<string>} Short result for lastResult / the event
+ * @throws {Error} If the action could not run
+ */
+async function runScheduledJob(job) {
+  var inst = manager.get(job.serverKey);
+
+  if (job.action === 'scan') {
+    var scanRes = await dispatchInternal({ type: 'REQUEST_SCAN', serverKey: job.serverKey });
+    if (!scanRes.success) throw new Error(scanRes.error || 'scan failed');
+    return 'scan requested';
+  }
+
+  if (!inst || !inst.engine.running) throw new Error('bot not running');
+
+  if (job.action === 'pause' || job.action === 'resume') {
+    var wantPaused = job.action === 'pause';
+    if (inst.engine.paused === wantPaused) return 'unchanged';
+    if (!wantPaused && inst.engine.captchaBlocked) throw new Error('paused for a captcha');
+    if (!wantPaused && inst.engine.manualMode) throw new Error('manual control is on');
+    clearNetworkPause(job.serverKey);
+    if (wantPaused) inst.engine.pause();
+    else inst.engine.resume();
+    return wantPaused ? 'paused' : 'resumed';
+  }
+
+  if (job.action === 'sendFarmLists') {
+    var gs = inst.engine.gameState;
+    var farmTaskId = inst.engine.taskQueue.add('send_farm', { farmListId: null }, 7,
+      (gs && gs.currentVillageId) || null);
+    if (!farmTaskId) return 'farm task already queued';
+    inst.engine.taskQueue.update(farmTaskId, { reason: 'Schedule: ' + (job.name || job.cron) });
+    return 'farm task queued';
+  }
+
+  throw new Error('unknown action ' + job.action);
+}
+
+/** Poll Telegram for commands and answer each one. */
+async function pollTelegram() {
+  var cfg = await self.TravianTelegram.getConfig();
+  if (!self.TravianTelegram.isReady(cfg) || !cfg.commandsEnabled) {
+    syncTelegramPollAlarm(cfg);
+    return;
+  }
+  var commands = await self.TravianTelegram.pollCommands(cfg);
+  for (var i = 0; i < commands.length; i++) {
+    logger.info('Telegram command: /' + commands[i].command + ' ' + commands[i].args.join(' '));
+    try {
+      var reply = await runTelegramCommand(commands[i], cfg);
+      if (reply) await self.TravianTelegram.send(cfg, reply);
+    } catch (err) {
+      await self.TravianTelegram.send(cfg, '/' + commands[i].command + ' failed: ' + err.message).catch(function () {});
+    }
+  }
+}
+
+// ---------------------------------------------------------------------------
+// 6. Message Handler (from popup, content scripts, etc.)
+// ---------------------------------------------------------------------------
+chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
+  handleMessage(message, sender, sendResponse).then(scheduleIconRefresh);
+
+  // CRITICAL: return true to keep sendResponse channel open for async work.
+  return true;
+});
+
+/**
+ * Handle one message. Also used for internal callers (remote commands) so
+ * they go through exactly the same code path as the popup.
+ * @param {object} message
+ * @param {object|null} sender - chrome.runtime MessageSender, null for internal calls
+ * @param {function(object)} sendResponse
+ * @returns {Promise<void>}
+ */
+function handleMessage(message, sender, sendResponse) {
+  return (async function () {
+    try {
+      var type = message ? message.type : null;
+      var data = message ? message.data : null;
+      var msgConfig = message ? message.config : null;
+      var serverKey = message ? message.serverKey : null;
+      logger.debug(LOG_TAG + ' onMessage: ' + type + (serverKey ? ' [' + serverKey + ']' : ''));
+
+      switch (type) {
+
+        // ---- List all server instances ----
+        case 'GET_SERVERS': {
+          var servers = manager.listActive();
+          var registry = await self.TravianStorage.getServerRegistry();
+          // Send registry.servers (the actual server map), not the full registry wrapper
+          var serverMap = (registry && registry.servers) ? registry.servers : {};
+          sendResponse({ success: true, data: { instances: servers, registry: serverMap } });
+          break;
+        }
+
+        // ---- Overview of every known server (main screen, one round-trip) ----
+        case 'GET_OVERVIEW': {
+          var ovRegistry = await self.TravianStorage.getServerRegistry();
+          var ovServers = (ovRegistry && ovRegistry.servers) ? ovRegistry.servers : {};
+          var ovKeys = Object.keys(ovServers);
+          manager.listActive().forEach(function (i) {
+            if (ovKeys.indexOf(i.serverKey) === -1) ovKeys.push(i.serverKey);
+          });
+          sendResponse({ success: true, data: {
+            generatedAt: Date.now(),
+            servers: ovKeys.map(function (key) {
+              return buildOverviewEntry(key, manager.get(key), ovServers[key]);
+            })
+          }});
+          break;
+        }
+
+        // ---- Status (per-server) ----
+        case 'GET_STATUS': {
+          var inst = resolveInstance(message, sender);
+          sendResponse({ success: true, data: await buildStatus(inst, serverKey) });
+          break;
+        }
+
+        // ---- Dashboard batch: status + logs (+ strategy) in one round-trip ----
+        case 'GET_DASHBOARD': {
+          var dashInst = resolveInstance(message, sender);
+          var dashboard = {
+            status: await buildStatus(dashInst, serverKey),
+            logs: self.TravianLogger.getLogs ? self.TravianLogger.getLogs(null, null, serverKey || null) : []
+          };
+          // Strategy analysis is expensive to render — popup asks for it on its own throttle
+          if (data && data.includeStrategy) {
+            dashboard.strategy = buildStrategy(dashInst);
+          }
+          sendResponse({ success: true, data: dashboard });
+          break;
+        }
+
+        // ---- Start Bot (per-server) ----
+        case 'START_BOT': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'No serverKey provided' });
+            break;
+          }
+          sendResponse(await startServer(serverKey));
+          break;
+        }
+
+        // ---- Fan-out controls (all servers) ----
+        // Each returns { results: { [serverKey]: { success, error? } } }
+        case 'START_ALL_BOTS': {
+          // Every known server that currently has an open tab
+          var saTabs = await findAllTravianTabs();
+          var saKeys = [];
+          for (var sa = 0; sa < saTabs.length; sa++) {
+            var saKey = self.TravianStorage.extractServerKey(saTabs[sa].url);
+            if (saKey && saKeys.indexOf(saKey) === -1) saKeys.push(saKey);
+          }
+          var saResults = await fanOut(saKeys, function (key) {
+            var existing = manager.get(key);
+            if (existing && existing.engine.running && !existing.engine.paused) {
+              return { success: true, skipped: 'already running' };
+            }
+            return startServer(key);
+          });
+          sendResponse({ success: true, data: { results: saResults } });
+          break;
+        }
+
+        case 'STOP_ALL_BOTS': {
+          var soKeys = manager.listActive()
+            .filter(function (i) { return i.running; })
+            .map(function (i) { return i.serverKey; });
+          if (data && data.graceful) {
+            // Answer now (draining can take minutes); a notification says when it's done
+            var soBusy = soKeys.filter(function (key) { return manager.get(key).engine._executionLocked; });
+            stopAllGracefully().catch(function (err) {
+              logger.error('Graceful stop failed: ' + err.message);
+            });
+            sendResponse({ success: true, data: { stopping: soKeys, busy: soBusy } });
+            break;
+          }
+          var soResults = await fanOut(soKeys, async function (key) {
+            await manager.get(key).engine.stop();
+            return { success: true };
+          });
+          if (soKeys.length > 0) notify('Stopped', 'Stopped ' + soKeys.length + ' bot(s)');
+          sendResponse({ success: true, data: { results: soResults } });
+          break;
+        }
+
+        case 'PAUSE_ALL_BOTS': {
+          // Pause only — never toggles a paused bot back on (unlike PAUSE_BOT)
+          var paKeys = manager.listActive()
+            .filter(function (i) { return i.running && !i.paused; })
+            .map(function (i) { return i.serverKey; });
+          var paResults = await fanOut(paKeys, function (key) {
+            manager.get(key).engine.pause();
+            return { success: true };
+          });
+          sendResponse({ success: true, data: { results: paResults } });
+          break;
+        }
+
+        // ---- Stop Bot (per-server) ----
+        case 'STOP_BOT': {
+          var stopInst = resolveInstance(message, sender);
+          if (stopInst && stopInst.engine.running) {
+            try {
+              await stopInst.engine.stop();
+              notify('Stopped', 'Bot stopped on ' + stopInst.serverKey);
+            } catch (err) {
+              console.error('[SW] Error during STOP_BOT:', err);
+              notify('Stopped', 'Bot stopped on ' + stopInst.serverKey + ' (with errors)');
+            }
+          }
+          sendResponse({ success: true, data: stopInst ? stopInst.engine.getStatus() : null });
+          break;
+        }
+
+        // ---- Pause / Resume (per-server) ----
+        case 'PAUSE_BOT': {
+          var pauseInst = resolveInstance(message, sender);
+          if (pauseInst && pauseInst.engine.captchaBlocked) {
+            sendResponse({ success: false, error: 'Paused for a captcha — solve it, then resume after captcha' });
+          } else if (pauseInst && pauseInst.engine.manualMode) {
+            sendResponse({ success: false, error: 'Manual control is on — hand control back to resume' });
+          } else if (pauseInst) {
+            clearNetworkPause(pauseInst.serverKey);
+            if (pauseInst.engine.paused) {
+              pauseInst.engine.resume();
+              sendResponse({ success: true, data: { paused: false } });
+            } else {
+              pauseInst.engine.pause();
+              sendResponse({ success: true, data: { paused: true } });
+            }
+          } else {
+            sendResponse({ success: false, error: 'No bot instance found' });
+          }
+          break;
+        }
+
+        // ---- Manual control: user takes over the game tab (per-server) ----
+        case 'SET_MANUAL_MODE': {
+          var mmInst = serverKey ? manager.getOrCreate(serverKey) : resolveInstance(message, sender);
+          if (!mmInst) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          var mmOn = !!(data && data.enabled);
+          mmInst.engine.setManualMode(mmOn);
+          logger.info('Manual control ' + (mmOn ? 'on' : 'off') + ' for ' + mmInst.serverKey);
+          if (mmOn) {
+            // Bring the game up so the user can play right away
+            try {
+              await setGameWindowsVisible(true);
+              await focusServerTab(mmInst.serverKey);
+            } catch (mmErr) {
+              logger.warn('Could not bring up game tab: ' + mmErr.message);
+            }
+          }
+          sendResponse({ success: true, data: { manualMode: mmInst.engine.manualMode } });
+          break;
+        }
+
+        // ---- Resume after the user solved a captcha (per-server) ----
+        case 'RESUME_AFTER_CAPTCHA': {
+          var capInst = resolveInstance(message, sender);
+          if (!capInst) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          try {
+            await capInst.engine.resumeAfterCaptcha();
+            self.TravianAlertCenter.acknowledgeAll(capInst.serverKey, 'captcha').catch(function () {});
+            logger.info('Resumed after captcha on ' + capInst.serverKey);
+            sendResponse({ success: true, data: { paused: false } });
+          } catch (capErr) {
+            sendResponse({ success: false, error: capErr.message });
+          }
+          break;
+        }
+
+        // ---- Emergency Stop (per-server) ----
+        case 'EMERGENCY_STOP': {
+          var emergInst = resolveInstance(message, sender);
+          var reason = (data && data.reason) || 'User triggered emergency stop';
+          if (emergInst) {
+            await emergInst.engine.emergencyStop(reason);
+            notify('EMERGENCY STOP', reason + ' (' + emergInst.serverKey + ')');
+            sendResponse({ success: true, data: emergInst.engine.getStatus() });
+          } else {
+            // Stop all as safety fallback
+            await manager.stopAll();
+            sendResponse({ success: true });
+          }
+          break;
+        }
+
+        // ---- Save Config (per-server) ----
+        case 'SAVE_CONFIG': {
+          var configData = msgConfig || data;
+          if (!configData) {
+            sendResponse({ success: false, error: 'No config data provided' });
+            break;
+          }
+          var cfgCheck = self.TravianConfigSchema.check(configData);
+          if (cfgCheck.errors.length > 0) {
+            logger.warn('Config rejected' + (serverKey ? ' for ' + serverKey : '') + ': ' +
+              cfgCheck.errors.map(function(e) { return e.path + ': ' + e.message; }).join('; '));
+            sendResponse({
+              success: false,
+              error: 'Invalid config (' + cfgCheck.errors.length + ' field' + (cfgCheck.errors.length === 1 ? '' : 's') + ')',
+              data: { errors: cfgCheck.errors, warnings: cfgCheck.warnings }
+            });
+            break;
+          }
+          var ruleErrors = self.TravianNotifier.checkRules(cfgCheck.config.notificationConfig);
+          if (ruleErrors.length > 0) {
+            sendResponse({ success: false, error: 'Invalid notification rules: ' + ruleErrors.join('; ') });
+            break;
+          }
+          configData = cfgCheck.config;
+          if (serverKey) {
+            await self.TravianStorage.saveServerConfig(serverKey, configData);
+            var cfgInst = manager.get(serverKey);
+            if (cfgInst) {
+              // Reload merged config from storage to preserve defaults
+              var mergedCfg = await self.TravianStorage.getServerConfig(serverKey);
+              cfgInst.engine.config = mergedCfg;
+            }
+          } else {
+            // Legacy fallback
+            await self.TravianStorage.set('bot_config', configData);
+          }
+          logger.info('Config saved' + (serverKey ? ' for ' + serverKey : ''));
+          sendResponse({ success: true, data: { warnings: cfgCheck.warnings } });
+          break;
+        }
+
+        // ---- Config Profiles (per-server) ----
+        case 'LIST_PROFILES': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          sendResponse({ success: true, data: await self.TravianStorage.listProfiles(serverKey) });
+          break;
+        }
+
+        case 'CREATE_PROFILE': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          try {
+            await self.TravianStorage.createProfile(serverKey, data && data.name, {
+              from: (data && data.from) || null,
+              overwrite: !!(data && data.overwrite)
+            });
+            sendResponse({ success: true, data: await self.TravianStorage.listProfiles(serverKey) });
+          } catch (profErr) {
+            sendResponse({ success: false, error: profErr.message });
+          }
+          break;
+        }
+
+        case 'SWITCH_PROFILE': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          try {
+            await self.TravianStorage.switchProfile(serverKey, data && data.name);
+            var spInst = manager.get(serverKey);
+            if (spInst) {
+              spInst.engine.config = await self.TravianStorage.getServerConfig(serverKey);
+            }
+            logger.info('Switched ' + serverKey + ' to profile "' + data.name + '"');
+            sendResponse({ success: true, data: await self.TravianStorage.listProfiles(serverKey) });
+          } catch (swErr) {
+            sendResponse({ success: false, error: swErr.message });
+          }
+          break;
+        }
+
+        case 'DELETE_PROFILE': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          var dpExisted = await self.TravianStorage.deleteProfile(serverKey, data && data.name);
+          sendResponse(dpExisted
+            ? { success: true, data: await self.TravianStorage.listProfiles(serverKey) }
+            : { success: false, error: 'Profile not found' });
+          break;
+        }
+
+        // ---- Config Bundles (all servers) ----
+        case 'EXPORT_CONFIG': {
+          sendResponse({ success: true, data: await self.TravianStorage.exportBundle() });
+          break;
+        }
+
+        case 'IMPORT_CONFIG': {
+          try {
+            var icResult = await self.TravianStorage.importBundle(data && data.bundle, {
+              serverKey: (data && data.serverKey) || serverKey || null
+            });
+            // Running engines pick up their imported config immediately
+            for (var ici = 0; ici < icResult.imported.length; ici++) {
+              var icInst = manager.get(icResult.imported[ici]);
+              if (icInst) icInst.engine.config = await self.TravianStorage.getServerConfig(icResult.imported[ici]);
+            }
+            logger.info('Config bundle imported: ' + icResult.imported.length + ' server(s), ' +
+              icResult.skipped.length + ' skipped');
+            sendResponse({ success: icResult.imported.length > 0 || icResult.skipped.length === 0, data: icResult });
+          } catch (icErr) {
+            sendResponse({ success: false, error: icErr.message });
+          }
+          break;
+        }
+
+        // ---- Logs ----
+        case 'GET_LOGS': {
+          // Pass serverKey to filter logs for the requesting server (backward compat: null = all)
+          var logServerKey = message.serverKey || null;
+          var logs = self.TravianLogger.getLogs ? self.TravianLogger.getLogs(null, null, logServerKey) : [];
+          sendResponse({ success: true, data: logs });
+          break;
+        }
+
+        case 'QUERY_LOGS': {
+          // Persistent history (IndexedDB): filter + paging, newest first
+          var qlFilter = Object.assign({}, message.filter || (data && data.filter) || {});
+          if (qlFilter.serverKey === undefined && message.serverKey) qlFilter.serverKey = message.serverKey;
+          try {
+            var qlResult = await self.TravianHistoryStore.queryLogs(qlFilter,
+              message.offset || (data && data.offset) || 0,
+              message.limit || (data && data.limit) || 100);
+            sendResponse({ success: true, data: qlResult });
+          } catch (qlErr) {
+            sendResponse({ success: false, error: 'Log history unavailable: ' + qlErr.message });
+          }
+          break;
+        }
+
+        case 'SEARCH_LOGS': {
+          // Full-text search over persisted logs, ranked, with highlights
+          var slQuery = message.query || (data && data.query);
+          var slFilter = Object.assign({}, message.filter || (data && data.filter) || {});
+          if (slFilter.serverKey === undefined && message.serverKey) slFilter.serverKey = message.serverKey;
+          try {
+            var slResult = await self.TravianHistoryStore.searchLogs(slQuery, slFilter,
+              message.limit || (data && data.limit) || 50);
+            sendResponse({ success: true, data: slResult });
+          } catch (slErr) {
+            sendResponse({ success: false, error: slErr.message });
+          }
+          break;
+        }
+
+        // ---- Activity windows (per-server) ----
+        case 'GET_ACTIVITY_PLAN': {
+          var apInst = resolveInstance(message, sender);
+          var apKey = (apInst && apInst.serverKey) || serverKey;
+          if (!apKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          var apCfg = apInst && apInst.engine.config
+            ? apInst.engine.config.activityConfig
+            : ((await self.TravianStorage.getServerConfig(apKey)) || {}).activityConfig;
+          var apPlan = self.TravianHumanizer.getPlan(apKey, apCfg, message.days || (data && data.days) || 7);
+          apPlan.pausedBySleep = !!(apInst && apInst.humanizerPaused);
+          sendResponse({ success: true, data: apPlan });
+          break;
+        }
+
+        // ---- Server time (per-server) ----
+        case 'GET_SERVER_TIME': {
+          var stKey = serverKey || (resolveInstance(message, sender) || {}).serverKey;
+          if (!stKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          if (message.refresh || (data && data.refresh)) {
+            try {
+              await self.TravianServerClock.measure(stKey);
+            } catch (stErr) {
+              sendResponse({ success: false, error: 'Clock measurement failed: ' + stErr.message });
+              break;
+            }
+          }
+          sendResponse({ success: true, data: await self.TravianServerClock.getServerTime(stKey) });
+          break;
+        }
+
+        // ---- Scheduled jobs (cron-style, global list) ----
+        case 'GET_SCHEDULES': {
+          var gsJobs = await self.TravianSchedules.list();
+          var gsKey = message.serverKey || (data && data.serverKey);
+          if (gsKey) gsJobs = gsJobs.filter(function (j) { return j.serverKey === gsKey; });
+          sendResponse({ success: true, data: { schedules: gsJobs, actions: self.TravianSchedules.ACTIONS } });
+          break;
+        }
+
+        case 'SAVE_SCHEDULE': {
+          var ssInput = Object.assign({}, message.schedule || (data && data.schedule) || {});
+          if (!ssInput.id && !ssInput.serverKey) ssInput.serverKey = serverKey;
+          try {
+            var ssSaved = await self.TravianSchedules.save(ssInput);
+            await syncScheduleAlarm();
+            sendResponse({ success: true, data: ssSaved });
+          } catch (ssErr) {
+            sendResponse({ success: false, error: ssErr.message });
+          }
+          break;
+        }
+
+        case 'DELETE_SCHEDULE': {
+          var dsId = message.id || (data && data.id);
+          var dsRemoved = await self.TravianSchedules.remove(dsId);
+          await syncScheduleAlarm();
+          sendResponse(dsRemoved ? { success: true } : { success: false, error: 'Unknown schedule: ' + dsId });
+          break;
+        }
+
+        // ---- Resource history (per-server) ----
+        case 'GET_RESOURCE_HISTORY': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          var rhInst = resolveInstance(message, sender);
+          var rhVillage = message.villageId || (data && data.villageId) ||
+            (rhInst && rhInst.engine.gameState && rhInst.engine.gameState.currentVillageId);
+          if (!rhVillage) {
+            sendResponse({ success: false, error: 'villageId required' });
+            break;
+          }
+          try {
+            var rhResult = await self.TravianHistoryStore.getResourceHistory(serverKey, rhVillage,
+              message.range || (data && data.range), message.resolution || (data && data.resolution));
+            rhResult.villageId = String(rhVillage);
+            sendResponse({ success: true, data: rhResult });
+          } catch (rhErr) {
+            sendResponse({ success: false, error: rhErr.message });
+          }
+          break;
+        }
+
+        // ---- Action timeline (per-server) ----
+        case 'GET_ACTION_TIMELINE': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          try {
+            sendResponse({ success: true, data: await self.TravianHistoryStore.getActionTimeline(serverKey,
+              message.filter || (data && data.filter),
+              message.offset || (data && data.offset) || 0,
+              message.limit || (data && data.limit) || 100) });
+          } catch (atlErr) {
+            sendResponse({ success: false, error: atlErr.message });
+          }
+          break;
+        }
+
+        // ---- Raid analytics (per-server) ----
+        case 'GET_RAID_STATS': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          try {
+            sendResponse({ success: true, data: await self.TravianHistoryStore.getRaidStats(serverKey,
+              message.range || (data && data.range), message.resolution || (data && data.resolution)) });
+          } catch (rsErr) {
+            sendResponse({ success: false, error: rsErr.message });
+          }
+          break;
+        }
+
+        case 'GET_FARM_RANKINGS': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          try {
+            sendResponse({ success: true, data: await self.TravianHistoryStore.getFarmRankings(serverKey,
+              message.options || data || {}) });
+          } catch (frErr) {
+            sendResponse({ success: false, error: frErr.message });
+          }
+          break;
+        }
+
+        // ---- Task Queue (per-server) ----
+        case 'GET_QUEUE': {
+          var qInst = resolveInstance(message, sender);
+          var queue = (qInst && qInst.engine.taskQueue) ? qInst.engine.taskQueue.getAll() : [];
+          sendResponse({ success: true, data: queue });
+          break;
+        }
+
+        case 'ADD_TASK': {
+          var atInst = resolveInstance(message, sender);
+          if (atInst && atInst.engine.taskQueue) {
+            var tType = message.taskType || (data && data.taskType);
+            var tParams = message.params || (data && data.params) || {};
+            var tPrio = message.priority || (data && data.priority) || 5;
+            var tVid = message.villageId || (data && data.villageId) || null;
+            
+            var atRegistry = atInst.engine._handlerRegistry;
+            var atErrors = !tType ? ['Missing taskType']
+              : atRegistry ? atRegistry.validateSpec({ type: tType, params: tParams, priority: tPrio, villageId: tVid })
+              : [];
+            if (atErrors.length > 0) {
+              sendResponse({ success: false, error: 'Invalid task: ' + atErrors.join('; '), data: { errors: atErrors } });
+            } else {
+              var newId = atInst.engine.taskQueue.add(tType, tParams, tPrio, tVid);
+              if (newId) atInst.engine.taskQueue.update(newId, { reason: 'manual' });
+              sendResponse(newId
+                ? { success: true, data: { taskId: newId } }
+                : { success: false, error: 'Duplicate task already queued' });
+            }
+          } else {
+            sendResponse({ success: false, error: 'No instance found' });
+          }
+          break;
+        }
+
+        case 'REMOVE_TASK': {
+          var rtInst = resolveInstance(message, sender);
+          var rTaskId = message.taskId || (data && data.taskId);
+          if (rtInst && rtInst.engine.taskQueue && rTaskId) {
+            var removed = rtInst.engine.taskQueue.remove(rTaskId);
+            sendResponse({ success: removed });
+          } else {
+            sendResponse({ success: false, error: 'No instance or taskId' });
+          }
+          break;
+        }
+
+        case 'REORDER_QUEUE': {
+          var roInst = resolveInstance(message, sender);
+          var roIds = message.taskIds || (data && data.taskIds);
+          if (roInst && roInst.engine.taskQueue && Array.isArray(roIds)) {
+            sendResponse(roInst.engine.taskQueue.reorder(roIds)
+              ? { success: true, data: { reordered: roIds.length } }
+              : { success: false, error: 'taskIds must list every pending task exactly once' });
+          } else {
+            sendResponse({ success: false, error: 'No instance or taskIds' });
+          }
+          break;
+        }
+
+        case 'PAUSE_TASK': {
+          var ptInst = resolveInstance(message, sender);
+          var pTaskId = message.taskId || (data && data.taskId);
+          if (ptInst && ptInst.engine.taskQueue && pTaskId) {
+            var ptPaused = !(data && data.paused === false);
+            var toggled = ptInst.engine.taskQueue.setPaused(pTaskId, ptPaused);
+            sendResponse(toggled
+              ? { success: true, data: { taskId: pTaskId, paused: ptPaused } }
+              : { success: false, error: 'Task not found or not ' + (ptPaused ? 'pending' : 'paused') });
+          } else {
+            sendResponse({ success: false, error: 'No instance or taskId' });
+          }
+          break;
+        }
+
+        case 'CLEAR_QUEUE': {
+          var cqInst = resolveInstance(message, sender);
+          if (cqInst && cqInst.engine.taskQueue) cqInst.engine.taskQueue.clear();
+          sendResponse({ success: true });
+          break;
+        }
+
+        case 'GET_ATTACK_ALARMS': {
+          sendResponse({ success: true, data: await manager.getAttackAlarms() });
+          break;
+        }
+
+        case 'ACKNOWLEDGE_ALARM': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          sendResponse({ success: await manager.acknowledgeAttackAlarm(serverKey) });
+          break;
+        }
+
+        case 'SNOOZE_ALARM': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          sendResponse({ success: await manager.snoozeAttackAlarm(serverKey, data && data.minutes) });
+          break;
+        }
+
+        // ---- Alert center (global; serverKey optional) ----
+        case 'GET_ALERTS': {
+          var gaFilter = Object.assign({}, message.filter || {});
+          if (gaFilter.serverKey === undefined && serverKey) gaFilter.serverKey = serverKey;
+          sendResponse({ success: true, data: await self.TravianAlertCenter.list(gaFilter, message.offset, message.limit) });
+          break;
+        }
+
+        case 'CAPTURE_SCREENSHOT': {
+          try {
+            var csShot = await captureScreenshot(serverKey || null, (data && data.target) || 'game', 'manual', null);
+            var csMeta = Object.assign({}, csShot);
+            delete csMeta.dataUrl;
+            csMeta.bytes = csShot.dataUrl.length;
+            sendResponse({ success: true, data: csMeta });
+          } catch (csErr) {
+            sendResponse({ success: false, error: csErr.message });
+          }
+          break;
+        }
+
+        case 'GET_SCREENSHOTS': {
+          var gsFilter = Object.assign({}, message.filter || {});
+          if (gsFilter.serverKey === undefined && serverKey) gsFilter.serverKey = serverKey;
+          sendResponse({ success: true, data: await self.TravianHistoryStore.queryScreenshots(gsFilter, message.offset, message.limit) });
+          break;
+        }
+
+        case 'GET_SCREENSHOT': {
+          var gShot = await self.TravianHistoryStore.getScreenshot(Number(message.screenshotId));
+          sendResponse(gShot ? { success: true, data: gShot } : { success: false, error: 'Unknown screenshot: ' + message.screenshotId });
+          break;
+        }
+
+        case 'ACK_ALERT': {
+          var akAlert = await acknowledgeAlert(message.alertId);
+          sendResponse(akAlert ? { success: true, data: akAlert } : { success: false, error: 'Unknown alert: ' + message.alertId });
+          break;
+        }
+
+        case 'ACK_ALL_ALERTS': {
+          var aaAcked = await self.TravianAlertCenter.acknowledgeAll(serverKey || undefined);
+          aaAcked.forEach(function (a) {
+            if (a.kind === 'attack') manager.acknowledgeAttackAlarm(a.serverKey);
+          });
+          sendResponse({ success: true, data: { acknowledged: aaAcked.length } });
+          break;
+        }
 
-      switch (type) {
+        // ---- Detached windows (global) ----
+        case 'GET_GAME_WINDOWS': {
+          sendResponse({ success: true, data: await getGameWindows() });
+          break;
+        }
 
-        // ---- List all server instances ----
-        case 'GET_SERVERS': {
-          var servers = manager.listActive();
-          var registry = await self.TravianStorage.getServerRegistry();
-          // Send registry.servers (the actual server map), not the full registry wrapper
-          var serverMap = (registry && registry.servers) ? registry.servers : {};
-          sendResponse({ success: true, data: { instances: servers, registry: serverMap } });
+        case 'SET_GAME_WINDOWS_VISIBLE': {
+          var gwVisible = data && data.visible !== undefined
+            ? !!data.visible
+            : !(await getGameWindows()).visible; // no value → toggle
+          sendResponse({ success: true, data: await setGameWindowsVisible(gwVisible) });
           break;
         }
 
-        // ---- Status (per-server) ----
-        case 'GET_STATUS': {
-          var inst = resolveInstance(message, sender);
-          if (inst) {
-            var status = inst.engine.getStatus();
-            status.activeTabId = inst.tabId;
-            sendResponse({ success: true, data: status });
-          } else {
-            // No instance for this server yet — load saved config from storage
-            var savedConfig = null;
-            if (serverKey) {
-              try {
-                savedConfig = await self.TravianStorage.getServerConfig(serverKey);
-              } catch (e) { console.warn(LOG_TAG, 'Failed to load config for idle status:', e); }
-            }
-            sendResponse({
-              success: true,
-              data: {
-                running: false, paused: false, emergencyStopped: false,
-                activeTabId: null, serverKey: serverKey,
-                stats: { tasksCompleted: 0, tasksFailed: 0, startTime: null, lastAction: null, farmRaidsSent: 0 },
-                actionsThisHour: 0, taskQueue: { total: 0, pending: 0, tasks: [] },
-                gameState: null, config: savedConfig, nextActionTime: null
-              }
-            });
+        case 'TOGGLE_HUD': {
+          var hudOpen = await toggleHud();
+          sendResponse({ success: true, data: { open: hudOpen } });
+          break;
+        }
+
+        case 'OPEN_LOGS_WINDOW': {
+          var olwId = await openLogsWindow(message.serverKey);
+          sendResponse({ success: true, data: { windowId: olwId } });
+          break;
+        }
+
+        // ---- Map data (map.sql snapshots) ----
+        case 'REFRESH_MAP': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          try {
+            sendResponse({ success: true, data: await self.TravianMapStore.refresh(serverKey, { force: !!(data && data.force) }) });
+          } catch (rmErr) {
+            sendResponse({ success: false, error: rmErr.message });
           }
           break;
         }
 
-        // ---- Start Bot (per-server) ----
-        case 'START_BOT': {
+        case 'QUERY_MAP': {
           if (!serverKey) {
-            sendResponse({ success: false, error: 'No serverKey provided' });
+            sendResponse({ success: false, error: 'serverKey required' });
             break;
           }
-          var startInst = manager.getOrCreate(serverKey);
+          try {
+            sendResponse({ success: true, data: await self.TravianMapStore.query(serverKey, message.filter || {}) });
+          } catch (qmErr) {
+            sendResponse({ success: false, error: qmErr.message });
+          }
+          break;
+        }
 
-          if (startInst.engine.running && !startInst.engine.paused) {
-            sendResponse({ success: false, error: 'Bot is already running for ' + serverKey });
+        case 'FIND_FARMS': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
             break;
           }
+          var ffInst = resolveInstance(message, sender);
+          var ffGs = ffInst && ffInst.engine.gameState;
+          var ffCenter = message.center || await defaultMapCenter(ffInst, serverKey);
+          var ffFilters = Object.assign({}, message.filters || {});
+          if (ffGs && ffGs.myUserId) ffFilters.excludeUids = (ffFilters.excludeUids || []).concat([ffGs.myUserId]);
+          // Targets the farm intelligence gave up on stay out
+          var ffIntel = ffInst && ffInst.engine._farmIntelligence;
+          if (ffIntel && ffIntel._targets) {
+            var ffBlacklisted = Object.keys(ffIntel._targets).filter(function(k) {
+              return ffIntel._targets[k].status === 'blacklisted';
+            });
+            ffFilters.excludeCoords = (ffFilters.excludeCoords || []).concat(ffBlacklisted);
+          }
+          try {
+            sendResponse({ success: true, data: await self.TravianFarmFinder.findFarms(serverKey,
+              ffCenter ? { x: Number(ffCenter.x), y: Number(ffCenter.y) } : null, message.radius, ffFilters) });
+          } catch (ffErr) {
+            sendResponse({ success: false, error: ffErr.message });
+          }
+          break;
+        }
 
-          // Find the tab for this server
-          var startTab = null;
-          if (startInst.tabId) {
-            startTab = await chrome.tabs.get(startInst.tabId).catch(function () { return null; });
+        case 'CALC_TRAVEL_TIME': {
+          var cttInst = resolveInstance(message, sender);
+          var cttCfg = (cttInst && cttInst.engine.config) ||
+            (serverKey ? await self.TravianStorage.getServerConfig(serverKey) : null) || {};
+          var cttMeta = serverKey ? await self.TravianMapStore.getMeta(serverKey) : null;
+          var cttFrom = message.from || (serverKey ? await defaultMapCenter(cttInst, serverKey) : null);
+          try {
+            sendResponse({ success: true, data: self.TravianTravelTime.calcTravelTime(
+              cttFrom ? { x: Number(cttFrom.x), y: Number(cttFrom.y) } : null,
+              message.to ? { x: Number(message.to.x), y: Number(message.to.y) } : null,
+              message.unit, message.tribe || cttCfg.tribe, message.speedArtifact, message.ts, {
+                serverSpeed: message.serverSpeed || cttCfg.serverSpeed || 1,
+                boots: message.boots,
+                mapRadius: cttMeta ? cttMeta.mapRadius : 200,
+                departAt: message.departAt
+              }) });
+          } catch (cttErr) {
+            sendResponse({ success: false, error: cttErr.message });
           }
-          if (!startTab) {
-            // Search all Travian tabs for one matching this server
-            var allTabs = await findAllTravianTabs();
-            for (var t = 0; t < allTabs.length; t++) {
-              var tabKey = self.TravianStorage.extractServerKey(allTabs[t].url);
-              if (tabKey === serverKey) {
-                startTab = allTabs[t];
-                break;
-              }
-            }
+          break;
+        }
+
+        case 'SIMULATE_BATTLE': {
+          var sbAttacker = Object.assign({}, message.attacker || {});
+          if (!sbAttacker.tribe && serverKey) {
+            var sbInst = resolveInstance(message, sender);
+            var sbCfg = (sbInst && sbInst.engine.config) || await self.TravianStorage.getServerConfig(serverKey) || {};
+            sbAttacker.tribe = sbCfg.tribe;
           }
-          if (!startTab) {
-            sendResponse({ success: false, error: 'No tab found for server ' + serverKey + '. Open it first.' });
+          try {
+            sendResponse({ success: true, data: self.TravianCombat.simulateBattle(sbAttacker, message.defender || {}) });
+          } catch (sbErr) {
+            sendResponse({ success: false, error: sbErr.message });
+          }
+          break;
+        }
+
+        case 'FIND_CROPPERS': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
             break;
           }
+          var fcInst = resolveInstance(message, sender);
+          var fcCenter = message.center || await defaultMapCenter(fcInst, serverKey);
+          var fcCfg = (fcInst && fcInst.engine.config) || await self.TravianStorage.getServerConfig(serverKey) || {};
+          var fcMeta = await self.TravianMapStore.getMeta(serverKey);
+          try {
+            sendResponse({ success: true, data: await self.TravianCropperFinder.findCroppers(serverKey,
+              fcCenter ? { x: Number(fcCenter.x), y: Number(fcCenter.y) } : null, message.radius, message.cropperType, {
+                cookieHeader: await buildCookieHeader(serverKey),
+                serverSpeed: fcCfg.serverSpeed || 1,
+                mapRadius: fcMeta ? fcMeta.mapRadius : 200
+              }) });
+          } catch (fcErr) {
+            sendResponse({ success: false, error: fcErr.message });
+          }
+          break;
+        }
 
-          startInst.tabId = startTab.id;
-          startInst.engine.activeTabId = startTab.id;
+        case 'ADD_FARM_TARGETS': {
+          var aftTargets = Array.isArray(message.targets) ? message.targets.filter(function(t) {
+            return t && typeof t.x === 'number' && typeof t.y === 'number';
+          }) : [];
+          if (!serverKey || aftTargets.length === 0) {
+            sendResponse({ success: false, error: 'serverKey and targets [{x, y}] required' });
+            break;
+          }
+          var aftInst = resolveInstance(message, sender) || manager.getOrCreate(serverKey);
+          if (aftInst.engine.manualMode) {
+            sendResponse({ success: false, error: 'Manual control is on — hand control back first' });
+            break;
+          }
+          var aftOrigin = await resolveServerOrigin(aftInst, serverKey);
+          if (!aftOrigin) {
+            sendResponse({ success: false, error: 'No active Travian tab found. Open a Travian tab for this server first.' });
+            break;
+          }
+          var aftTroops = message.troops;
+          if (!aftTroops) {
+            var aftCfg = ((aftInst.engine.config || await self.TravianStorage.getServerConfig(serverKey) || {}).farmConfig) || {};
+            aftTroops = {};
+            aftTroops[aftCfg.scanTroopSlot || 't1'] = aftCfg.scanTroopCount || 1;
+          }
+          try {
+            var aftRes = await addFarmListTargets(aftInst.tabId, aftOrigin, aftTargets, aftTroops);
+            aftRes.message = aftRes.full
+              ? 'Farm list full (' + aftRes.existing + '/' + FARM_LIST_MAX_SLOTS + ')'
+              : 'Added ' + aftRes.added + ' of ' + aftTargets.length + (aftRes.failed > 0 ? ' (' + aftRes.failed + ' failed)' : '');
+            logger.info('[FarmFinder] ' + serverKey + ': ' + aftRes.message);
+            sendResponse({ success: true, data: aftRes });
+          } catch (aftErr) {
+            sendResponse({ success: false, error: aftErr.message });
+          }
+          break;
+        }
 
-          // Resume if paused, start fresh otherwise
-          if (startInst.engine.running && startInst.engine.paused) {
-            startInst.engine.resume();
-          } else {
-            await startInst.engine.start(startTab.id);
+        // ---- Extension updates ----
+        case 'CHECK_FOR_UPDATES': {
+          var cuCurrent = chrome.runtime.getManifest().version;
+          if (pendingUpdate) {
+            sendResponse({ success: true, data: { status: 'update_pending', version: pendingUpdate.version, currentVersion: cuCurrent } });
+            break;
           }
+          try {
+            // Resolves 'throttled' | 'no_update' | 'update_available'; a found
+            // update is downloaded, then onUpdateAvailable applies it
+            var cuResult = await chrome.runtime.requestUpdateCheck();
+            sendResponse({ success: true, data: { status: cuResult.status, version: cuResult.version || null, currentVersion: cuCurrent } });
+          } catch (cuErr) {
+            // Unpacked / self-managed installs have no update URL
+            sendResponse({ success: false, error: 'Update check unavailable: ' + cuErr.message });
+          }
+          break;
+        }
 
-          notify('Started', 'Bot running on ' + serverKey);
-          sendResponse({ success: true, data: startInst.engine.getStatus() });
+        // ---- Autostart (global) ----
+        case 'GET_AUTOSTART': {
+          sendResponse({ success: true, data: await getAutostartConfig() });
           break;
         }
 
-        // ---- Stop Bot (per-server) ----
-        case 'STOP_BOT': {
-          var stopInst = resolveInstance(message, sender);
-          if (stopInst && stopInst.engine.running) {
-            try {
-              await stopInst.engine.stop();
-              notify('Stopped', 'Bot stopped on ' + stopInst.serverKey);
-            } catch (err) {
-              console.error('[SW] Error during STOP_BOT:', err);
-              notify('Stopped', 'Bot stopped on ' + stopInst.serverKey + ' (with errors)');
-            }
-          }
-          sendResponse({ success: true, data: stopInst ? stopInst.engine.getStatus() : null });
+        case 'SET_AUTOSTART': {
+          var asPatch = data || message;
+          var asNext = await getAutostartConfig();
+          if (asPatch.enabled !== undefined) asNext.enabled = !!asPatch.enabled;
+          if (asPatch.startMinimized !== undefined) asNext.startMinimized = !!asPatch.startMinimized;
+          await self.TravianStorage.set('autostart_config', asNext);
+          sendResponse({ success: true, data: asNext });
           break;
         }
 
-        // ---- Pause / Resume (per-server) ----
-        case 'PAUSE_BOT': {
-          var pauseInst = resolveInstance(message, sender);
-          if (pauseInst) {
-            if (pauseInst.engine.paused) {
-              pauseInst.engine.resume();
-              sendResponse({ success: true, data: { paused: false } });
-            } else {
-              pauseInst.engine.pause();
-              sendResponse({ success: true, data: { paused: true } });
-            }
-          } else {
-            sendResponse({ success: false, error: 'No bot instance found' });
+        // ---- Toolbar (global) ----
+        case 'GET_TOOLBAR_CONFIG': {
+          sendResponse({ success: true, data: await getToolbarConfig() });
+          break;
+        }
+
+        case 'SET_TOOLBAR_CONFIG': {
+          var tbPatch = data || {};
+          var tbUnknown = Object.keys(tbPatch.menu || {}).filter(function (k) { return !(k in MENU_DEFAULTS); });
+          if (tbUnknown.length > 0) {
+            sendResponse({ success: false, error: 'Unknown menu section(s): ' + tbUnknown.join(', ') });
+            break;
           }
+          var tbNext = await getToolbarConfig();
+          if (tbPatch.countdown !== undefined) tbNext.countdown = !!tbPatch.countdown;
+          Object.keys(tbPatch.menu || {}).forEach(function (k) { tbNext.menu[k] = !!tbPatch.menu[k]; });
+          await self.TravianStorage.set('toolbar_config', tbNext);
+          _countdownEnabled = tbNext.countdown;
+          await syncCountdown();
+          if (tbPatch.menu) await buildActionMenu();
+          sendResponse({ success: true, data: tbNext });
           break;
         }
 
-        // ---- Emergency Stop (per-server) ----
-        case 'EMERGENCY_STOP': {
-          var emergInst = resolveInstance(message, sender);
-          var reason = (data && data.reason) || 'User triggered emergency stop';
-          if (emergInst) {
-            await emergInst.engine.emergencyStop(reason);
-            notify('EMERGENCY STOP', reason + ' (' + emergInst.serverKey + ')');
-            sendResponse({ success: true, data: emergInst.engine.getStatus() });
-          } else {
-            // Stop all as safety fallback
-            await manager.stopAll();
+        // ---- Telegram (global) ----
+        case 'GET_TELEGRAM_CONFIG': {
+          var tgCfg = await self.TravianTelegram.getConfig();
+          sendResponse({ success: true, data: self.TravianTelegram.redact(tgCfg) });
+          break;
+        }
+
+        case 'SAVE_TELEGRAM_CONFIG': {
+          var tgSaved = await self.TravianTelegram.saveConfig(data || {});
+          syncTelegramPollAlarm(tgSaved);
+          sendResponse({ success: true, data: self.TravianTelegram.redact(tgSaved) });
+          break;
+        }
+
+        case 'TEST_TELEGRAM': {
+          var tgTest = await self.TravianTelegram.getConfig();
+          if (!tgTest.botToken || !tgTest.chatId) {
+            sendResponse({ success: false, error: 'Bot token and chat ID required' });
+            break;
+          }
+          try {
+            await self.TravianTelegram.send(tgTest, '🔔 Travian Assistant connected.\n' + self.TravianTelegram.HELP_TEXT);
             sendResponse({ success: true });
+          } catch (tgErr) {
+            sendResponse({ success: false, error: tgErr.message });
           }
           break;
         }
 
-        // ---- Save Config (per-server) ----
-        case 'SAVE_CONFIG': {
-          var configData = msgConfig || data;
-          if (!configData) {
-            sendResponse({ success: false, error: 'No config data provided' });
-            break;
+        // ---- Discord (global) ----
+        case 'GET_DISCORD_CONFIG': {
+          var dcCfg = await self.TravianDiscord.getConfig();
+          sendResponse({ success: true, data: Object.assign(self.TravianDiscord.redact(dcCfg), {
+            rate: self.TravianDiscord.getRateStatus()
+          }) });
+          break;
+        }
+
+        case 'SAVE_DISCORD_CONFIG': {
+          try {
+            var dcSaved = await self.TravianDiscord.saveConfig(data || {});
+            sendResponse({ success: true, data: self.TravianDiscord.redact(dcSaved) });
+          } catch (dcErr) {
+            sendResponse({ success: false, error: dcErr.message });
           }
-          if (serverKey) {
-            await self.TravianStorage.saveServerConfig(serverKey, configData);
-            var cfgInst = manager.get(serverKey);
-            if (cfgInst) {
-              // Reload merged config from storage to preserve defaults
-              var mergedCfg = await self.TravianStorage.getServerConfig(serverKey);
-              cfgInst.engine.config = mergedCfg;
-            }
-          } else {
-            // Legacy fallback
-            await self.TravianStorage.set('bot_config', configData);
+          break;
+        }
+
+        case 'TEST_DISCORD': {
+          try {
+            var dcPosted = await self.TravianDiscord.sendTest(await self.TravianDiscord.getConfig());
+            sendResponse(dcPosted ? { success: true } : { success: false, error: 'Rate limited — try again shortly' });
+          } catch (dcTestErr) {
+            sendResponse({ success: false, error: dcTestErr.message });
           }
-          logger.info('Config saved' + (serverKey ? ' for ' + serverKey : ''));
-          sendResponse({ success: true });
           break;
         }
 
-        // ---- Logs ----
-        case 'GET_LOGS': {
-          // Pass serverKey to filter logs for the requesting server (backward compat: null = all)
-          var logServerKey = message.serverKey || null;
-          var logs = self.TravianLogger.getLogs ? self.TravianLogger.getLogs(null, null, logServerKey) : [];
-          sendResponse({ success: true, data: logs });
+        // ---- Generic webhook (global) ----
+        case 'GET_WEBHOOK_CONFIG': {
+          sendResponse({ success: true, data: Object.assign(self.TravianWebhook.redact(await self.TravianWebhook.getConfig()), {
+            availableEvents: self.TravianWebhook.EVENTS,
+            stats: self.TravianWebhook.getStats()
+          }) });
           break;
         }
 
-        // ---- Task Queue (per-server) ----
-        case 'GET_QUEUE': {
-          var qInst = resolveInstance(message, sender);
-          var queue = (qInst && qInst.engine.taskQueue) ? qInst.engine.taskQueue.getAll() : [];
-          sendResponse({ success: true, data: queue });
+        case 'SAVE_WEBHOOK_CONFIG': {
+          try {
+            var whSaved = await self.TravianWebhook.saveConfig(data || {});
+            sendResponse({ success: true, data: self.TravianWebhook.redact(whSaved) });
+          } catch (whErr) {
+            sendResponse({ success: false, error: whErr.message });
+          }
           break;
         }
 
-        case 'ADD_TASK': {
-          var atInst = resolveInstance(message, sender);
-          if (atInst && atInst.engine.taskQueue) {
-            var tType = message.taskType || (data && data.taskType);
-            var tParams = message.params || (data && data.params) || {};
-            var tPrio = message.priority || (data && data.priority) || 5;
-            var tVid = message.villageId || (data && data.villageId) || null;
-            
-            if (tType) {
-              var newId = atInst.engine.taskQueue.add(tType, tParams, tPrio, tVid);
-              sendResponse({ success: true, data: { taskId: newId } });
-            } else {
-              sendResponse({ success: false, error: 'Missing taskType' });
-            }
-          } else {
-            sendResponse({ success: false, error: 'No instance found' });
+        // ---- Notification channels (global) ----
+        case 'GET_NOTIFIERS': {
+          sendResponse({ success: true, data: {
+            channels: self.TravianNotifier.listChannels(),
+            severities: self.TravianNotifier.SEVERITIES,
+            kindSeverity: self.TravianNotifier.KIND_SEVERITY,
+            defaultRoutes: self.TravianNotifier.DEFAULT_ROUTES,
+            defaultThresholds: self.TravianNotifier.DEFAULT_THRESHOLDS
+          } });
+          break;
+        }
+
+        case 'GET_NOTIFICATIONS_SNAPSHOT': {
+          // Per-server: which alert kinds reach which channels now, and when
+          // quiet hours end for the ones that don't
+          var nsInst = resolveInstance(message, sender);
+          var nsKey = (nsInst && nsInst.serverKey) || serverKey;
+          if (!nsKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
           }
+          var nsConfig = nsInst && nsInst.engine.config
+            ? nsInst.engine.config
+            : (await self.TravianStorage.getServerConfig(nsKey)) || {};
+          sendResponse({ success: true, data: self.TravianNotifier.snapshot(nsConfig.notificationConfig) });
           break;
         }
 
-        case 'REMOVE_TASK': {
-          var rtInst = resolveInstance(message, sender);
-          var rTaskId = message.taskId || (data && data.taskId);
-          if (rtInst && rtInst.engine.taskQueue && rTaskId) {
-            var removed = rtInst.engine.taskQueue.remove(rTaskId);
-            sendResponse({ success: removed });
-          } else {
-            sendResponse({ success: false, error: 'No instance or taskId' });
+        case 'TEST_NOTIFIER': {
+          var tnChannel = message.channel || (data && data.channel);
+          try {
+            var tnSent = await self.TravianNotifier.test(tnChannel);
+            sendResponse(tnSent === false
+              ? { success: false, error: tnChannel + ' did not send (rate limited?)' }
+              : { success: true });
+          } catch (tnErr) {
+            sendResponse({ success: false, error: tnErr.message });
           }
           break;
         }
 
-        case 'CLEAR_QUEUE': {
-          var cqInst = resolveInstance(message, sender);
-          if (cqInst && cqInst.engine.taskQueue) cqInst.engine.taskQueue.clear();
+        case 'TEST_NOTIFICATION': {
+          self.TravianInstanceManager.notify('test',
+            '🔔 Test notification' + (serverKey ? ' [' + serverKey.split('.')[0] + ']' : ''),
+            'Desktop notifications are working.', false);
           sendResponse({ success: true });
           break;
         }
 
+        // ---- Session Health (per-server) ----
+        case 'GET_SESSION_HEALTH': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          sendResponse({ success: true, data: await buildSessionHealth(serverKey, resolveInstance(message, sender)) });
+          break;
+        }
+
         // ---- Strategy Analysis (per-server) ----
         case 'GET_STRATEGY': {
           var strInst = resolveInstance(message, sender);
-          var analysis = (strInst && strInst.engine.decisionEngine) ? strInst.engine.decisionEngine.getLastAnalysis() : null;
-          var phase = (strInst && strInst.engine.decisionEngine) ? strInst.engine.decisionEngine.getPhase() : 'unknown';
-          var plannerState = (strInst && strInst.engine.decisionEngine) ? strInst.engine.decisionEngine.getPlannerState() : null;
-          sendResponse({ success: true, data: { analysis: analysis, phase: phase, planner: plannerState } });
+          sendResponse({ success: true, data: buildStrategy(strInst) });
+          break;
+        }
+
+        case 'GET_STRATEGY_PRESETS': {
+          sendResponse({ success: true, data: self.TravianGlobalPlanner.listPresets() });
+          break;
+        }
+
+        case 'SET_STRATEGY': {
+          if (!serverKey) {
+            sendResponse({ success: false, error: 'serverKey required' });
+            break;
+          }
+          // Typed check (unknown fields, types, ranges, mode enum) before anything is stored
+          var ssCheck = self.TravianConfigSchema.validateStrategy(message.strategy || data);
+          var ssStrategy = ssCheck.strategy;
+          var ssErrors = ssCheck.errors;
+          // Plan names come from the planner's templates, not the static schema
+          if (ssStrategy.plan && !self.TravianGlobalPlanner.listPresets().plans.some(function(p) { return p.name === ssStrategy.plan; })) {
+            ssErrors.push('plan: unknown plan "' + ssStrategy.plan + '"');
+          }
+          if (ssErrors.length > 0) {
+            sendResponse({ success: false, error: ssErrors.join('; '), data: { errors: ssErrors } });
+            break;
+          }
+          var ssConfig = await self.TravianStorage.getServerConfig(serverKey);
+          var ssPinned = Object.assign({ mode: 'auto', plan: null }, ssConfig.strategyConfig || {});
+          if ('mode' in ssStrategy) ssPinned.mode = ssStrategy.mode;
+          if ('plan' in ssStrategy) ssPinned.plan = ssStrategy.plan;
+          var ssUpdate = { strategyConfig: ssPinned };
+          if (ssStrategy.farm) ssUpdate.farmConfig = ssStrategy.farm;
+          if (ssStrategy.troops) ssUpdate.troopConfig = ssStrategy.troops;
+          await self.TravianStorage.saveServerConfig(serverKey, ssUpdate);
+          var ssInst = manager.get(serverKey);
+          if (ssInst) {
+            ssInst.engine.config = await self.TravianStorage.getServerConfig(serverKey);
+          }
+          logger.info('Strategy set for ' + serverKey + ': mode=' + ssPinned.mode + ' plan=' + (ssPinned.plan || 'auto'));
+          sendResponse({ success: true, data: ssUpdate });
           break;
         }
 
@@ -737,17 +2673,7 @@ chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
             } catch (_) {}
           }
 
-          // Discover Travian tab if not set (same pattern as START_BOT)
-          if (!scanFarmInst.tabId) {
-            var scanTabs = await findAllTravianTabs();
-            for (var st = 0; st < scanTabs.length; st++) {
-              if (self.TravianStorage.extractServerKey(scanTabs[st].url) === serverKey) {
-                scanFarmInst.tabId = scanTabs[st].id;
-                scanFarmInst.engine.activeTabId = scanTabs[st].id;
-                break;
-              }
-            }
-          }
+          var farmServerUrl = await resolveServerOrigin(scanFarmInst, serverKey);
 
           var gs = scanFarmInst.engine.gameState;
           var cfg = scanFarmInst.engine.config;
@@ -776,22 +2702,10 @@ chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
             break;
           }
 
-          // Get server base URL from tab
-          var farmScanTab = null;
-          if (scanFarmInst.tabId) {
-            farmScanTab = await chrome.tabs.get(scanFarmInst.tabId).catch(function() { return null; });
-          }
-          if (!farmScanTab || !farmScanTab.url) {
-            sendResponse({ success: false, error: 'No active Travian tab found. Open a Travian tab for this server first.' });
-            break;
-          }
-
-          var farmServerUrl = farmScanTab.url.match(/^https?:\/\/[^\/]+/);
           if (!farmServerUrl) {
-            sendResponse({ success: false, error: 'Cannot parse server URL from tab' });
+            sendResponse({ success: false, error: 'No active Travian tab found. Open a Travian tab for this server first.' });
             break;
           }
-          farmServerUrl = farmServerUrl[0];
 
           try {
             // Step 1: Scan map.sql for candidates
@@ -840,125 +2754,34 @@ chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
                 }
               }
 
-              logger.info('[MapScanner] Oasis check: ' + emptyOases.length + ' empty, ' + animalOases + ' with animals');
-              candidates = villageCandidates.concat(emptyOases);
-              // Re-sort by distance
-              candidates.sort(function(a, b) { return a.distance - b.distance; });
-
-              if (candidates.length === 0) {
-                sendResponse({ success: true, data: { found: oasisCandidates.length + villageCandidates.length, added: 0, failed: 0,
-                  message: 'All ' + animalOases + ' oases have animals. No valid targets.' } });
-                break;
-              }
-            }
-
-            // Step 2: Navigate to farm list page
-            var farmTabId = scanFarmInst.tabId;
-            var farmListUrl = farmServerUrl + '/build.php?id=39&tt=99';
-
-            await new Promise(function(resolve, reject) {
-              var navTimeout = setTimeout(function() {
-                chrome.tabs.onUpdated.removeListener(navListener);
-                reject(new Error('Navigation timeout'));
-              }, 15000);
-              function navListener(updatedTabId, changeInfo) {
-                if (updatedTabId === farmTabId && changeInfo.status === 'complete') {
-                  chrome.tabs.onUpdated.removeListener(navListener);
-                  clearTimeout(navTimeout);
-                  setTimeout(function() { resolve(); }, 1000);
-                }
-              }
-              chrome.tabs.onUpdated.addListener(navListener);
-              chrome.tabs.update(farmTabId, { url: farmListUrl });
-            });
-
-            // Step 3: Wait for content script and get existing slots
-            await new Promise(function(r) { setTimeout(r, 2000); });
+              logger.info('[MapScanner] Oasis check: ' + emptyOases.length + ' empty, ' + animalOases + ' with animals');
+              candidates = villageCandidates.concat(emptyOases);
+              // Re-sort by distance
+              candidates.sort(function(a, b) { return a.distance - b.distance; });
 
-            var existingSlots = [];
-            for (var scanRetry = 0; scanRetry < 3; scanRetry++) {
-              try {
-                var slotResp = await new Promise(function(resolve, reject) {
-                  chrome.tabs.sendMessage(farmTabId, {
-                    type: 'EXECUTE', action: 'scanFarmListSlots', params: {}
-                  }, function(r) {
-                    if (chrome.runtime.lastError) reject(new Error(chrome.runtime.lastError.message));
-                    else resolve(r);
-                  });
-                });
-                // Unwrap bridge response: slots may be in .data.slots or .slots
-                var slotData = (slotResp && slotResp.data && typeof slotResp.data === 'object')
-                  ? slotResp.data : slotResp;
-                if (slotData && slotData.slots) {
-                  existingSlots = slotData.slots;
-                  break;
-                }
-              } catch (slotErr) {
-                if (scanRetry === 2) logger.warn('[MapScanner] Could not scan existing slots: ' + slotErr.message);
-                await new Promise(function(r) { setTimeout(r, 1500); });
+              if (candidates.length === 0) {
+                sendResponse({ success: true, data: { found: oasisCandidates.length + villageCandidates.length, added: 0, failed: 0,
+                  message: 'All ' + animalOases + ' oases have animals. No valid targets.' } });
+                break;
               }
             }
 
-            // Check farm list capacity (max 100 slots)
-            var existingCount = existingSlots.length;
-            var maxSlots = 100;
-            var available = maxSlots - existingCount;
+            // Step 2: Add them to the first farm list
+            var troopSlot = farmScanCfg.scanTroopSlot || 't1';
+            var scanTroops = {};
+            scanTroops[troopSlot] = farmScanCfg.scanTroopCount || 1;
+            logger.info('[MapScanner] Troops per target: ' + troopSlot + '=' + scanTroops[troopSlot]);
 
-            if (available <= 0) {
+            var addRes = await addFarmListTargets(scanFarmInst.tabId, farmServerUrl, candidates, scanTroops);
+            if (addRes.full) {
               sendResponse({ success: true, data: {
                 found: candidates.length, added: 0, failed: 0,
-                message: 'Farm list full (' + existingCount + '/' + maxSlots + '). Found ' + candidates.length + ' targets.'
+                message: 'Farm list full (' + addRes.existing + '/' + FARM_LIST_MAX_SLOTS + '). Found ' + candidates.length + ' targets.'
               }});
               break;
             }
-
-            // Step 4: Add targets one by one with human-like delays
-            // Build troops object from config (e.g., {t1: 5} or {t3: 10})
-            var troopSlot = farmScanCfg.scanTroopSlot || 't1';
-            var troopCount = farmScanCfg.scanTroopCount || 1;
-            var scanTroops = {};
-            scanTroops[troopSlot] = troopCount;
-            logger.info('[MapScanner] Troops per target: ' + troopSlot + '=' + troopCount);
-
-            var toAdd = candidates.slice(0, available);
-            var addedCount = 0;
-            var failedCount = 0;
-
-            for (var ci = 0; ci < toAdd.length; ci++) {
-              var target = toAdd[ci];
-              try {
-                var addResp = await new Promise(function(resolve, reject) {
-                  chrome.tabs.sendMessage(farmTabId, {
-                    type: 'EXECUTE', action: 'addToFarmList', params: {
-                      x: target.x, y: target.y, troops: scanTroops, listIndex: 0
-                    }
-                  }, function(r) {
-                    if (chrome.runtime.lastError) reject(new Error(chrome.runtime.lastError.message));
-                    else resolve(r);
-                  });
-                });
-
-                if (addResp && addResp.success) {
-                  addedCount++;
-                } else {
-                  failedCount++;
-                  logger.warn('[MapScanner] Failed to add (' + target.x + '|' + target.y + '): ' + (addResp ? (addResp.error || addResp.message || JSON.stringify(addResp)) : 'no response'));
-                  // If we get 'no_input' or 'button_not_found', the selectors may be wrong — stop trying
-                  if (addResp && (addResp.reason === 'no_input' || addResp.reason === 'button_not_found')) {
-                    logger.error('[MapScanner] Stopping: farm list add UI not found. Selectors may need updating.');
-                    break;
-                  }
-                }
-              } catch (addErr) {
-                failedCount++;
-                logger.warn('[MapScanner] Error adding target: ' + addErr.message);
-              }
-
-              // Human-like delay between adds (1-3 seconds)
-              if (ci < toAdd.length - 1) {
-                await new Promise(function(r) { setTimeout(r, 1000 + Math.random() * 2000); });
-              }
-            }
+            var addedCount = addRes.added;
+            var failedCount = addRes.failed;
 
             var resultMsg = 'Found ' + candidates.length + ' targets, added ' + addedCount;
             if (failedCount > 0) resultMsg += ' (' + failedCount + ' failed)';
@@ -989,6 +2812,17 @@ chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
           break;
         }
 
+        // ---- Risk report: pacing vs action ceilings (per-server) ----
+        case 'GET_RISK_REPORT': {
+          var riskInst = resolveInstance(message, sender);
+          if (riskInst && riskInst.engine._safety) {
+            sendResponse({ success: true, data: riskInst.engine._safety.getRiskReport() });
+          } else {
+            sendResponse({ success: false, error: 'No safety engine available (bot not running?)' });
+          }
+          break;
+        }
+
         // ---- Exit Safe Mode (per-server) ----
         case 'EXIT_SAFE_MODE': {
           var safeModeInst = resolveInstance(message, sender);
@@ -1001,6 +2835,88 @@ chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
           break;
         }
 
+        // ---- Recent EventBus events (per-server, for timeline backfill) ----
+        case 'GET_RECENT_EVENTS': {
+          var reInst = resolveInstance(message, sender);
+          var reBus = reInst && reInst.engine.eventBus;
+          if (!reBus) {
+            sendResponse({ success: true, data: (data && data.event) ? [] : {} });
+            break;
+          }
+          sendResponse({ success: true, data: reBus.getRecent(data && data.event, data && data.limit) });
+          break;
+        }
+
+        // ---- Content script call metrics (per-server) ----
+        case 'GET_BRIDGE_METRICS': {
+          var bmInst = resolveInstance(message, sender);
+          if (!bmInst || !bmInst.engine._bridge) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          var bridgeMetrics = bmInst.engine._bridge.getMetrics();
+          if (data && data.reset) bmInst.engine._bridge.resetMetrics();
+          sendResponse({ success: true, data: {
+            messageTimeoutMs: bmInst.engine._bridge._messageTimeout,
+            methods: bridgeMetrics
+          }});
+          break;
+        }
+
+        // ---- Late content script responses (per-server) ----
+        case 'GET_BRIDGE_DEAD_LETTERS': {
+          var dlInst = resolveInstance(message, sender);
+          if (!dlInst || !dlInst.engine._bridge) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          var deadLetters = dlInst.engine._bridge.getDeadLetters();
+          if (data && data.clear) dlInst.engine._bridge.clearDeadLetters();
+          sendResponse({ success: true, data: deadLetters });
+          break;
+        }
+
+        // ---- Content script message trace (per-server, opt-in debugging) ----
+        case 'SET_BRIDGE_TRACE': {
+          var stInst = resolveInstance(message, sender);
+          if (!stInst || !stInst.engine._bridge) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          var traceOn = !!(data && data.enabled);
+          stInst.engine._bridge.setTraceEnabled(traceOn);
+          if (data && data.clear) stInst.engine._bridge.clearTrace();
+          logger.info('Bridge trace ' + (traceOn ? 'enabled' : 'disabled') + ' for ' + stInst.serverKey);
+          sendResponse({ success: true, data: { enabled: traceOn } });
+          break;
+        }
+
+        case 'GET_BRIDGE_TRACE': {
+          var gtInst = resolveInstance(message, sender);
+          if (!gtInst || !gtInst.engine._bridge) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          sendResponse({ success: true, data: {
+            enabled: gtInst.engine._bridge._traceEnabled,
+            entries: gtInst.engine._bridge.getTrace(data && data.limit)
+          }});
+          break;
+        }
+
+        case 'EXPORT_BRIDGE_TRACE': {
+          var etInst = resolveInstance(message, sender);
+          if (!etInst || !etInst.engine._bridge) {
+            sendResponse({ success: false, error: 'No bot instance found' });
+            break;
+          }
+          sendResponse({ success: true, data: {
+            filename: 'bridge-trace_' + etInst.serverKey + '_' + Date.now() + '.jsonl',
+            jsonl: etInst.engine._bridge.exportTrace()
+          }});
+          break;
+        }
+
         default:
           sendResponse({ success: false, error: 'Unknown message type: ' + type });
       }
@@ -1009,10 +2925,18 @@ chrome.runtime.onMessage.addListener(function (message, sender, sendResponse) {
       sendResponse({ success: false, error: err.message || String(err) });
     }
   })();
+}
 
-  // CRITICAL: return true to keep sendResponse channel open for async work.
-  return true;
-});
+/**
+ * Dispatch a message internally and resolve with its response.
+ * @param {object} message
+ * @returns {Promise<object>}
+ */
+function dispatchInternal(message) {
+  return new Promise(function (resolve) {
+    handleMessage(message, null, resolve);
+  });
+}
 
 // ---------------------------------------------------------------------------
 // 7. Tab Listeners — multi-instance aware
@@ -1090,6 +3014,76 @@ chrome.tabs.onRemoved.addListener(function (tabId) {
 // 8. Alarm System — per-server heartbeats
 // ---------------------------------------------------------------------------
 chrome.alarms.onAlarm.addListener(async function (alarm) {
+  await checkForWake();
+  await checkNetwork();
+  scheduleIconRefresh();
+
+  if (alarm.name === 'historyPrune') {
+    try {
+      var pruned = await self.TravianHistoryStore.prune();
+      logger.debug('History pruned', pruned);
+    } catch (err) {
+      logger.warn('History prune failed: ' + err.message);
+    }
+    return;
+  }
+
+  if (alarm.name === 'clockSync') {
+    await syncServerClocks();
+    return;
+  }
+
+  if (alarm.name === 'mapRefresh') {
+    // One server at a time: each dump is a few MB
+    var staleMaps = await self.TravianMapStore.staleServers().catch(function () { return []; });
+    for (var mi = 0; mi < staleMaps.length; mi++) {
+      try {
+        var mapMeta = await self.TravianMapStore.refresh(staleMaps[mi]);
+        logger.info('Map of ' + staleMaps[mi] + ' refreshed: ' + mapMeta.villages + ' villages');
+      } catch (err) {
+        logger.warn('Map refresh for ' + staleMaps[mi] + ' failed: ' + err.message);
+      }
+    }
+    return;
+  }
+
+  if (alarm.name === 'schedules') {
+    try {
+      var ran = await self.TravianSchedules.runDue(runScheduledJob);
+      ran.forEach(function (r) {
+        var label = r.job.name || (r.job.action + ' @ ' + r.job.cron);
+        if (r.ok) logger.info('Schedule "' + label + '" on ' + r.job.serverKey + ': ' + r.result);
+        else logger.warn('Schedule "' + label + '" on ' + r.job.serverKey + ' failed: ' + r.result);
+        var firedInst = manager.get(r.job.serverKey);
+        if (firedInst && firedInst.engine.eventBus) {
+          firedInst.engine.eventBus.emit(self.TravianEventBus.Events.SCHEDULE_FIRED, {
+            scheduleId: r.job.id, name: r.job.name, action: r.job.action,
+            ok: r.ok, result: r.result, nextRunAt: r.job.nextRunAt
+          });
+        }
+      });
+    } catch (err) {
+      logger.warn('Scheduled jobs failed: ' + err.message);
+    }
+    await syncScheduleAlarm();
+    return;
+  }
+
+  if (alarm.name === 'telegramPoll') {
+    try {
+      await pollTelegram();
+    } catch (err) {
+      logger.warn('Telegram poll failed: ' + err.message);
+    }
+    return;
+  }
+
+  // Attack alarm repeat: "attackAlarm__ts5.x1.asia.travian.com"
+  if (alarm.name.indexOf('attackAlarm__') === 0) {
+    manager.repeatAttackAlarm(alarm.name.replace('attackAlarm__', ''));
+    return;
+  }
+
   // Per-server heartbeat: "botHeartbeat__ts5.x1.asia.travian.com"
   if (alarm.name.indexOf('botHeartbeat') === 0) {
     var heartbeatKey = alarm.name.replace('botHeartbeat__', '');
@@ -1133,6 +3127,28 @@ chrome.alarms.onAlarm.addListener(async function (alarm) {
       return;
     }
 
+    // Activity windows: sleep pauses the bot, waking up resumes it — but
+    // only if the pause was ours, never one the user made.
+    var activity = inst.engine.config && inst.engine.config.activityConfig;
+    if (activity && activity.enabled) {
+      var sleepState = self.TravianHumanizer.state(inst.serverKey, activity);
+      if (sleepState.asleep && !inst.engine.paused) {
+        inst.engine.pause();
+        inst.humanizerPaused = true;
+        logger.info('Going to sleep on ' + inst.serverKey + ' until ' + new Date(sleepState.until).toLocaleTimeString());
+        return;
+      }
+      if (!sleepState.asleep && inst.engine.paused && inst.humanizerPaused) {
+        inst.engine.resume();   // refused while a captcha or manual control holds the pause
+        inst.humanizerPaused = false;
+        if (!inst.engine.paused) logger.info('Waking up on ' + inst.serverKey);
+      }
+    } else if (inst.humanizerPaused) {
+      // Windows switched off while asleep
+      inst.humanizerPaused = false;
+      if (inst.engine.paused) inst.engine.resume();
+    }
+
     if (!inst.engine.paused) {
       // Verify tab still exists
       if (inst.tabId) {
@@ -1160,12 +3176,485 @@ chrome.alarms.onAlarm.addListener(async function (alarm) {
   }
 });
 
+// Attack alarm notification buttons: 0 = Acknowledge, 1 = Snooze
+chrome.notifications.onButtonClicked.addListener(function (notificationId, buttonIndex) {
+  if (notificationId === CONFIRM_STOP_ID) {
+    chrome.notifications.clear(CONFIRM_STOP_ID);
+    if (buttonIndex === 0) emergencyStopFromMenu();
+    return;
+  }
+  if (notificationId.indexOf('attackAlarm__') !== 0) return;
+  var alarmKey = notificationId.replace('attackAlarm__', '');
+  if (buttonIndex === 0) manager.acknowledgeAttackAlarm(alarmKey);
+  else manager.snoozeAttackAlarm(alarmKey);
+});
+
+// History export over a port: progress messages while rows are read, then
+// the finished file. A port instead of sendMessage because big exports
+// outlive UIClient's 5s message timeout.
+//   UI → { kind, serverKey?, range?, format? }
+//   SW → { type: 'progress', rows } … { type: 'done', filename, mimeType, content, rows } | { type: 'error', error }
+chrome.runtime.onConnect.addListener(function (port) {
+  if (port.name !== 'historyExport') return;
+  port.onMessage.addListener(async function (req) {
+    try {
+      var exported = await self.TravianHistoryStore.exportData(req.kind, {
+        serverKey: req.serverKey || null,
+        range: req.range,
+        format: req.format,
+        onProgress: function (p) { port.postMessage({ type: 'progress', rows: p.rows }); }
+      });
+      port.postMessage(Object.assign({ type: 'done' }, exported));
+      logger.info('Exported ' + exported.rows + ' ' + req.kind + ' rows as ' + exported.filename);
+    } catch (err) {
+      port.postMessage({ type: 'error', error: err.message });
+    }
+  });
+});
+
+// Live log tail over a port, filtered here so the Logs view doesn't poll
+// GET_LOGS and re-diff. Each filter message (re)starts the tail with a
+// backfill of matching in-memory entries (oldest first).
+//   UI → { filter: { serverKey?, level?, text? }, backfill? (default 200) }
+//   SW → { type: 'backfill', entries } then { type: 'logs:tail', entry } per new entry
+chrome.runtime.onConnect.addListener(function (port) {
+  if (port.name !== 'logTail') return;
+  var tailFilter = null;
+  var unsubscribe = null;
+
+  function matches(entry) {
+    var f = tailFilter;
+    if (f.serverKey && entry.serverKey !== f.serverKey) return false;
+    if (f.minLevel && (logger.LOG_LEVELS[entry.level] || 0) < f.minLevel) return false;
+    if (f.text && String(entry.message).toLowerCase().indexOf(f.text) === -1) return false;
+    return true;
+  }
+
+  port.onMessage.addListener(function (req) {
+    var f = (req && req.filter) || {};
+    tailFilter = {
+      serverKey: f.serverKey || null,
+      minLevel: f.level ? logger.LOG_LEVELS[String(f.level).toUpperCase()] || 0 : 0,
+      text: f.text ? String(f.text).toLowerCase() : null
+    };
+    var backfillCount = req && typeof req.backfill === 'number' ? req.backfill : 200;
+    var backlog = backfillCount > 0
+      ? logger.getLogs(null, null, tailFilter.serverKey).filter(matches).slice(-backfillCount)
+      : [];
+    port.postMessage({ type: 'backfill', entries: backlog });
+
+    if (!unsubscribe) {
+      unsubscribe = logger.subscribe(function (entry) {
+        if (matches(entry)) port.postMessage({ type: 'logs:tail', entry: entry });
+      });
+    }
+  });
+
+  port.onDisconnect.addListener(function () {
+    if (unsubscribe) unsubscribe();
+    unsubscribe = null;
+  });
+});
+
+// Keyboard shortcut (manifest "commands", global so it works outside the
+// browser too; rebind at chrome://extensions/shortcuts): emergency-stop
+// every bot, e.g. when a sitter logs in by hand.
+chrome.commands.onCommand.addListener(async function (command) {
+  if (command === 'toggle-hud') {
+    await toggleHud();
+    return;
+  }
+  if (command !== 'emergency-stop-all') return;
+  var stoppedKeys = await manager.emergencyStopAll('Emergency stop shortcut');
+  logger.warn('Emergency stop shortcut: stopped ' + (stoppedKeys.length ? stoppedKeys.join(', ') : 'nothing (no bot running)'));
+  notify('EMERGENCY STOP', stoppedKeys.length
+    ? 'Stopped ' + stoppedKeys.length + ' bot(s): ' + stoppedKeys.join(', ')
+    : 'No bot was running');
+});
+
+// Extension updates. Chrome installs a downloaded update once the service
+// worker is idle, which it never is while a bot runs. So apply it here,
+// between tasks, with state saved so the bots restart after the reload.
+chrome.runtime.onUpdateAvailable.addListener(function (details) {
+  pendingUpdate = { version: details.version, since: Date.now() };
+  logger.info('Update ' + details.version + ' downloaded — applying between tasks');
+  applyPendingUpdate().catch(function (err) {
+    logger.warn('Update failed: ' + err.message);
+  });
+});
+
+// Autostart: when the browser starts, reopen the game for every server whose
+// bot was running and has no tab yet; the heartbeat then restarts the bot
+// once the tab has loaded (see SAF-2 in the heartbeat handler). Starting the
+// browser itself at login is the OS / browser's job.
+chrome.runtime.onStartup.addListener(function () {
+  autostartServers().catch(function (err) {
+    logger.warn('Autostart failed: ' + err.message);
+  });
+});
+
+// Quick actions on the toolbar icon's right-click menu, in sections that
+// toolbar_config.menu can switch on or off (see MENU_DEFAULTS).
+var ACTION_MENU = [
+  { id: 'summary', section: 'summary', title: 'Travian Assistant', enabled: false }, // live summary (TravianActionIcon)
+  { id: 'startAll', section: 'controls', title: 'Start all bots' },
+  { id: 'pauseAll', section: 'controls', title: 'Pause all bots' },
+  { id: 'emergencyStopAll', section: 'controls', title: 'EMERGENCY STOP…' },     // asks first (confirmEmergencyStop)
+  { id: 'recentAlerts', section: 'alerts', title: 'Recent alerts' },             // children: latest alerts
+  { id: 'servers', section: 'servers', title: 'Servers' },                       // children: one submenu per server
+  { id: 'windows', section: 'windows', title: 'Windows' },
+  { id: 'openLogs', section: 'windows', parentId: 'windows', title: 'Show logs' },
+  { id: 'toggleHud', section: 'windows', parentId: 'windows', title: 'Show/hide status window' },
+  { id: 'showGameWindows', section: 'windows', parentId: 'windows', title: 'Show game windows', type: 'checkbox' }
+];
+var MENU_DEFAULTS = { summary: true, controls: true, alerts: true, servers: false, windows: true };
+var MENU_TOP_LEVEL_LIMIT = 6; // chrome.contextMenus.ACTION_MENU_TOP_LEVEL_LIMIT; extra items go under "More"
+var RECENT_ALERT_ITEMS = 5;
+var SERVER_MENU_ACTIONS = [
+  { action: 'start', title: 'Start / resume' },
+  { action: 'pause', title: 'Pause' },
+  { action: 'stop', title: 'Stop' },
+  { action: 'focus', title: 'Show game tab' },
+  { action: 'manual', title: 'Take over / hand back (manual control)' }
+];
+
+var _menuLayout = null;    // layout the menu was last built with
+var _menuServers = null;   // server keys the Servers submenu was built for
+
+function menuServerKeys() {
+  return manager.listActive().map(function (r) { return r.serverKey; }).sort().join(',');
+}
+
+var _menuBuild = null;     // rebuild in flight
+var _menuRebuild = null;   // one more rebuild queued behind it
+
+/**
+ * (Re)create the toolbar menu from the layout in toolbar_config.menu, plus
+ * the latest alerts and one submenu per active server. Rebuilt when the
+ * layout, the alert list or the set of servers changes — simpler than
+ * diffing, and the items survive service worker restarts anyway.
+ *
+ * Rebuilds run one at a time: removeAll() and create() of two overlapping
+ * rebuilds would interleave into duplicate-id errors. Calls made while one
+ * runs share a single follow-up rebuild.
+ * @returns {Promise<void>}
+ */
+function buildActionMenu() {
+  if (!_menuBuild) {
+    _menuBuild = _buildActionMenu().finally(function () { _menuBuild = null; });
+    return _menuBuild;
+  }
+  if (!_menuRebuild) {
+    _menuRebuild = _menuBuild.catch(function () {}).then(function () {
+      _menuRebuild = null;
+      return buildActionMenu();
+    });
+  }
+  return _menuRebuild;
+}
+
+async function _buildActionMenu() {
+  var layout = (await getToolbarConfig()).menu;
+  var items = ACTION_MENU
+    .filter(function (item) { return layout[item.section]; })
+    .map(function (item) { return Object.assign({}, item); });
+
+  var topLevel = items.filter(function (item) { return !item.parentId; });
+  if (topLevel.length > MENU_TOP_LEVEL_LIMIT) {
+    var overflow = topLevel.slice(MENU_TOP_LEVEL_LIMIT - 1);
+    overflow.forEach(function (item) { item.parentId = 'more'; });
+    items.splice(items.indexOf(overflow[0]), 0, { id: 'more', title: 'More' });
+  }
+
+  await chrome.contextMenus.removeAll();
+  items.forEach(function (item) {
+    chrome.contextMenus.create({
+      id: item.id, parentId: item.parentId, title: item.title, type: item.type || 'normal',
+      enabled: item.enabled !== false, contexts: ['action']
+    });
+  });
+
+  if (layout.windows) syncGameWindowsItem((await getGameWindows()).visible);
+
+  if (layout.alerts) {
+    var alerts = await self.TravianAlertCenter.recent(RECENT_ALERT_ITEMS);
+    if (alerts.length === 0) {
+      chrome.contextMenus.create({ id: 'alert__none', parentId: 'recentAlerts', title: 'No recent alerts', enabled: false, contexts: ['action'] });
+    }
+    alerts.forEach(function (a) {
+      var at = new Date(a.ts);
+      var hhmm = String(at.getHours()).padStart(2, '0') + ':' + String(at.getMinutes()).padStart(2, '0');
+      chrome.contextMenus.create({
+        id: 'alert__' + a.id,
+        parentId: 'recentAlerts',
+        title: hhmm + '  ' + (a.acknowledged ? '✓ ' : '') + a.title,
+        contexts: ['action']
+      });
+    });
+  }
+
+  _menuServers = menuServerKeys();
+  if (layout.servers) {
+    var servers = manager.listActive();
+    if (servers.length === 0) {
+      chrome.contextMenus.create({ id: 'srv__none', parentId: 'servers', title: 'No active servers', enabled: false, contexts: ['action'] });
+    }
+    servers.forEach(function (r) {
+      var parent = 'srv__' + r.serverKey;
+      chrome.contextMenus.create({ id: parent, parentId: 'servers', title: r.serverKey.split('.')[0], contexts: ['action'] });
+      SERVER_MENU_ACTIONS.forEach(function (sa) {
+        chrome.contextMenus.create({ id: 'srv__' + sa.action + '__' + r.serverKey, parentId: parent, title: sa.title, contexts: ['action'] });
+      });
+    });
+  }
+
+  _menuLayout = layout;
+  self.TravianActionIcon.invalidate();
+  scheduleIconRefresh();
+}
+
+/** Rebuild the menu if the servers it lists are out of date. */
+function syncServerMenu() {
+  if (!_menuLayout || !_menuLayout.servers || _menuServers === menuServerKeys()) return;
+  buildActionMenu().catch(function (err) {
+    logger.debug('Toolbar menu rebuild failed: ' + err.message);
+  });
+}
+
+/**
+ * Run a per-server action from the toolbar menu.
+ * @param {string} action - 'start' | 'pause' | 'stop' | 'focus' | 'manual'
+ * @param {string} key - serverKey
+ */
+async function runServerMenuAction(action, key) {
+  var inst = manager.get(key);
+  if (action === 'start') {
+    await dispatchInternal({ type: 'START_BOT', serverKey: key });
+  } else if (action === 'pause') {
+    // PAUSE_BOT toggles — only send it to a running, unpaused bot
+    if (inst && inst.engine.running && !inst.engine.paused) await dispatchInternal({ type: 'PAUSE_BOT', serverKey: key });
+  } else if (action === 'stop') {
+    await dispatchInternal({ type: 'STOP_BOT', serverKey: key });
+  } else if (action === 'focus') {
+    if (!(await focusServerTab(key))) await openLogsWindow(key);
+  } else if (action === 'manual') {
+    await dispatchInternal({ type: 'SET_MANUAL_MODE', serverKey: key, data: { enabled: !(inst && inst.engine.manualMode) } });
+  }
+}
+
+self.TravianAlertCenter.onChange(function () {
+  manager._updateAlarmBadge();
+  buildActionMenu().catch(function (err) {
+    logger.debug('Toolbar menu rebuild failed: ' + err.message);
+  });
+});
+
+/**
+ * Emergency stop from the toolbar menu asks first: a sticky notification
+ * with "Stop all now" / "Cancel" buttons. A menu item is easy to hit by
+ * accident; the keyboard shortcut stays immediate.
+ */
+var CONFIRM_STOP_ID = 'confirmEmergencyStop';
+function confirmEmergencyStop() {
+  var running = manager.listActive().filter(function (r) { return r.running; });
+  if (running.length === 0) {
+    notify('Emergency stop', 'No bot is running');
+    return;
+  }
+  chrome.notifications.create(CONFIRM_STOP_ID, {
+    type: 'basic',
+    iconUrl: '../icons/icon48.png',
+    title: '🛑 EMERGENCY STOP — are you sure?',
+    message: 'Stops ' + running.length + ' bot(s) immediately, mid-action: ' +
+      running.map(function (r) { return r.serverKey.split('.')[0]; }).join(', '),
+    priority: 2,
+    requireInteraction: true,
+    buttons: [{ title: 'Stop all now' }, { title: 'Cancel' }]
+  });
+}
+
+async function emergencyStopFromMenu() {
+  var stopped = await manager.emergencyStopAll('Emergency stop from toolbar menu');
+  logger.warn('Emergency stop from toolbar menu: stopped ' + (stopped.length ? stopped.join(', ') : 'nothing'));
+  notify('EMERGENCY STOP', stopped.length ? 'Stopped ' + stopped.length + ' bot(s)' : 'No bot was running');
+}
+
+/**
+ * Acknowledge an alert and bring up where it can be dealt with: the
+ * server's game tab (attack, captcha, login), else the logs window.
+ * @param {string} alertId
+ */
+async function openAlert(alertId) {
+  var alert = await acknowledgeAlert(alertId);
+  if (!alert) return;
+  if (!(await focusServerTab(alert.serverKey))) await openLogsWindow(alert.serverKey);
+}
+
+/**
+ * Acknowledge one alert; an attack alert also stops that server's attack alarm.
+ * @param {number|string} alertId
+ * @returns {Promise<Object|null>}
+ */
+async function acknowledgeAlert(alertId) {
+  var alert = await self.TravianAlertCenter.acknowledge(alertId);
+  if (alert && alert.kind === 'attack') manager.acknowledgeAttackAlarm(alert.serverKey);
+  return alert;
+}
+
+// A captcha pauses the bot; unless switched off, bring its game tab up so
+// the user can solve it (game windows may be minimized, see above)
+manager.onCaptcha = function (serverKey) {
+  var inst = manager.get(serverKey);
+  var nc = (inst && inst.engine.config && inst.engine.config.notificationConfig) || {};
+  if (nc.revealOnCaptcha === false) return;
+  setGameWindowsVisible(true).then(function () {
+    return focusServerTab(serverKey);
+  }).catch(function (err) {
+    logger.warn('Could not bring up game tab for captcha: ' + err.message);
+  });
+};
+
+// ---------------------------------------------------------------------------
+// Screenshots (CAPTURE_SCREENSHOT, evidence for attack/captcha alerts)
+// ---------------------------------------------------------------------------
+
+var SCREENSHOT_QUALITY = 70;        // JPEG; a game page comes out at ~100-300 KB
+var EVIDENCE_KINDS = ['attack', 'captcha'];
+var EVIDENCE_DELAY_MS = 2000;       // lets the captcha reveal bring the tab up and the page paint
+
+/**
+ * Capture a tab and keep the image in the history store ('screenshots').
+ * Only what is on screen can be captured: the game tab must be the visible
+ * tab of its window. captureVisibleTab needs <all_urls> (optional, granted
+ * when screenshots are enabled) or activeTab, which only covers captures
+ * the user asked for from the popup.
+ * @param {string|null} serverKey
+ * @param {'game'|'current'} [target='game'] - The server's game tab, or
+ *   whatever tab is visible in the last focused window
+ * @param {string} [reason='manual']
+ * @param {number|null} [alertId] - Alert this is evidence for
+ * @returns {Promise<Object>} Stored row, including the image (dataUrl)
+ * @throws {Error} If there is no such tab or it is hidden behind another one
+ */
+async function captureScreenshot(serverKey, target, reason, alertId) {
+  var tab;
+  if (target === 'current') {
+    tab = (await chrome.tabs.query({ active: true, lastFocusedWindow: true }))[0];
+    if (!tab) throw new Error('No visible tab');
+  } else {
+    var inst = serverKey ? manager.get(serverKey) : null;
+    tab = inst && inst.tabId ? await chrome.tabs.get(inst.tabId).catch(function () { return null; }) : null;
+    if (!tab) throw new Error('No game tab for ' + serverKey);
+    if (!tab.active) throw new Error('The game tab for ' + serverKey + ' is not the visible tab in its window');
+  }
+  var dataUrl = await chrome.tabs.captureVisibleTab(tab.windowId, { format: 'jpeg', quality: SCREENSHOT_QUALITY });
+  var shot = {
+    ts: Date.now(),
+    serverKey: serverKey || null,
+    reason: reason || 'manual',
+    alertId: alertId || null,
+    url: tab.url || '',
+    dataUrl: dataUrl
+  };
+  shot.id = await self.TravianHistoryStore.putScreenshot(shot);
+  return shot;
+}
+
+// Attack and captcha alerts get a screenshot of the game tab as evidence
+// (first occurrence only; skipped if the tab isn't on screen or <all_urls>
+// hasn't been granted)
+self.TravianAlertCenter.onAdd(function (alert) {
+  if (alert.repeats > 0 || EVIDENCE_KINDS.indexOf(alert.kind) === -1) return;
+  var inst = manager.get(alert.serverKey);
+  var nc = (inst && inst.engine.config && inst.engine.config.notificationConfig) || {};
+  if (nc.evidenceScreenshots === false) return;
+  setTimeout(function () {
+    chrome.permissions.contains({ origins: ['<all_urls>'] }).then(function (granted) {
+      if (!granted) throw new Error('screenshots are not enabled');
+      return captureScreenshot(alert.serverKey, 'game', alert.kind, alert.id);
+    }).then(function (shot) {
+      return self.TravianAlertCenter.attachScreenshot(alert.id, shot.id);
+    }).catch(function (err) {
+      logger.warn('No evidence screenshot for ' + alert.kind + ' alert: ' + err.message);
+    });
+  }, EVIDENCE_DELAY_MS);
+});
+
+/**
+ * Bring a server's game tab to the front.
+ * @param {string} serverKey
+ * @returns {Promise<boolean>} False if the server has no open tab
+ */
+async function focusServerTab(serverKey) {
+  var inst = manager.get(serverKey);
+  var tab = inst && inst.tabId ? await chrome.tabs.get(inst.tabId).catch(function () { return null; }) : null;
+  if (!tab) return false;
+  await chrome.tabs.update(tab.id, { active: true });
+  await chrome.windows.update(tab.windowId, { focused: true });
+  return true;
+}
+
+// Windows get minimized/restored by hand too: re-check when focus moves
+chrome.windows.onFocusChanged.addListener(function () {
+  getGameWindows().then(function (gw) { syncGameWindowsItem(gw.visible); }).catch(function () {});
+});
+
+chrome.contextMenus.onClicked.addListener(async function (info) {
+  try {
+    var menuId = String(info.menuItemId);
+    if (menuId.indexOf('alert__') === 0) {
+      await openAlert(menuId.slice('alert__'.length));
+      return;
+    }
+    var srvMatch = /^srv__(\w+)__(.+)$/.exec(menuId);
+    if (srvMatch) {
+      await runServerMenuAction(srvMatch[1], srvMatch[2]);
+      return;
+    }
+    switch (info.menuItemId) {
+      case 'startAll': {
+        var menuStartRes = await dispatchInternal({ type: 'START_ALL_BOTS' });
+        var menuStarted = Object.keys((menuStartRes.data && menuStartRes.data.results) || {});
+        if (menuStarted.length === 0) notify('Start all', 'No open Travian tab to start');
+        break;
+      }
+      case 'pauseAll': {
+        var menuPauseRes = await dispatchInternal({ type: 'PAUSE_ALL_BOTS' });
+        var menuPaused = Object.keys((menuPauseRes.data && menuPauseRes.data.results) || {});
+        notify('Paused', menuPaused.length ? 'Paused ' + menuPaused.length + ' bot(s)' : 'No running bot to pause');
+        break;
+      }
+      case 'emergencyStopAll':
+        confirmEmergencyStop();
+        break;
+      case 'openLogs':
+        await openLogsWindow();
+        break;
+      case 'toggleHud':
+        await toggleHud();
+        break;
+      case 'showGameWindows':
+        await setGameWindowsVisible(info.checked);
+        break;
+    }
+  } catch (err) {
+    logger.warn('Toolbar menu action ' + info.menuItemId + ' failed: ' + err.message);
+  }
+});
+
 // ---------------------------------------------------------------------------
 // 9. Installation Handler — set default config
 // ---------------------------------------------------------------------------
 chrome.runtime.onInstalled.addListener(async function (details) {
   logger.info('Extension installed/updated (reason: ' + details.reason + ')');
 
+  // Toolbar icon right-click menu (persists; recreated on every install/update)
+  try {
+    await buildActionMenu();
+  } catch (menuErr) {
+    logger.warn('Toolbar menu setup failed: ' + menuErr.message);
+  }
+
   // Run migration
   try {
     var tab = await findTravianTab();
@@ -1232,6 +3721,8 @@ chrome.runtime.onInstalled.addListener(async function (details) {
 
       // Check if bot was running for this server
       var savedState = await self.TravianStorage.getServerState(serverKey);
+      // Manual control outlives the worker: show it and let the user hand back
+      if (savedState && savedState.manualMode) inst.engine.manualMode = savedState.manualMode;
       if (savedState && savedState.wasRunning) {
         logger.info('Bot was running on ' + serverKey + ' before restart — setting up heartbeat');
         chrome.alarms.create('botHeartbeat__' + serverKey, { periodInMinutes: 1 });
@@ -1250,6 +3741,38 @@ chrome.runtime.onInstalled.addListener(async function (details) {
   } catch (err) {
     logger.warn('State recovery failed:', err.message);
   }
+
+  // Daily history retention pass (create() with the same name replaces it)
+  chrome.alarms.create('historyPrune', { delayInMinutes: 5, periodInMinutes: 1440 });
+
+  // Server clock offsets, re-measured every 30 minutes
+  chrome.alarms.create('clockSync', { delayInMinutes: 1, periodInMinutes: 30 });
+
+  // Keep stored maps current: servers whose map is not from today are
+  // re-downloaded (map.sql changes once a day)
+  chrome.alarms.create('mapRefresh', { delayInMinutes: 10, periodInMinutes: 360 });
+
+  // Re-arm the scheduled-jobs alarm (overdue jobs run once, right away)
+  try {
+    await self.TravianServerClock.init();
+    await syncScheduleAlarm();
+  } catch (schErr) {
+    logger.warn('Schedules init failed:', schErr.message);
+  }
+
+  // Unacknowledged alerts drive the badge and the toolbar menu
+  try {
+    await self.TravianAlertCenter.init();
+  } catch (alErr) {
+    logger.warn('Alert center init failed:', alErr.message);
+  }
+
+  // Re-arm Telegram command polling
+  try {
+    syncTelegramPollAlarm(await self.TravianTelegram.getConfig());
+  } catch (tgErr) {
+    logger.warn('Telegram init failed:', tgErr.message);
+  }
 })();
 
 logger.info(LOG_TAG + ' Service worker script loaded');
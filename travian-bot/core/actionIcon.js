@@ -0,0 +1,172 @@
+/**
+ * ActionIcon — Toolbar icon that shows the combined state of all bots.
+ *
+ *   running  green dot   every active bot is running
+ *   paused   yellow dot  some bot is paused (by hand, sleep window, offline)
+ *   manual   blue dot    the user has taken over some server (manual control)
+ *   alert    red dot     an attack alarm, incoming attack or emergency stop
+ *   stopped  grey icon   no bot is running
+ *
+ * The icon is drawn on an OffscreenCanvas from icons/icon48.png (16 and
+ * 32 px) and only redrawn when the state changes. The tooltip (and the
+ * disabled header item of the toolbar menu) carries a one-line summary:
+ * "3 running · next action 02:14 · 1 attack inbound".
+ *
+ * Optionally (toolbar_config.countdown) the badge shows a countdown to the
+ * soonest incoming attack (red) or else to the next bot action (blue),
+ * ticked every second by the service worker while there is something to
+ * count down to. It takes over the badge from the alert count meanwhile.
+ *
+ * Runs in service worker context. Exported via self.TravianActionIcon.
+ */
+(function(root) {
+  'use strict';
+
+  var SIZES = [16, 32];
+  var DOT_COLORS = { running: '#00ff88', paused: '#ffaa00', manual: '#3388ff', alert: '#ff3366' };
+
+  var _base = null;       // ImageBitmap of the plain icon (lazy-loaded)
+  var _shown = null;      // signature of what is currently displayed
+
+  var TravianActionIcon = {};
+
+  TravianActionIcon.STATES = ['running', 'paused', 'manual', 'alert', 'stopped'];
+
+  /**
+   * Combine per-server rows into one state.
+   * @param {Array<{ running: boolean, paused: boolean, manualMode?: boolean, emergencyStopped?: boolean,
+   *   incomingAttacks?: number, nextActionTime?: number, label?: string }>} rows
+   * @param {number} [activeAlarms=0] - Attack alarms not yet acknowledged
+   * @returns {{ state: string, running: number, paused: number, manual: Array<string>, alerts: number,
+   *   attacks: number, nextActionAt: number|null }} manual = labels of servers under manual control
+   */
+  TravianActionIcon.aggregate = function(rows, activeAlarms) {
+    var counts = { running: 0, paused: 0, manual: [], alerts: activeAlarms || 0, attacks: 0, nextActionAt: null };
+    rows.forEach(function(r) {
+      if (r.emergencyStopped || r.incomingAttacks > 0) counts.alerts++;
+      counts.attacks += r.incomingAttacks || 0;
+      if (r.manualMode) counts.manual.push(r.label || '?');
+      else if (r.running && r.paused) counts.paused++;
+      else if (r.running) {
+        counts.running++;
+        if (r.nextActionTime && (counts.nextActionAt === null || r.nextActionTime < counts.nextActionAt)) {
+          counts.nextActionAt = r.nextActionTime;
+        }
+      }
+    });
+    var state = counts.alerts > 0 ? 'alert'
+      : counts.manual.length > 0 ? 'manual'
+      : counts.paused > 0 ? 'paused'
+      : counts.running > 0 ? 'running'
+      : 'stopped';
+    return Object.assign({ state: state }, counts);
+  };
+
+  async function _loadBase() {
+    if (!_base) {
+      var resp = await fetch(chrome.runtime.getURL('icons/icon48.png'));
+      _base = await createImageBitmap(await resp.blob());
+    }
+    return _base;
+  }
+
+  function _draw(base, size, state) {
+    var canvas = new OffscreenCanvas(size, size);
+    var ctx = canvas.getContext('2d');
+    if (state === 'stopped') ctx.filter = 'grayscale(1) opacity(0.6)';
+    ctx.drawImage(base, 0, 0, size, size);
+    ctx.filter = 'none';
+    if (DOT_COLORS[state]) {
+      var r = size * 0.22;
+      ctx.beginPath();
+      ctx.arc(size - r - 0.5, size - r - 0.5, r, 0, 2 * Math.PI);
+      ctx.fillStyle = DOT_COLORS[state];
+      ctx.fill();
+      ctx.lineWidth = Math.max(1, size / 16);
+      ctx.strokeStyle = '#0a0a1a';
+      ctx.stroke();
+    }
+    return ctx.getImageData(0, 0, size, size);
+  }
+
+  function _clock(ms) {
+    var d = new Date(ms);
+    return String(d.getHours()).padStart(2, '0') + ':' + String(d.getMinutes()).padStart(2, '0');
+  }
+
+  /**
+   * One-line summary of an aggregate, e.g. "3 running · next action 02:14 · 1 attack inbound".
+   * The next action is a clock time so the text doesn't go stale between refreshes.
+   * @param {Object} agg - From aggregate()
+   * @returns {string}
+   */
+  TravianActionIcon.summary = function(agg) {
+    var parts = [];
+    if (agg.manual && agg.manual.length) parts.push('✋ manual control: ' + agg.manual.join(', '));
+    if (agg.running) parts.push(agg.running + ' running');
+    if (agg.paused) parts.push(agg.paused + ' paused');
+    if (!agg.running && !agg.paused && !(agg.manual && agg.manual.length)) parts.push('stopped');
+    if (agg.nextActionAt) parts.push('next action ' + _clock(agg.nextActionAt));
+    if (agg.attacks) parts.push(agg.attacks + (agg.attacks === 1 ? ' attack' : ' attacks') + ' inbound');
+    else if (agg.alerts) parts.push(agg.alerts + ' alert' + (agg.alerts === 1 ? '' : 's'));
+    return parts.join(' · ');
+  };
+
+  /**
+   * Show an aggregate state on the toolbar icon (no-op if unchanged).
+   * @param {Object} agg - From aggregate()
+   * @param {string} [menuItemId] - Context menu item whose title mirrors the summary
+   * @returns {Promise<boolean>} True if anything was updated
+   */
+  TravianActionIcon.show = async function(agg, menuItemId) {
+    var text = TravianActionIcon.summary(agg);
+    var sig = agg.state + '|' + text;
+    if (sig === _shown) return false;
+    var iconChanged = !_shown || _shown.split('|')[0] !== agg.state;
+    _shown = sig;
+
+    if (iconChanged) {
+      var base = await _loadBase();
+      var imageData = {};
+      SIZES.forEach(function(size) { imageData[size] = _draw(base, size, agg.state); });
+      await chrome.action.setIcon({ imageData: imageData });
+    }
+
+    await chrome.action.setTitle({ title: 'Travian Assistant — ' + text });
+    if (menuItemId && chrome.contextMenus) {
+      chrome.contextMenus.update(menuItemId, { title: text }, function() {
+        void chrome.runtime.lastError; // menu not created yet (first install)
+      });
+    }
+    return true;
+  };
+
+  /**
+   * Badge-sized countdown: "45s", "12m", "3h" (badges fit ~4 characters).
+   * @param {number} ms
+   * @returns {string}
+   */
+  TravianActionIcon.countdownText = function(ms) {
+    var sec = Math.max(0, Math.ceil(ms / 1000));
+    if (sec < 60) return sec + 's';
+    if (sec < 3600) return Math.floor(sec / 60) + 'm';
+    return Math.floor(sec / 3600) + 'h';
+  };
+
+  /**
+   * Put a countdown on the badge.
+   * @param {{ at: number, attack: boolean }} target
+   * @param {number} [now=Date.now()]
+   */
+  TravianActionIcon.showCountdown = function(target, now) {
+    chrome.action.setBadgeBackgroundColor({ color: target.attack ? '#ff3366' : '#0099aa' });
+    chrome.action.setBadgeText({ text: TravianActionIcon.countdownText(target.at - (now || Date.now())) });
+  };
+
+  /** Forget what is shown so the next show() redraws everything (e.g. after menus were recreated). */
+  TravianActionIcon.invalidate = function() {
+    _shown = null;
+  };
+
+  root.TravianActionIcon = TravianActionIcon;
+})(typeof window !== 'undefined' ? window : self);
@@ -0,0 +1,264 @@
+/**
+ * AlertCenter — Persistent urgent alerts and their acknowledgement state.
+ *
+ * InstanceManager records every urgent alert (incoming attack, captcha or
+ * emergency stop, lost session, crop crisis) here as well as routing it to
+ * the notification channels. Alerts stay until acknowledged: the toolbar
+ * badge counts the unacknowledged ones and the toolbar menu lists the
+ * latest. Rows live in TravianHistoryStore's 'alerts' store:
+ *   { id, ts, serverKey, kind, severity, title, message, acknowledged (0|1), ackAt, repeats,
+ *     screenshotId, refs }
+ * (screenshotId: evidence screenshot in the 'screenshots' store, if one was taken;
+ * refs: identities of the attacks an attack alert is about, see attackRefs())
+ * A repeat of the same kind for the same server while the previous one is
+ * still unacknowledged updates that row (ts, text, repeats + 1) instead of
+ * adding another. Attack alerts go by their refs: the same attacks again
+ * change nothing, attacks already acknowledged are not added back, and
+ * only a new attack counts as a repeat.
+ *
+ * Unacknowledged alerts are also cached in memory so the badge can be
+ * computed synchronously; init() loads them.
+ *
+ * Runs in service worker context. Exported via self.TravianAlertCenter.
+ */
+(function(root) {
+  'use strict';
+
+  var LEGACY_KEY = 'recent_alerts'; // pre-IndexedDB storage, imported once by init()
+  var ARRIVAL_SLACK_MS = 10000;     // arrival times are read off countdowns: allow some drift between scans
+  var ACKED_LOOKBACK = 20;          // acknowledged rows checked for the same attacks
+
+  /** Severity per alert kind (anything else is 'info') */
+  var SEVERITY = { attack: 'critical', emergency: 'critical', captcha: 'critical', session: 'warning', crop: 'warning' };
+
+  var _open = null;       // unacknowledged alerts, newest first (lazy-loaded)
+  var _loading = null;
+  var _listeners = [];
+  var _addListeners = [];
+
+  var TravianAlertCenter = {};
+
+  TravianAlertCenter.SEVERITY = SEVERITY;
+
+  /**
+   * Identities of incoming attacks: the arrival time, or for attacks seen
+   * only in the village list (no timer) the target village.
+   * @param {Array<{arrivalTime: number|null, targetVillage?: string}>} attacks
+   * @returns {Array<number|string>}
+   */
+  TravianAlertCenter.attackRefs = function(attacks) {
+    return (attacks || []).map(function(a) {
+      return a.arrivalTime ? a.arrivalTime : 'village:' + (a.targetVillage || '?');
+    });
+  };
+
+  /**
+   * @param {Array<number|string>} refs
+   * @param {Array<number|string>} known
+   * @returns {boolean} True if every ref is one of known (arrival times within ARRIVAL_SLACK_MS)
+   */
+  TravianAlertCenter.knownRefs = function(refs, known) {
+    if (!refs || refs.length === 0 || !known) return false;
+    return refs.every(function(r) {
+      return known.some(function(k) {
+        return typeof r === 'number' && typeof k === 'number' ? Math.abs(r - k) <= ARRIVAL_SLACK_MS : r === k;
+      });
+    });
+  };
+
+  /** Were these attacks already acknowledged on this server? */
+  async function _acknowledgedRefs(alert) {
+    var res = await root.TravianHistoryStore.queryAlerts({ serverKey: alert.serverKey, acknowledged: true }, 0, ACKED_LOOKBACK);
+    var known = [];
+    res.entries.forEach(function(a) {
+      if (a.kind === alert.kind && a.refs) known = known.concat(a.refs);
+    });
+    return TravianAlertCenter.knownRefs(alert.refs, known);
+  }
+
+  function _load() {
+    if (_open) return Promise.resolve(_open);
+    if (!_loading) {
+      _loading = root.TravianHistoryStore.queryAlerts({ acknowledged: false }, 0, 500).then(function(res) {
+        _open = res.entries;
+        return _open;
+      }).finally(function() {
+        _loading = null;
+      });
+    }
+    return _loading;
+  }
+
+  function _changed() {
+    _listeners.forEach(function(fn) {
+      try { fn(); } catch (e) { console.warn('[AlertCenter] listener failed:', e.message); }
+    });
+  }
+
+  /**
+   * Load unacknowledged alerts (and import alerts kept in chrome.storage by
+   * older versions).
+   * @returns {Promise<void>}
+   */
+  TravianAlertCenter.init = async function() {
+    var legacy = await root.TravianStorage.get(LEGACY_KEY, null);
+    if (Array.isArray(legacy)) {
+      for (var i = legacy.length - 1; i >= 0; i--) {
+        var a = legacy[i];
+        await root.TravianHistoryStore.putAlert({
+          ts: a.at, serverKey: a.serverKey, kind: a.kind, severity: SEVERITY[a.kind] || 'info',
+          title: a.title, message: a.message || '', acknowledged: a.acknowledged ? 1 : 0,
+          ackAt: a.acknowledged ? a.at : null, repeats: 0
+        });
+      }
+      await chrome.storage.local.remove(LEGACY_KEY);
+      _open = null;
+    }
+    await _load();
+    _changed();
+  };
+
+  /**
+   * Record an alert.
+   * @param {{ serverKey: string, kind: string, title: string, message?: string,
+   *   refs?: Array<number|string> }} alert
+   * @returns {Promise<Object|null>} The stored alert (the open one, untouched, if it
+   *   is about the same attacks), or null if these attacks were already acknowledged
+   */
+  TravianAlertCenter.add = async function(alert) {
+    var open = await _load();
+    var idx = open.findIndex(function(a) { return a.serverKey === alert.serverKey && a.kind === alert.kind; });
+    var prev = idx === -1 ? null : open[idx];
+    if (alert.refs) {
+      if (prev && TravianAlertCenter.knownRefs(alert.refs, prev.refs)) return prev;
+      if (!prev && await _acknowledgedRefs(alert)) return null;
+    }
+    var row = {
+      ts: Date.now(),
+      serverKey: alert.serverKey,
+      kind: alert.kind,
+      severity: SEVERITY[alert.kind] || 'info',
+      title: alert.title,
+      message: alert.message || '',
+      acknowledged: 0,
+      ackAt: null,
+      repeats: prev ? prev.repeats + 1 : 0
+    };
+    if (alert.refs) row.refs = alert.refs;
+    if (prev) {
+      row.id = prev.id;
+      row.screenshotId = prev.screenshotId;
+      open.splice(idx, 1);
+    }
+    row.id = await root.TravianHistoryStore.putAlert(row);
+    open.unshift(row);
+    _changed();
+    _addListeners.forEach(function(fn) {
+      try { fn(row); } catch (e) { console.warn('[AlertCenter] add listener failed:', e.message); }
+    });
+    return row;
+  };
+
+  /**
+   * Page through alerts, newest first.
+   * @param {{ serverKey?: string, acknowledged?: boolean, severity?: string }} [filter]
+   * @param {number} [offset=0]
+   * @param {number} [limit=50]
+   * @returns {Promise<{ entries: Array<Object>, hasMore: boolean }>}
+   */
+  TravianAlertCenter.list = function(filter, offset, limit) {
+    return root.TravianHistoryStore.queryAlerts(filter, offset, limit);
+  };
+
+  /**
+   * @param {number} limit
+   * @returns {Promise<Array<Object>>} Latest alerts, acknowledged or not
+   */
+  TravianAlertCenter.recent = async function(limit) {
+    return (await TravianAlertCenter.list({}, 0, limit)).entries;
+  };
+
+  /**
+   * Unacknowledged alerts from the cache (empty until loaded).
+   * @returns {Array<Object>} Newest first
+   */
+  TravianAlertCenter.unacknowledged = function() {
+    return _open ? _open.slice() : [];
+  };
+
+  async function _ack(row) {
+    row.acknowledged = 1;
+    row.ackAt = Date.now();
+    await root.TravianHistoryStore.putAlert(row);
+  }
+
+  /**
+   * Mark one alert as handled.
+   * @param {number|string} id
+   * @returns {Promise<Object|null>} The alert, or null if unknown
+   */
+  TravianAlertCenter.acknowledge = async function(id) {
+    id = Number(id);
+    var open = await _load();
+    var idx = open.findIndex(function(a) { return a.id === id; });
+    if (idx === -1) return root.TravianHistoryStore.getAlert(id); // already acknowledged (or unknown)
+    var row = open.splice(idx, 1)[0];
+    await _ack(row);
+    _changed();
+    return row;
+  };
+
+  /**
+   * Acknowledge every open alert, optionally only one server's or one kind.
+   * @param {string} [serverKey]
+   * @param {string} [kind]
+   * @returns {Promise<Array<Object>>} The alerts acknowledged
+   */
+  TravianAlertCenter.acknowledgeAll = async function(serverKey, kind) {
+    var open = await _load();
+    var acked = open.filter(function(a) {
+      return (!serverKey || a.serverKey === serverKey) && (!kind || a.kind === kind);
+    });
+    if (acked.length === 0) return [];
+    _open = open.filter(function(a) { return acked.indexOf(a) === -1; });
+    for (var i = 0; i < acked.length; i++) await _ack(acked[i]);
+    _changed();
+    return acked;
+  };
+
+  /**
+   * Link an evidence screenshot to an alert.
+   * @param {number|string} id
+   * @param {number} screenshotId
+   * @returns {Promise<Object|null>} The alert, or null if unknown
+   */
+  TravianAlertCenter.attachScreenshot = async function(id, screenshotId) {
+    id = Number(id);
+    var open = await _load();
+    var row = open.find(function(a) { return a.id === id; }) ||
+      await root.TravianHistoryStore.getAlert(id);
+    if (!row) return null;
+    row.screenshotId = screenshotId;
+    await root.TravianHistoryStore.putAlert(row);
+    return row;
+  };
+
+  /**
+   * Call fn(alert) after an alert is recorded (new, or a repeat merged into
+   * an open one — alert.repeats > 0).
+   * @param {function(Object)} fn
+   */
+  TravianAlertCenter.onAdd = function(fn) {
+    _addListeners.push(fn);
+  };
+
+  /**
+   * Call fn() whenever the set of alerts changes.
+   * @param {function()} fn
+   */
+  TravianAlertCenter.onChange = function(fn) {
+    _listeners.push(fn);
+  };
+
+  root.TravianAlertCenter = TravianAlertCenter;
+})(typeof window !== 'undefined' ? window : self);
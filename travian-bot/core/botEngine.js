@@ -49,6 +49,8 @@ class BotEngine {
     this._paused = false;
     this._emergencyStopped = false;
     this._emergencyReason = null; // SAF-5 FIX: remember WHY we emergency-stopped
+    this.captchaBlocked = null;   // { reason, since } while paused for a captcha
+    this.manualMode = null;       // { since } while the user has taken over the game tab
 
     // Core subsystems
     this.taskQueue = new self.TravianTaskQueue();
@@ -341,8 +343,8 @@ class BotEngine {
           if (savedState.taskQueue && Array.isArray(savedState.taskQueue) && savedState.taskQueue.length > 0) {
             let restoredCount = 0;
             for (const task of savedState.taskQueue) {
-              // Only restore tasks that were pending or running (running gets reset to pending)
-              if (task.status === 'pending' || task.status === 'running') {
+              // Only restore tasks that were pending, paused or running (running gets reset to pending)
+              if (task.status === 'pending' || task.status === 'paused' || task.status === 'running') {
                 const newId = this.taskQueue.add(
                   task.type,
                   task.params || {},
@@ -358,6 +360,9 @@ class BotEngine {
                     restored.maxRetries = task.maxRetries || this.taskQueue.maxRetries;
                   }
                 }
+                if (newId && task.status === 'paused') {
+                  this.taskQueue.setPaused(newId, true);
+                }
                 restoredCount++;
               }
             }
@@ -384,6 +389,26 @@ class BotEngine {
             }
           }
 
+          // Paused by the user before an update reload: stay paused
+          if (savedState.paused) {
+            this._transition(BOT_STATES.PAUSED, 'paused (restored)');
+            console.log('[BotEngine] Restored user pause');
+          }
+
+          // Still waiting for a captcha when the worker died: stay paused
+          if (savedState.captchaBlocked) {
+            this.captchaBlocked = savedState.captchaBlocked;
+            this._transition(BOT_STATES.PAUSED, 'captcha (restored)');
+            console.log('[BotEngine] Restored captcha pause: ' + this.captchaBlocked.reason);
+          }
+
+          // The user still had the game tab: don't take it back on restart
+          if (savedState.manualMode) {
+            this.manualMode = savedState.manualMode;
+            this._transition(BOT_STATES.PAUSED, 'manual control (restored)');
+            console.log('[BotEngine] Restored manual control since ' + new Date(this.manualMode.since).toLocaleTimeString());
+          }
+
           // Safety state is restored via _safety.load() from its own storage key
           // (bot_safety__<serverKey>), not from the inline bot_state snapshot.
         }
@@ -491,6 +516,7 @@ class BotEngine {
    */
   async stop() {
     this._transition(BOT_STATES.STOPPED, 'stopped');
+    this.captchaBlocked = null;
 
     // Stop scheduler (clears all timers and cycles)
     this.scheduler.stop();
@@ -532,13 +558,99 @@ class BotEngine {
   }
 
   /**
-   * Resume the bot from a paused state.
+   * Resume the bot from a paused state. Refused while manual control is on
+   * or while paused for a captcha — only resumeAfterCaptcha() clears that.
    */
   resume() {
     if (!this.running) return;
+    if (this.captchaBlocked) {
+      console.warn('[BotEngine] Not resuming — waiting for the captcha to be solved');
+      return;
+    }
+    if (this.manualMode) {
+      console.warn('[BotEngine] Not resuming — manual control is on');
+      return;
+    }
     this._transition(BOT_STATES.IDLE, 'user resumed');
   }
 
+  /**
+   * Hand the game tab to the user (or take it back). While manual control
+   * is on the bot stays paused and nothing resumes it — not the popup,
+   * schedules or activity windows — until it is switched off again, which
+   * resumes a bot that is still running. The flag is saved with the server
+   * state, so a worker restart doesn't hand the tab back to the bot.
+   * @param {boolean} enabled
+   */
+  setManualMode(enabled) {
+    if (enabled) {
+      if (this.manualMode) return;
+      this.manualMode = { since: Date.now() };
+      this._slog('INFO', 'Manual control on — automation paused');
+      if (this.running && !this.paused) this._transition(BOT_STATES.PAUSED, 'manual control');
+    } else {
+      if (!this.manualMode) return;
+      this.manualMode = null;
+      this._slog('INFO', 'Manual control off — handing back to the bot');
+      if (this.running && this.paused) this.resume();
+    }
+    this._saveManualMode();
+  }
+
+  /**
+   * Persist manualMode now. A running engine saves its whole state; a
+   * stopped one only patches the flag so it doesn't overwrite wasRunning
+   * before heartbeat recovery has read it.
+   */
+  _saveManualMode() {
+    if (this.running) {
+      this.saveState().catch(() => {});
+      return;
+    }
+    if (!this.serverKey || typeof self.TravianStorage === 'undefined') return;
+    const manualMode = this.manualMode;
+    self.TravianStorage.getServerState(this.serverKey).then((saved) => {
+      if (!saved) return;
+      saved.manualMode = manualMode;
+      return self.TravianStorage.saveServerState(this.serverKey, saved);
+    }).catch(() => {});
+  }
+
+  /**
+   * Pause because the page shows a captcha / bot-protection check. The bot
+   * waits for the user to solve it instead of stopping outright; the
+   * 'bot:captcha' event raises the alert. Repeat detections while already
+   * blocked are ignored.
+   * @param {string} reason
+   */
+  captchaPause(reason) {
+    if (!this.running || this.captchaBlocked) return;
+    this.captchaBlocked = { reason: String(reason), since: Date.now() };
+    this._slog('WARN', 'Paused: ' + reason);
+    this._transition(BOT_STATES.PAUSED, reason);
+    if (this.eventBus && self.TravianEventBus) {
+      this.eventBus.emit(self.TravianEventBus.Events.CAPTCHA_DETECTED, { reason: String(reason) });
+    }
+  }
+
+  /**
+   * Resume after the user solved the captcha. Checks the page first so the
+   * bot doesn't walk straight back into it.
+   * @returns {Promise<void>}
+   * @throws {Error} If the bot isn't paused for a captcha or the captcha is still showing
+   */
+  async resumeAfterCaptcha() {
+    if (!this.captchaBlocked) throw new Error('Bot is not paused for a captcha');
+    if (this.manualMode) throw new Error('Manual control is on — hand control back instead');
+    var check = await this.sendToContentScript({
+      type: 'SCAN', params: { property: 'captcha' }
+    }).catch(() => null);
+    if (!check || !check.success) throw new Error('Could not check the game page — is the tab open?');
+    if (check.data === true) throw new Error('Captcha is still on the page');
+    this.captchaBlocked = null;
+    this.resume();
+  }
+
   /**
    * Heartbeat — called by chrome.alarms every ~1 minute.
    * Detects if the scheduler's main_loop cycle died (service worker sleep)
@@ -593,6 +705,9 @@ class BotEngine {
 
     this._emergencyReason = reason; // SAF-5 FIX: store for getStatus()
     this._transition(BOT_STATES.EMERGENCY, reason);
+    if (this.eventBus && self.TravianEventBus) {
+      this.eventBus.emit(self.TravianEventBus.Events.BOT_EMERGENCY, { reason: String(reason) });
+    }
     await this.stop(); // EMERGENCY → STOPPED — must await to ensure saveState() completes
 
     // Persist the emergency stop reason
@@ -781,7 +896,7 @@ class BotEngine {
    */
   async mainLoop() {
     // 1. Check running state
-    if (!this.running || this.paused || this.emergencyStopped) {
+    if (!this.running || this.paused || this.emergencyStopped || this.manualMode) {
       return;
     }
 
@@ -843,7 +958,7 @@ class BotEngine {
             type: 'SCAN', params: { property: 'captcha' }
           }).catch(() => null);
           if (captchaCheck && captchaCheck.success && captchaCheck.data === true) {
-            await this.emergencyStop('Captcha detected (scan failed but captcha confirmed)');
+            this.captchaPause('Captcha detected (scan failed but captcha confirmed)');
             return;
           }
         } catch (_) { /* lightweight check failed too — fall through to circuit breaker */ }
@@ -965,6 +1080,8 @@ class BotEngine {
             await this.emergencyStop(safetyVerdict.reason);
           } else if (safetyVerdict.action === 'pause') {
             this.pause();
+          } else if (safetyVerdict.action === 'delay') {
+            this.nextActionTime = safetyVerdict.resumeAt;
           }
           return;
         }
@@ -972,7 +1089,7 @@ class BotEngine {
 
       // 4. Safety checks - captcha / errors
       if (this.gameState.captcha) {
-        await this.emergencyStop('Captcha detected on page');
+        this.captchaPause('Captcha detected on page');
         return;
       }
 
@@ -993,6 +1110,13 @@ class BotEngine {
         // skip loop where bot appeared running but did nothing.
         this._notLoggedInCount++;
         this._slog('WARN', 'Not logged in (count: ' + this._notLoggedInCount + '/' + this._notLoggedInMaxCount + ')');
+        if (this._notLoggedInCount === 1 && this.eventBus && self.TravianEventBus) {
+          // Alert on the first miss so the user can re-login before the emergency stop
+          this.eventBus.emit(self.TravianEventBus.Events.SESSION_LOST, {
+            notLoggedInCount: this._notLoggedInCount,
+            maxCount: this._notLoggedInMaxCount
+          });
+        }
         if (this._notLoggedInCount >= this._notLoggedInMaxCount) {
           await this.emergencyStop('Session expired — not logged in for ' + this._notLoggedInCount + ' consecutive cycles');
         }
@@ -1025,13 +1149,15 @@ class BotEngine {
 
       // Add new tasks to the queue
       for (const task of filteredTasks) {
-        this.taskQueue.add(
+        const queuedId = this.taskQueue.add(
           task.type,
           task.params,
           task.priority,
           task.villageId,
           task.scheduledFor || null
         );
+        // Keep the decision rule for the action timeline
+        if (queuedId && task.reason) this.taskQueue.update(queuedId, { reason: task.reason });
       }
 
       // 5a. Persist GlobalPlanner state after decision cycle
@@ -1227,6 +1353,12 @@ class BotEngine {
       // DecisionEngine will re-create the task once conditions clear.
       if (this._safety) {
         var execVerdict = this._safety.onPreExecute(task, this.gameState);
+        if (execVerdict.block && execVerdict.retryAt) {
+          // Over an action ceiling: keep the task and try again once there is room
+          this._slog('INFO', 'Paced: ' + task.type + ' held until ' + new Date(execVerdict.retryAt).toLocaleTimeString(), { taskId: task.id });
+          this.taskQueue.update(task.id, { status: 'pending', scheduledFor: execVerdict.retryAt });
+          return;
+        }
         if (execVerdict.block) {
           this._slog('WARN', 'Safety blocked: ' + task.type + ' — ' + execVerdict.reason, { taskId: task.id });
           this.taskQueue.remove(task.id);
@@ -1251,6 +1383,16 @@ class BotEngine {
       if (response && response.success) {
         this.taskQueue.markCompleted(task.id);
         this.stats.tasksCompleted++;
+        if (this.eventBus && self.TravianEventBus) {
+          this.eventBus.emit(self.TravianEventBus.Events.TASK_COMPLETED, {
+            taskId: task.id,
+            type: task.type,
+            villageId: task.villageId || null,
+            params: task.params,
+            reason: task.reason || null,
+            result: response
+          });
+        }
         this.stats.lastAction = Date.now();
         this.actionsThisHour++;
         this._consecutiveFailures = 0; // Circuit breaker: reset on success
@@ -1291,6 +1433,7 @@ class BotEngine {
           }
 
           this._slog('WARN', 'Task skipped (' + reason + '): ' + task.type, { taskId: task.id, reason, duration_ms: Date.now() - _taskStart });
+          this._emitTaskFailed(task, errorMsg, reason);
 
           // Special: if insufficient resources, try claiming hero inventory
           if (reason === 'insufficient_resources' &&
@@ -1314,6 +1457,7 @@ class BotEngine {
           if (task.status === 'failed') {
             this.stats.tasksFailed++;
             this._slog('ERROR', 'Task permanently failed: ' + task.type, { taskId: task.id, error: errorMsg, duration_ms: Date.now() - _taskStart });
+            this._emitTaskFailed(task, errorMsg, reason);
           } else {
             this._slog('WARN', 'Task failed, will retry: ' + task.type, { taskId: task.id, error: errorMsg, retries: task.retries });
           }
@@ -1328,6 +1472,7 @@ class BotEngine {
       // FIX: Check task.status (set by markFailed) instead of manual retries+1 calc
       if (task.status === 'failed') {
         this.stats.tasksFailed++;
+        this._emitTaskFailed(task, errorMsg, 'exception');
       }
 
       this._slog('ERROR', 'Exception executing task ' + task.type, { taskId: task.id, error: err.message, duration_ms: Date.now() - _taskStart });
@@ -1553,6 +1698,8 @@ class BotEngine {
       paused: this.paused,
       emergencyStopped: this.emergencyStopped,
       emergencyReason: this._emergencyReason, // SAF-5 FIX
+      captchaBlocked: this.captchaBlocked,
+      manualMode: this.manualMode,
       botState: this._botState,
       cycleId: this._currentCycleId,
       activeTabId: this.activeTabId,
@@ -1630,8 +1777,9 @@ class BotEngine {
 
   /**
    * Persist current bot state to chrome.storage.local
+   * @param {Object} [extra] - Fields stored on top, e.g. { paused } before an update reload
    */
-  async saveState() {
+  async saveState(extra) {
     try {
       if (typeof chrome === 'undefined' || !chrome.storage) return;
 
@@ -1642,8 +1790,11 @@ class BotEngine {
         hourResetTime: this.hourResetTime,
         lastFarmTime: this._lastFarmTime || 0,
         wasRunning: this.running,
+        captchaBlocked: this.captchaBlocked,
+        manualMode: this.manualMode,
         savedAt: Date.now()
       };
+      if (extra) Object.assign(state, extra);
 
       // Persist safety state to its own dedicated storage key
       if (this._safety && this._safety.isDirty()) {
@@ -1889,6 +2040,25 @@ class BotEngine {
     return new Promise(resolve => setTimeout(resolve, delay));
   }
 
+  /**
+   * Emit task:failed for a task that will not be retried.
+   * @param {object} task
+   * @param {string} error - Error message
+   * @param {string} [code] - Failure reason code (e.g. 'queue_full', 'exception')
+   */
+  _emitTaskFailed(task, error, code) {
+    if (!this.eventBus || !self.TravianEventBus) return;
+    this.eventBus.emit(self.TravianEventBus.Events.TASK_FAILED, {
+      taskId: task.id,
+      type: task.type,
+      villageId: task.villageId || null,
+      params: task.params,
+      reason: task.reason || null,
+      error: error,
+      code: code || null
+    });
+  }
+
   /**
    * Get the appropriate cooldown duration for a task type.
    * Prevents the decision engine from re-creating the same task too quickly.
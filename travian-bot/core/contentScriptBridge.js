@@ -4,11 +4,16 @@
  *
  * Handles:
  *   - Sending messages with retry on transient connection errors (MP-1)
+ *   - Retry on timeout for read-only messages (SCAN, GET_STATE)
  *   - Adaptive timeout for Chrome's background tab throttling (TQ-6)
  *   - Request deduplication via unique requestId stamps (TQ-6)
  *   - Ghost callback prevention via settled flags (FIX 1)
  *   - Waiting for content script readiness after navigation
  *   - Page verification after navigation (FIX 9)
+ *   - Opt-in message trace ring buffer for debugging protocol mismatches
+ *   - Per-method call metrics (count, errors, timeouts, p50/p95 latency)
+ *   - Dead-letter buffer of late (ghost) responses that arrived after timeout
+ *   - Fast-failing in-flight requests (e.g. ones issued before system sleep)
  *
  * Runs in service worker context (no DOM, no window).
  * Exported via self.TravianContentScriptBridge
@@ -17,6 +22,14 @@
   'use strict';
   const root = typeof window !== 'undefined' ? window : self;
 
+  // Read-only message types that may also be retried after a timeout, not just
+  // after a connection error. Value = max timeout retries. EXECUTE is never
+  // listed — a timed-out click may still have landed on the page.
+  const IDEMPOTENT_TIMEOUT_RETRIES = Object.freeze({
+    SCAN: 1,
+    GET_STATE: 1
+  });
+
   class ContentScriptBridge {
     /**
      * @param {Function} [logger] - Logging function with signature (level, message, meta).
@@ -41,7 +54,30 @@
       // Request deduplication counter (TQ-6)
       // Each EXECUTE message gets a unique requestId so the content script
       // can detect and discard duplicate requests from timeout->retry sequences.
-      this._requestIdCounter = 0;
+      // Seeded from the clock: the content script rejects ids <= the last one it
+      // saw, and it can outlive a service worker restart. Starting from 0 again
+      // would make every EXECUTE look like a duplicate until the page reloads.
+      this._requestIdCounter = Date.now();
+
+      // Opt-in message trace (off by default — payload stringify is not free).
+      // Each entry: {ts, type, action, requestId, latencyMs, ok, request, response}
+      this._traceEnabled = false;
+      this._trace = [];
+      this._traceMax = 200;          // Ring buffer size
+      this._tracePayloadMax = 500;   // Truncate stringified payloads to this many chars
+
+      // Per-method call metrics, keyed by 'SCAN', 'EXECUTE:clickUpgradeButton', etc.
+      // Always on — counters are cheap and latency samples are capped per method.
+      this._metrics = {};
+      this._metricsSampleMax = 100;
+
+      // Dead letters: responses that arrived after their request already timed out.
+      // Usually means the tab was throttled or the timeout is too tight for that action.
+      this._deadLetters = [];
+      this._deadLettersMax = 50;
+
+      // In-flight requests: {startedAt, fail(err)} — see failPending()
+      this._pending = new Set();
     }
 
     /**
@@ -58,6 +94,7 @@
 
     /**
      * Send a message to the content script with retry on transient connection errors.
+     * Read-only types in IDEMPOTENT_TIMEOUT_RETRIES are also retried after a timeout.
      * EXECUTE messages are stamped with a unique requestId for deduplication.
      *
      * MP-1 FIX: Retry wrapper for transient "Receiving end does not exist" errors.
@@ -84,19 +121,37 @@
       // MP-1 FIX: Retry wrapper for transient "Receiving end does not exist" errors.
       var maxRetries = 2;
       var lastErr = null;
+      var startedAt = Date.now();
+      var timeoutRetriesLeft = (message && IDEMPOTENT_TIMEOUT_RETRIES[message.type]) || 0;
       for (var attempt = 0; attempt <= maxRetries; attempt++) {
         try {
-          return await this._sendOnce(message);
+          var response = await this._sendOnce(message);
+          this._recordMetric(message, startedAt, null);
+          if (response && response.reason === 'duplicate_request') {
+            this._metricFor(message).duplicates++;
+            this._log('WARN', 'Content script rejected requestId ' + message._requestId + ' as duplicate');
+          }
+          return response;
         } catch (err) {
           lastErr = err;
           var isConnectionError = err.message && (
             err.message.indexOf('Receiving end does not exist') !== -1 ||
             err.message.indexOf('Could not establish connection') !== -1
           );
-          if (!isConnectionError || attempt >= maxRetries) throw err;
-          // Wait before retry -- content script may be loading
-          var retryDelay = 1000 * (attempt + 1); // 1s, 2s
-          this._log('WARN', 'Content script not ready, retry ' + (attempt + 1) + '/' + maxRetries + ' in ' + retryDelay + 'ms');
+          // Read-only messages may also retry a timeout (tab throttled mid-scan).
+          // _sendOnce already raised the adaptive timeout, so the retry gets more headroom.
+          var isRetryableTimeout = !isConnectionError && timeoutRetriesLeft > 0 &&
+            err.message && err.message.indexOf('timed out') !== -1;
+          if ((!isConnectionError && !isRetryableTimeout) || attempt >= maxRetries) {
+            this._recordMetric(message, startedAt, err);
+            throw err;
+          }
+          if (isRetryableTimeout) timeoutRetriesLeft--;
+          // Wait before retry -- content script may be loading. Jitter avoids
+          // lockstep retries when several servers hit the same hiccup.
+          var retryDelay = 1000 * (attempt + 1) + Math.floor(Math.random() * 500); // ~1s, ~2s
+          this._log('WARN', (isRetryableTimeout ? 'Read timed out' : 'Content script not ready') +
+            ', retry ' + (attempt + 1) + '/' + maxRetries + ' in ' + retryDelay + 'ms');
           await new Promise(r => setTimeout(r, retryDelay));
         }
       }
@@ -115,6 +170,25 @@
      * @returns {Promise<object>} The response from the content script
      */
     async _sendOnce(message) {
+      if (!this._traceEnabled) return this._sendOnceRaw(message);
+
+      var startedAt = Date.now();
+      try {
+        var response = await this._sendOnceRaw(message);
+        this._recordTrace(message, startedAt, true, response);
+        return response;
+      } catch (err) {
+        this._recordTrace(message, startedAt, false, err.message);
+        throw err;
+      }
+    }
+
+    /**
+     * Raw send without tracing. See _sendOnce().
+     * @param {object} message - The message to send
+     * @returns {Promise<object>} The response from the content script
+     */
+    async _sendOnceRaw(message) {
       var bridge = this;
       return new Promise(function(resolve, reject) {
         // FIX 1: "settled" flag prevents ghost actions from the timeout/callback race.
@@ -122,11 +196,27 @@
         // can still arrive later. Without this flag, both resolve AND reject would fire,
         // or the late callback would trigger side-effects on an already-abandoned promise.
         var settled = false;
+        var timedOutAt = 0;
 
         var currentTimeout = bridge._messageTimeout;
+        var pending = {
+          startedAt: Date.now(),
+          fail: function(err) {
+            if (settled) return;
+            settled = true;
+            timedOutAt = Date.now();
+            clearTimeout(timeoutId);
+            bridge._pending.delete(pending);
+            reject(err);
+          }
+        };
+        bridge._pending.add(pending);
+
         var timeoutId = setTimeout(function() {
           if (settled) return;
           settled = true;
+          bridge._pending.delete(pending);
+          timedOutAt = Date.now();
           // Adaptive timeout: increase for next attempt (Chrome may be throttling)
           if (bridge._messageTimeout < bridge._messageTimeoutMax) {
             bridge._messageTimeout = Math.min(bridge._messageTimeout + bridge._messageTimeoutStep, bridge._messageTimeoutMax);
@@ -138,12 +228,14 @@
         try {
           chrome.tabs.sendMessage(bridge.activeTabId, message, function(response) {
             if (settled) {
-              // Ghost callback -- timeout already fired. Log and discard.
+              // Ghost callback -- timeout already fired. Record and discard.
               console.warn('[ContentScriptBridge] Ghost callback after timeout for:', message.type || message.action);
+              bridge._recordDeadLetter(message, timedOutAt, response);
               return;
             }
             settled = true;
             clearTimeout(timeoutId);
+            bridge._pending.delete(pending);
 
             if (chrome.runtime.lastError) {
               reject(new Error(chrome.runtime.lastError.message));
@@ -161,11 +253,229 @@
           if (settled) return;
           settled = true;
           clearTimeout(timeoutId);
+          bridge._pending.delete(pending);
           reject(err);
         }
       });
     }
 
+    /**
+     * Reject in-flight requests now instead of letting them run into their
+     * timeout. A response that still arrives later goes to the dead letters.
+     * The error is not retried by send() (not a connection error or timeout).
+     * @param {string} reason - Error message for the rejected calls
+     * @param {number} [startedBefore=Infinity] - Only requests sent before this epoch ms
+     * @returns {number} Requests failed
+     */
+    failPending(reason, startedBefore) {
+      var cutoff = startedBefore || Infinity;
+      var failed = 0;
+      Array.from(this._pending).forEach(function(p) {
+        if (p.startedAt >= cutoff) return;
+        p.fail(new Error(reason));
+        failed++;
+      });
+      if (failed > 0) this._log('WARN', 'Failed ' + failed + ' in-flight request(s): ' + reason);
+      return failed;
+    }
+
+    // -----------------------------------------------------------------------
+    // Call metrics
+    // -----------------------------------------------------------------------
+
+    /**
+     * Get per-method call metrics.
+     * duplicates = responses the content script rejected as already-processed ids;
+     * lateResponses = callbacks that arrived after the request timed out.
+     * @returns {Object.<string, {calls: number, errors: number, timeouts: number,
+     *   duplicates: number, lateResponses: number,
+     *   p50Ms: number|null, p95Ms: number|null, lastError: string|null}>}
+     */
+    getMetrics() {
+      var out = {};
+      for (var key in this._metrics) {
+        var m = this._metrics[key];
+        var sorted = m.samples.slice().sort(function(a, b) { return a - b; });
+        out[key] = {
+          calls: m.calls,
+          errors: m.errors,
+          timeouts: m.timeouts,
+          duplicates: m.duplicates,
+          lateResponses: m.lateResponses,
+          p50Ms: this._percentile(sorted, 0.5),
+          p95Ms: this._percentile(sorted, 0.95),
+          lastError: m.lastError
+        };
+      }
+      return out;
+    }
+
+    /** Reset all call metrics. */
+    resetMetrics() {
+      this._metrics = {};
+    }
+
+    /**
+     * Record the outcome of one send() call (including its retries).
+     * @param {object} message
+     * @param {number} startedAt - Timestamp of the first attempt
+     * @param {Error|null} err - Final error, or null on success
+     */
+    _recordMetric(message, startedAt, err) {
+      var m = this._metricFor(message);
+      m.calls++;
+      if (err) {
+        m.errors++;
+        m.lastError = err.message;
+        if (err.message && err.message.indexOf('timed out') !== -1) m.timeouts++;
+        return; // Only successful calls contribute latency samples
+      }
+      m.samples.push(Date.now() - startedAt);
+      if (m.samples.length > this._metricsSampleMax) m.samples.shift();
+    }
+
+    /**
+     * Get (creating if needed) the metrics bucket for a message.
+     * @param {object} message
+     * @returns {object}
+     */
+    _metricFor(message) {
+      var key = (message && message.type) || 'unknown';
+      if (key === 'EXECUTE' && message.action) key += ':' + message.action;
+
+      var m = this._metrics[key];
+      if (!m) {
+        m = this._metrics[key] = {
+          calls: 0, errors: 0, timeouts: 0, duplicates: 0, lateResponses: 0,
+          samples: [], lastError: null
+        };
+      }
+      return m;
+    }
+
+    /**
+     * Nearest-rank percentile of a sorted sample array.
+     * @param {number[]} sorted
+     * @param {number} p - 0..1
+     * @returns {number|null}
+     */
+    _percentile(sorted, p) {
+      if (sorted.length === 0) return null;
+      var idx = Math.min(sorted.length - 1, Math.ceil(p * sorted.length) - 1);
+      return sorted[Math.max(0, idx)];
+    }
+
+    // -----------------------------------------------------------------------
+    // Dead letters (late responses)
+    // -----------------------------------------------------------------------
+
+    /**
+     * Get responses that arrived after their request had timed out (oldest first).
+     * @returns {Array<{ts: number, type: string, action: string, requestId: number,
+     *   lateByMs: number, response: string|null}>}
+     */
+    getDeadLetters() {
+      return this._deadLetters.slice();
+    }
+
+    /** Drop all recorded dead letters. */
+    clearDeadLetters() {
+      this._deadLetters = [];
+    }
+
+    /**
+     * Record a ghost callback. chrome.runtime.lastError must be read here too,
+     * otherwise Chrome logs "Unchecked runtime.lastError" for late failures.
+     * @param {object} message - Original outgoing message
+     * @param {number} timedOutAt - When the timeout fired
+     * @param {*} response - Late response payload
+     */
+    _recordDeadLetter(message, timedOutAt, response) {
+      var lastError = chrome.runtime.lastError;
+      this._metricFor(message).lateResponses++;
+      this._deadLetters.push({
+        ts: Date.now(),
+        type: message && message.type,
+        action: message && message.action,
+        requestId: message && message._requestId,
+        lateByMs: timedOutAt ? Date.now() - timedOutAt : null,
+        response: lastError ? 'error: ' + lastError.message : this._truncatePayload(response)
+      });
+      if (this._deadLetters.length > this._deadLettersMax) this._deadLetters.shift();
+    }
+
+    // -----------------------------------------------------------------------
+    // Message trace (opt-in)
+    // -----------------------------------------------------------------------
+
+    /**
+     * Enable or disable message tracing. Disabling keeps existing entries
+     * so they can still be read/exported after reproducing an issue.
+     * @param {boolean} enabled
+     */
+    setTraceEnabled(enabled) {
+      this._traceEnabled = !!enabled;
+    }
+
+    /**
+     * Get the most recent trace entries (oldest first).
+     * @param {number} [count] - Max entries to return (default: all)
+     * @returns {Array<object>}
+     */
+    getTrace(count) {
+      return count ? this._trace.slice(-count) : this._trace.slice();
+    }
+
+    /**
+     * Export the trace buffer as JSONL (one entry per line) for saving to a file.
+     * @returns {string}
+     */
+    exportTrace() {
+      return this._trace.map(function(e) { return JSON.stringify(e); }).join('\n');
+    }
+
+    /** Drop all recorded trace entries. */
+    clearTrace() {
+      this._trace = [];
+    }
+
+    /**
+     * Append a trace entry, evicting the oldest when the buffer is full.
+     * @param {object} message - Outgoing message
+     * @param {number} startedAt - Send timestamp (ms)
+     * @param {boolean} ok - Whether a response arrived (vs. error/timeout)
+     * @param {*} response - Response payload or error message
+     */
+    _recordTrace(message, startedAt, ok, response) {
+      this._trace.push({
+        ts: startedAt,
+        type: message && message.type,
+        action: message && message.action,
+        requestId: message && message._requestId,
+        latencyMs: Date.now() - startedAt,
+        ok: ok,
+        request: this._truncatePayload(message),
+        response: this._truncatePayload(response)
+      });
+      if (this._trace.length > this._traceMax) this._trace.shift();
+    }
+
+    /**
+     * Stringify a payload and cap its length so big SCAN results don't bloat the buffer.
+     * @param {*} payload
+     * @returns {string|null}
+     */
+    _truncatePayload(payload) {
+      if (payload === undefined || payload === null) return null;
+      var str;
+      try { str = typeof payload === 'string' ? payload : JSON.stringify(payload); }
+      catch (e) { str = String(payload); }
+      if (str.length > this._tracePayloadMax) {
+        str = str.slice(0, this._tracePayloadMax) + '…(' + str.length + ' chars)';
+      }
+      return str;
+    }
+
     // -----------------------------------------------------------------------
     // Public: wait for content script readiness (extracted from BotEngine._waitForContentScript)
     // -----------------------------------------------------------------------
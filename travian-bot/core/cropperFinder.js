@@ -0,0 +1,205 @@
+/**
+ * CropperFinder — Searches the map for free 9- and 15-cropper valleys.
+ *
+ * map.sql only lists villages, not what the empty fields are, so the
+ * finder reads the game's map API (POST /api/v1/map/position, the call the
+ * map page makes while scrolling) block by block around a center. Free
+ * valleys come back titled "{k.vt} {k.fN}" where fN is the field layout;
+ * oases "{k.fo}" (free) or "{k.bt}" (occupied), their bonus in the text as
+ * "{a.rN} 25%" (r4 = crop).
+ *
+ * Each cropper is rated by the crop bonus of the oases it could annex —
+ * the best three within 3 fields in each direction — and ranked by
+ * cropFields × (1 + oasis bonus), then distance. Settle time assumes
+ * settlers' 5 fields/h times the server's troop speed.
+ *
+ * Blocks already read are kept in memory for CACHE_MS: field layouts never
+ * change, so repeated searches around the same spot cost no requests.
+ *
+ * Runs in service worker context. Exported via self.TravianCropperFinder.
+ */
+(function(root) {
+  'use strict';
+
+  var BLOCK = 31;                  // fields per side read per request (zoom level 3 shows 31×31)
+  var MAX_RADIUS = 50;
+  var REQUEST_DELAY_MS = 400;
+  var CACHE_MS = 6 * 3600000;
+  var SETTLER_SPEED = 5;           // fields/h on a speed-1 server, same for every tribe
+  var OASIS_REACH = 3;
+  var MAX_OASES = 3;
+
+  /** Resource field layout per valley type: wood-clay-iron-crop */
+  var FIELD_TYPES = {
+    f1: '3-3-3-9', f2: '3-4-5-6', f3: '4-4-4-6', f4: '4-5-3-6', f5: '5-3-4-6', f6: '1-1-1-15',
+    f7: '4-4-3-7', f8: '3-4-4-7', f9: '4-3-4-7', f10: '3-5-4-6', f11: '4-3-5-6', f12: '5-4-3-6'
+  };
+
+  /** Which valley type each search type looks for */
+  var CROPPERS = { '9c': 'f1', '15c': 'f6' };
+
+  var _cache = {};   // serverKey → { "x|y" (block center): { at, tiles } }
+
+  var TravianCropperFinder = {};
+
+  TravianCropperFinder.FIELD_TYPES = FIELD_TYPES;
+
+  /**
+   * Sort map API tiles into free valleys and oases.
+   * @param {Array<Object>} tiles - { position: {x, y}, did, title, text }
+   * @returns {{ valleys: Array<{x, y, fieldType, layout}>,
+   *   oases: Array<{x, y, occupied, bonus: {wood, clay, iron, crop}}> }}
+   */
+  TravianCropperFinder.parseTiles = function(tiles) {
+    var valleys = [], oases = [];
+    (tiles || []).forEach(function(t) {
+      if (!t || !t.position) return;
+      var title = String(t.title || '');
+      var x = Number(t.position.x), y = Number(t.position.y);
+      var vt = /\{k\.vt\}\s*\{k\.(f\d+)\}/.exec(title);
+      if (vt && FIELD_TYPES[vt[1]]) {
+        valleys.push({ x: x, y: y, fieldType: vt[1], layout: FIELD_TYPES[vt[1]] });
+        return;
+      }
+      if (title.indexOf('{k.fo}') !== -1 || title.indexOf('{k.bt}') !== -1) {
+        var bonus = { wood: 0, clay: 0, iron: 0, crop: 0 };
+        var names = ['wood', 'clay', 'iron', 'crop'];
+        var re = /\{a\.r([1-4])\}\s*(\d+)%/g, m;
+        while ((m = re.exec(String(t.text || ''))) !== null) {
+          bonus[names[Number(m[1]) - 1]] += parseInt(m[2], 10);
+        }
+        oases.push({ x: x, y: y, occupied: title.indexOf('{k.bt}') !== -1, bonus: bonus });
+      }
+    });
+    return { valleys: valleys, oases: oases };
+  };
+
+  function _wrapDelta(a, b, size) {
+    var d = Math.abs(a - b);
+    return Math.min(d, size - d);
+  }
+
+  /**
+   * Rate and rank cropper valleys.
+   * @param {Array<Object>} valleys - From parseTiles
+   * @param {Array<Object>} oases - From parseTiles
+   * @param {{x: number, y: number}} center
+   * @param {{ radius?: number, type?: '9c'|'15c'|'both', serverSpeed?: number, mapRadius?: number }} [opts]
+   * @returns {Array<{ x, y, type, fieldType, layout, distance, oasisCrop, oases, settleHours, cropFactor }>}
+   *   Best first; oases = the annexable ones counted in oasisCrop ({x, y, crop, occupied})
+   */
+  TravianCropperFinder.rank = function(valleys, oases, center, opts) {
+    opts = opts || {};
+    var wanted = opts.type && opts.type !== 'both' ? [opts.type] : Object.keys(CROPPERS);
+    var mapRadius = opts.mapRadius || 200;
+    var size = 2 * mapRadius + 1;
+    var speed = SETTLER_SPEED * (opts.serverSpeed || 1);
+    var out = [];
+
+    valleys.forEach(function(v) {
+      var type = null;
+      wanted.forEach(function(w) { if (CROPPERS[w] === v.fieldType) type = w; });
+      if (!type) return;
+      var distance = root.TravianMapStore.distance(center, v, mapRadius);
+      if (opts.radius !== undefined && distance > opts.radius) return;
+
+      var near = oases.filter(function(o) {
+        return o.bonus.crop > 0 && _wrapDelta(o.x, v.x, size) <= OASIS_REACH && _wrapDelta(o.y, v.y, size) <= OASIS_REACH;
+      }).sort(function(a, b) { return b.bonus.crop - a.bonus.crop; }).slice(0, MAX_OASES);
+      var oasisCrop = near.reduce(function(sum, o) { return sum + o.bonus.crop; }, 0);
+      var cropFields = Number(v.layout.split('-')[3]);
+
+      out.push({
+        x: v.x, y: v.y, type: type, fieldType: v.fieldType, layout: v.layout,
+        distance: Math.round(distance * 10) / 10,
+        oasisCrop: oasisCrop,
+        oases: near.map(function(o) { return { x: o.x, y: o.y, crop: o.bonus.crop, occupied: o.occupied }; }),
+        settleHours: Math.round(distance / speed * 100) / 100,
+        cropFactor: Math.round(cropFields * (1 + oasisCrop / 100) * 100) / 100
+      });
+    });
+
+    out.sort(function(a, b) { return b.cropFactor - a.cropFactor || a.distance - b.distance; });
+    return out;
+  };
+
+  async function _fetchBlock(serverOrigin, x, y, cookieHeader) {
+    var resp = await fetch(serverOrigin + '/api/v1/map/position', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        'X-Requested-With': 'XMLHttpRequest',
+        'Cookie': cookieHeader || ''
+      },
+      body: JSON.stringify({ data: { x: x, y: y, zoomLevel: 3, ignorePositions: [] } })
+    });
+    if (!resp.ok) throw new Error('HTTP ' + resp.status + ' reading the map at (' + x + '|' + y + ')');
+    var json = await resp.json();
+    return json.tiles || [];
+  }
+
+  /**
+   * Search the map around a point for free croppers.
+   * @param {string} serverKey - Server hostname
+   * @param {{x: number, y: number}} center
+   * @param {number} [radius=20] - Capped at 50
+   * @param {'9c'|'15c'|'both'} [type='both']
+   * @param {{ cookieHeader?: string, serverSpeed?: number, mapRadius?: number }} [opts]
+   * @returns {Promise<{ entries: Array<Object>, requests: number, valleys: number, oases: number }>}
+   *   entries as from rank(); requests = map API calls made (cached blocks are free)
+   * @throws {Error} On a bad center/type or a failed map request
+   */
+  TravianCropperFinder.findCroppers = async function(serverKey, center, radius, type, opts) {
+    opts = opts || {};
+    if (!center || typeof center.x !== 'number' || typeof center.y !== 'number') {
+      throw new Error('center {x, y} required');
+    }
+    type = type || 'both';
+    if (type !== 'both' && !CROPPERS[type]) throw new Error('Unknown cropper type: ' + type + ' (9c, 15c or both)');
+    radius = Math.min(MAX_RADIUS, Math.max(1, radius || 20));
+    var mapRadius = opts.mapRadius || 200;
+    var size = 2 * mapRadius + 1;
+
+    // Block centers covering the square around center (+ oasis reach at the edge)
+    var reach = radius + OASIS_REACH;
+    var centers = [];
+    var half = (BLOCK - 1) / 2;
+    for (var dy = -reach; dy <= reach; dy += BLOCK) {
+      for (var dx = -reach; dx <= reach; dx += BLOCK) {
+        var bx = Math.min(dx + half, reach), by = Math.min(dy + half, reach);
+        // Wrap onto the map so blocks near the edge are cached under one key
+        centers.push({
+          x: ((center.x + bx + mapRadius) % size + size) % size - mapRadius,
+          y: ((center.y + by + mapRadius) % size + size) % size - mapRadius
+        });
+      }
+    }
+
+    var cache = _cache[serverKey] || (_cache[serverKey] = {});
+    var seen = {}, tiles = [], requests = 0;
+    for (var i = 0; i < centers.length; i++) {
+      var key = centers[i].x + '|' + centers[i].y;
+      var hit = cache[key];
+      if (!hit || Date.now() - hit.at > CACHE_MS) {
+        if (requests > 0) await new Promise(function(r) { setTimeout(r, REQUEST_DELAY_MS); });
+        hit = cache[key] = { at: Date.now(), tiles: await _fetchBlock('https://' + serverKey, centers[i].x, centers[i].y, opts.cookieHeader) };
+        requests++;
+      }
+      hit.tiles.forEach(function(t) {
+        var tk = t.position ? t.position.x + '|' + t.position.y : null;
+        if (tk && !seen[tk]) {
+          seen[tk] = true;
+          tiles.push(t);
+        }
+      });
+    }
+
+    var parsed = TravianCropperFinder.parseTiles(tiles);
+    var entries = TravianCropperFinder.rank(parsed.valleys, parsed.oases, center, {
+      radius: radius, type: type, serverSpeed: opts.serverSpeed, mapRadius: mapRadius
+    });
+    return { entries: entries, requests: requests, valleys: parsed.valleys.length, oases: parsed.oases.length };
+  };
+
+  root.TravianCropperFinder = TravianCropperFinder;
+})(typeof window !== 'undefined' ? window : self);
@@ -186,7 +186,8 @@ class DecisionEngine {
               type: action.type,
               params: action.params,
               priority: Math.max(1, 10 - Math.floor(action.score / 5)),
-              villageId: gameState.currentVillageId || null
+              villageId: gameState.currentVillageId || null,
+              reason: 'AI: ' + action.reason
             });
             aiHandledUpgradeOrTrain = true;
             break;
@@ -251,7 +252,8 @@ class DecisionEngine {
               type: sc.type,
               params: sc.params,
               priority: Math.max(1, 10 - Math.floor(sc.score / 5)),
-              villageId: gameState.currentVillageId || null
+              villageId: gameState.currentVillageId || null,
+              reason: 'Strategy: ' + sc.reason
             });
             break; // Only inject ONE strategy-derived task per cycle
           }
@@ -266,7 +268,7 @@ class DecisionEngine {
     if (!buildQueueFull && this.buildOptimizer && !this.isCoolingDown('upgrade_building')) {
       const overflowTask = this._evaluateStorageOverflow(gameState, taskQueue);
       if (overflowTask) {
-        newTasks.push(overflowTask);
+        newTasks.push(this._because(overflowTask, 'storage_overflow'));
       }
     }
 
@@ -275,7 +277,7 @@ class DecisionEngine {
     if (!buildQueueFull && !this.isCoolingDown('upgrade_building') && !this.isCoolingDown('build_new')) {
       const crannyTask = this._evaluateCrannyRule(gameState, config, taskQueue);
       if (crannyTask) {
-        newTasks.push(crannyTask);
+        newTasks.push(this._because(crannyTask, 'cranny_rule'));
       }
     }
 
@@ -283,7 +285,7 @@ class DecisionEngine {
     if (!buildQueueFull && !this.isCoolingDown('build_new')) {
       const buildNewTask = this._evaluateNewBuilds(gameState, config, taskQueue);
       if (buildNewTask) {
-        newTasks.push(buildNewTask);
+        newTasks.push(this._because(buildNewTask, 'new_build_selection'));
       }
     }
 
@@ -295,7 +297,7 @@ class DecisionEngine {
           !this.isCoolingDown('upgrade_resource') && !this.isCoolingDown('upgrade_building')) {
         const upgradeTask = this.evaluateUpgrades(gameState, config, autoRes, autoBld);
         if (upgradeTask && !taskQueue.hasTaskOfType(upgradeTask.type, upgradeTask.villageId)) {
-          newTasks.push(upgradeTask);
+          newTasks.push(this._because(upgradeTask, 'auto_upgrade'));
         }
       }
     }
@@ -314,7 +316,7 @@ class DecisionEngine {
               t.params.buildingType === tt.params.buildingType &&
               t.status !== 'completed' && t.status !== 'failed'
             );
-            if (!hasDup) newTasks.push(tt);
+            if (!hasDup) newTasks.push(this._because(tt, 'auto_train'));
           }
         }
       }
@@ -324,7 +326,7 @@ class DecisionEngine {
     if ((config.autoHeroAdventure) && !this.isCoolingDown('send_hero_adventure')) {
       const heroTask = this.evaluateHeroAdventure(gameState, config);
       if (heroTask && !taskQueue.hasTaskOfType('send_hero_adventure', heroTask.villageId)) {
-        newTasks.push(heroTask);
+        newTasks.push(this._because(heroTask, 'hero_adventure'));
       }
     }
 
@@ -332,7 +334,7 @@ class DecisionEngine {
     if (config.autoQuestClaim !== false && !this.isCoolingDown('claim_quest') && !taskQueue.hasAnyTaskOfType('claim_quest')) {
       const questTask = this._evaluateQuestClaiming(gameState);
       if (questTask) {
-        newTasks.push(questTask);
+        newTasks.push(this._because(questTask, 'quest_claim'));
       }
     }
 
@@ -342,7 +344,7 @@ class DecisionEngine {
       if (farmTasks && farmTasks.length > 0) {
         for (const ft of farmTasks) {
           if (!taskQueue.hasTaskOfType('send_farm', ft.villageId)) {
-            newTasks.push(ft);
+            newTasks.push(this._because(ft, 'farming'));
           }
         }
       }
@@ -351,19 +353,19 @@ class DecisionEngine {
     // 9. NPC marketplace trade — redistribute resources when imbalanced near overflow
     if (config.npcConfig && config.npcConfig.enabled && !this.isCoolingDown('npc_trade') && !taskQueue.hasAnyTaskOfType('npc_trade')) {
       const npcTask = this._evaluateNpcTrade(gameState, config);
-      if (npcTask) newTasks.push(npcTask);
+      if (npcTask) newTasks.push(this._because(npcTask, 'npc_rebalance'));
     }
 
     // 10. Auto-dodge troops on incoming attack
     if (config.dodgeConfig && config.dodgeConfig.enabled && !this.isCoolingDown('dodge_troops') && !taskQueue.hasAnyTaskOfType('dodge_troops')) {
       const dodgeTask = this._evaluateDodge(gameState, config);
-      if (dodgeTask) newTasks.push(dodgeTask);
+      if (dodgeTask) newTasks.push(this._because(dodgeTask, 'incoming_attack'));
     }
 
     // 11. Settler training — check if expansion is ready and settlers are needed
     if (!this.isCoolingDown('train_settlers') && !taskQueue.hasAnyTaskOfType('train_troops')) {
       const settlerTask = this._evaluateSettlerTraining(gameState, config);
-      if (settlerTask) newTasks.push(settlerTask);
+      if (settlerTask) newTasks.push(this._because(settlerTask, 'expansion'));
     }
 
     // Run custom rules
@@ -372,7 +374,7 @@ class DecisionEngine {
         const result = rule(gameState, config, taskQueue);
         if (result) {
           const tasks = Array.isArray(result) ? result : [result];
-          newTasks.push(...tasks);
+          newTasks.push(...tasks.map(t => this._because(t, 'custom_rule')));
         }
       } catch (err) {
         console.error('[DecisionEngine] Custom rule error:', err);
@@ -382,6 +384,18 @@ class DecisionEngine {
     return newTasks;
   }
 
+  /**
+   * Tag a task with the rule that produced it (kept if already set).
+   * Carried onto the queued task and into the action timeline.
+   * @param {object} task
+   * @param {string} reason
+   * @returns {object} The same task
+   */
+  _because(task, reason) {
+    if (task && !task.reason) task.reason = reason;
+    return task;
+  }
+
   // ---------------------------------------------------------------------------
   // Strategy-powered upgrade evaluation
   // ---------------------------------------------------------------------------
@@ -1749,6 +1763,7 @@ class DecisionEngine {
         ? (this.globalPlanner.planStepIndex + '/' + this.globalPlanner.activePlan.steps.length)
         : 'No plan',
       planName: this.globalPlanner.activePlan ? this.globalPlanner.activePlan.name : null,
+      planPinned: this.globalPlanner.planPinned,
       emergency: this.globalPlanner.emergencyOverride,
       cycleCount: this.globalPlanner.cycleCount,
       currentStep: this.lastPlannerContext ? this.lastPlannerContext.planStep : null,
@@ -0,0 +1,161 @@
+/**
+ * Discord — Posts bot alerts to a Discord channel webhook as embeds.
+ *
+ * Each alert kind has its own embed template (colour, title prefix) and can
+ * be switched on or off. Posting is rate limited below Discord's webhook
+ * limit (30/min per channel); alerts over the limit are dropped, not queued,
+ * so a burst never turns into minutes of stale messages.
+ *
+ * Settings are global, stored under 'discord_config':
+ *   { enabled, webhookUrl, events: { attack, raid, adventure, crop, session, emergency, captcha } }
+ *
+ * Runs in service worker context. Exported via self.TravianDiscord.
+ */
+(function(root) {
+  'use strict';
+
+  var STORAGE_KEY = 'discord_config';
+  var WEBHOOK_PATTERN = /^https:\/\/(discord|discordapp)\.com\/api\/webhooks\/\d+\/[\w-]+$/;
+
+  var RATE_WINDOW_MS = 60000;
+  var RATE_MAX = 25;
+
+  /** Per-kind embed templates; `default` says whether the kind is on out of the box */
+  var TEMPLATES = {
+    attack:    { color: 0xff3366, icon: '⚔️', default: true },
+    emergency: { color: 0xff9900, icon: '🛑', default: true },
+    captcha:   { color: 0xff33cc, icon: '🧩', default: true },
+    session:   { color: 0xffcc00, icon: '🔑', default: true },
+    crop:      { color: 0xcc6600, icon: '🌾', default: true },
+    raid:      { color: 0x33aa55, icon: '🐎', default: false },
+    adventure: { color: 0x3388ff, icon: '🗺️', default: false }
+  };
+
+  var _sentAt = [];        // timestamps of recent posts (sliding window)
+  var _blockedUntil = 0;   // set from a 429 retry_after
+  var _dropped = 0;
+
+  var TravianDiscord = {};
+
+  TravianDiscord.TEMPLATES = TEMPLATES;
+
+  // ── Config ──────────────────────────────────────────────────────────
+
+  /**
+   * @returns {Promise<{enabled: boolean, webhookUrl: string|null, events: Object.<string, boolean>}>}
+   */
+  TravianDiscord.getConfig = async function() {
+    var stored = await root.TravianStorage.get(STORAGE_KEY, {});
+    var events = {};
+    for (var kind in TEMPLATES) {
+      var v = stored.events && stored.events[kind];
+      events[kind] = typeof v === 'boolean' ? v : TEMPLATES[kind].default;
+    }
+    return { enabled: !!stored.enabled, webhookUrl: stored.webhookUrl || null, events: events };
+  };
+
+  /**
+   * Merge and save Discord settings. A missing webhookUrl, or the masked
+   * one redact() handed to the UI, keeps the stored URL.
+   * @param {Object} patch - Any of enabled, webhookUrl, events
+   * @returns {Promise<Object>} Saved config
+   * @throws {Error} If webhookUrl is not a Discord webhook URL
+   */
+  TravianDiscord.saveConfig = async function(patch) {
+    var next = await TravianDiscord.getConfig();
+    if (patch.enabled !== undefined) next.enabled = !!patch.enabled;
+    var masked = next.webhookUrl && patch.webhookUrl === _mask(next.webhookUrl);
+    if (patch.webhookUrl !== undefined && !masked) {
+      var url = patch.webhookUrl ? String(patch.webhookUrl).trim() : null;
+      if (url && !WEBHOOK_PATTERN.test(url)) throw new Error('Not a Discord webhook URL');
+      next.webhookUrl = url;
+    }
+    if (patch.events && typeof patch.events === 'object') {
+      for (var kind in patch.events) {
+        if (TEMPLATES[kind]) next.events[kind] = !!patch.events[kind];
+      }
+    }
+    await root.TravianStorage.set(STORAGE_KEY, next);
+    return next;
+  };
+
+  /** Config safe to hand to the UI (webhook secret masked). */
+  TravianDiscord.redact = function(cfg) {
+    var out = Object.assign({}, cfg);
+    if (out.webhookUrl) out.webhookUrl = _mask(out.webhookUrl);
+    return out;
+  };
+
+  function _mask(url) {
+    return url.replace(/\/[\w-]+$/, '/…');
+  }
+
+  // ── Posting ─────────────────────────────────────────────────────────
+
+  /**
+   * Post an alert if its kind is enabled and the rate limit allows.
+   * @param {Object} cfg - From getConfig()
+   * @param {string} kind - Key of TEMPLATES
+   * @param {string} title
+   * @param {string} message
+   * @returns {Promise<boolean>} True if posted
+   */
+  TravianDiscord.sendAlert = async function(cfg, kind, title, message) {
+    if (!cfg.enabled || !cfg.webhookUrl || !cfg.events[kind]) return false;
+    var tpl = TEMPLATES[kind];
+    return _post(cfg.webhookUrl, {
+      embeds: [{
+        title: (tpl.icon + ' ' + title).slice(0, 256),
+        description: String(message).slice(0, 4000),
+        color: tpl.color,
+        timestamp: new Date().toISOString()
+      }]
+    });
+  };
+
+  /**
+   * Post a plain test message (ignores event toggles, respects rate limit).
+   * @param {Object} cfg
+   * @returns {Promise<boolean>}
+   */
+  TravianDiscord.sendTest = function(cfg) {
+    if (!cfg.webhookUrl) return Promise.reject(new Error('Webhook URL required'));
+    return _post(cfg.webhookUrl, { content: '🔔 Travian Assistant connected.' });
+  };
+
+  /** @returns {{ sentLastMinute: number, dropped: number, blockedUntil: number }} */
+  TravianDiscord.getRateStatus = function() {
+    _prune(Date.now());
+    return { sentLastMinute: _sentAt.length, dropped: _dropped, blockedUntil: _blockedUntil };
+  };
+
+  function _prune(now) {
+    while (_sentAt.length > 0 && now - _sentAt[0] > RATE_WINDOW_MS) _sentAt.shift();
+  }
+
+  async function _post(url, body) {
+    var now = Date.now();
+    _prune(now);
+    if (now < _blockedUntil || _sentAt.length >= RATE_MAX) {
+      _dropped++;
+      return false;
+    }
+    _sentAt.push(now);
+
+    var resp = await fetch(url, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(body)
+    });
+    if (resp.status === 429) {
+      var info = await resp.json().catch(function() { return {}; });
+      _blockedUntil = Date.now() + Math.ceil((info.retry_after || 5) * 1000);
+      _dropped++;
+      return false;
+    }
+    if (!resp.ok) throw new Error('Discord webhook failed: HTTP ' + resp.status);
+    return true;
+  }
+
+  root.TravianDiscord = TravianDiscord;
+})(typeof window !== 'undefined' ? window : self);
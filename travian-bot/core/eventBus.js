@@ -18,8 +18,17 @@
     this._listeners = {};
     /** @type {Array<{event: string, data: *, timestamp: number}>} */
     this._history = [];
+    /**
+     * Per-event-type replay buffers. The shared history is dominated by
+     * scan:complete, so rare events (attacks) would fall out of it quickly.
+     * @type {Object.<string, Array<{data: *, timestamp: number}>>}
+     */
+    this._recent = {};
   }
 
+  /** Max entries kept per event type in the replay buffer. */
+  TravianEventBus.RECENT_PER_EVENT = 20;
+
   /**
    * Subscribe to an event.
    * @param {string} event - Event name (use TravianEventBus.Events constants)
@@ -56,10 +65,24 @@
    * @param {*} data - Event payload
    */
   TravianEventBus.prototype.emit = function(event, data) {
+    // Validate known payload shapes. Malformed events are still delivered —
+    // dropping an attack alert over a missing field would be worse — but a
+    // diagnostic event is raised so scanner/analyzer regressions show up.
+    var errors = TravianEventBus.validatePayload(event, data);
+    if (errors.length > 0) {
+      console.warn('[EventBus] Malformed ' + event + ' payload: ' + errors.join('; '));
+      this.emit(TravianEventBus.Events.MALFORMED_EVENT, { event: event, errors: errors });
+    }
+
     // Record in history (ring buffer of 50)
     this._history.push({ event: event, data: data, timestamp: Date.now() });
     if (this._history.length > 50) this._history.shift();
 
+    // Per-type replay buffer
+    var recent = this._recent[event] || (this._recent[event] = []);
+    recent.push({ data: data, timestamp: Date.now() });
+    if (recent.length > TravianEventBus.RECENT_PER_EVENT) recent.shift();
+
     var listeners = this._listeners[event];
     if (!listeners || listeners.length === 0) return;
 
@@ -115,6 +138,46 @@
     return this._history.slice(-n);
   };
 
+  /**
+   * Get recent events for backfilling a late-opened view.
+   * @param {string} [event] - Event name; if omitted, returns all types
+   * @param {number} [count] - Max entries per type (most recent)
+   * @returns {Array|Object.<string, Array>} Entries for one type, or a map of type → entries
+   */
+  TravianEventBus.prototype.getRecent = function(event, count) {
+    var n = count || TravianEventBus.RECENT_PER_EVENT;
+    if (event) return (this._recent[event] || []).slice(-n);
+
+    var out = {};
+    for (var name in this._recent) {
+      out[name] = this._recent[name].slice(-n);
+    }
+    return out;
+  };
+
+  /**
+   * Check an event payload against EventSchemas.
+   * @param {string} event
+   * @param {*} data
+   * @returns {string[]} Validation errors (empty if valid or no schema)
+   */
+  TravianEventBus.validatePayload = function(event, data) {
+    var schema = TravianEventBus.EventSchemas[event];
+    if (!schema) return [];
+    if (!data || typeof data !== 'object') return ['payload is not an object'];
+
+    var errors = [];
+    for (var field in schema) {
+      var expected = schema[field];
+      var value = data[field];
+      var actual = Array.isArray(value) ? 'array' : (value === null ? 'null' : typeof value);
+      if (actual !== expected) {
+        errors.push(field + ': expected ' + expected + ', got ' + actual);
+      }
+    }
+    return errors;
+  };
+
   // ── Event type constants ──────────────────────────────────────────
   TravianEventBus.Events = Object.freeze({
     OVERFLOW_IMMINENT:  'overflow:imminent',   // storage about to fill
@@ -125,7 +188,38 @@
     TASK_FAILED:        'task:failed',          // a task failed
     SCAN_COMPLETE:      'scan:complete',        // DOM scan finished
     PHASE_CHANGED:      'phase:changed',        // strategy phase shifted
-    CROP_CRISIS:        'crop:crisis'           // free crop dangerously low
+    CROP_CRISIS:        'crop:crisis',          // free crop dangerously low
+    SESSION_LOST:       'session:lost',         // game page shows logged-out state
+    BUILD_IDLE:         'build:idle',           // village build queue just ran empty
+    BOT_EMERGENCY:      'bot:emergency',        // bot stopped itself (errors, failed safety checks, ...)
+    CAPTCHA_DETECTED:   'bot:captcha',          // captcha / bot-protection page; bot paused until solved
+    SCHEDULE_FIRED:     'schedule:fired',       // a scheduled job ran (see TravianSchedules)
+    CLOCK_DRIFT:        'clock:drift',          // local clock off from / jumped against the server
+    SYSTEM_RESUMED:     'system:resumed',       // computer woke from sleep; re-syncing
+    NETWORK_OFFLINE:    'network:offline',      // connection lost; bot paused
+    NETWORK_ONLINE:     'network:online',       // connection back; bot resumed
+    MALFORMED_EVENT:    'event:malformed'       // payload failed EventSchemas check
+  });
+
+  // ── Payload schemas (required field → type) ───────────────────────
+  // Only fields that subscribers rely on are listed; extra fields are allowed.
+  TravianEventBus.EventSchemas = Object.freeze({
+    'overflow:imminent': { overflow: 'object', worstResource: 'string', hoursUntilFull: 'number' },
+    'attack:incoming':   { attacks: 'array', count: 'number', soonest: 'object' },
+    'quest:claimable':   { quests: 'array', count: 'number' },
+    'scan:complete':     { timestamp: 'number' },
+    'crop:crisis':       { freeCrop: 'number', cropProduction: 'number' },
+    'session:lost':      { notLoggedInCount: 'number', maxCount: 'number' },
+    'build:idle':        { previousCount: 'number' },
+    'task:completed':    { taskId: 'string', type: 'string' },
+    'task:failed':       { taskId: 'string', type: 'string', error: 'string' },
+    'bot:emergency':     { reason: 'string' },
+    'bot:captcha':       { reason: 'string' },
+    'schedule:fired':    { scheduleId: 'string', action: 'string', ok: 'boolean' },
+    'clock:drift':       { offsetMs: 'number', message: 'string' },
+    'system:resumed':    { sleptMs: 'number' },
+    'network:online':    { offlineMs: 'number' },
+    'event:malformed':   { event: 'string', errors: 'array' }
   });
 
   root.TravianEventBus = TravianEventBus;
@@ -0,0 +1,151 @@
+/**
+ * FarmFinder — Ranks inactive and small villages from the stored map as
+ * raid targets.
+ *
+ * Works on TravianMapStore's daily snapshots (refresh the map first). A
+ * village is a good farm when its owner has stopped playing: the player's
+ * total population hasn't grown over the last `minDays` dumps (player
+ * inactive), or at least this village hasn't (village stagnant). Villages
+ * of growing players are left out unless filters.includeActive is set.
+ *
+ * Score (higher is better):
+ *   base     3 player inactive, 2 player shrinking (being farmed/chiefed
+ *            is fine — they're not defending), 1 village stagnant, 0.3 active
+ *   × pop    sqrt(pop) — bigger villages keep producing more, but an
+ *            inactive 400-pop village is not worth 20 of a 1-pop one
+ *   ÷ dist   1 + distance / 10 — troops spend the trip walking
+ * Each entry lists the reasons behind its score.
+ *
+ * Runs in service worker context. Exported via self.TravianFarmFinder.
+ */
+(function(root) {
+  'use strict';
+
+  var DEFAULTS = {
+    maxPop: 300,          // bigger villages are rarely undefended
+    minPop: 1,
+    minDays: 3,           // dumps of history needed to call a village inactive
+    includeActive: false,
+    excludeNatars: true,
+    limit: 50
+  };
+
+  var NATARS = 5;
+  var MAX_LIMIT = 200;
+
+  var TravianFarmFinder = {};
+
+  TravianFarmFinder.DEFAULTS = DEFAULTS;
+
+  function _idSet(list) {
+    var set = {};
+    (Array.isArray(list) ? list : []).forEach(function(v) { set[Number(v)] = true; });
+    return set;
+  }
+
+  /**
+   * Classify one village and score it.
+   * @param {Object} village - Query entry from TravianMapStore (with distance)
+   * @param {Object|undefined} player - The owner's players row
+   * @param {number} minDays
+   * @returns {{ activity: string, score: number, reasons: string[] }}
+   *   activity: 'inactive' | 'shrinking' | 'stagnant' | 'active' | 'unknown'
+   */
+  TravianFarmFinder.rate = function(village, player, minDays) {
+    var vGrowth = root.TravianMapStore.growth(village.history, minDays);
+    var pGrowth = player ? root.TravianMapStore.growth(player.history, minDays) : vGrowth;
+    var reasons = [];
+    var activity, base;
+
+    if (pGrowth.days < minDays) {
+      activity = 'unknown';
+      base = 0.3;
+      reasons.push('only ' + (pGrowth.days + 1) + ' day(s) of history');
+    } else if (pGrowth.change < 0) {
+      activity = 'shrinking';
+      base = 2;
+      reasons.push('player lost ' + (-pGrowth.change) + ' pop in ' + pGrowth.days + 'd');
+    } else if (pGrowth.change === 0) {
+      activity = 'inactive';
+      base = 3;
+      reasons.push('player pop unchanged for ' + pGrowth.days + 'd');
+    } else if (vGrowth.days >= minDays && vGrowth.change <= 0) {
+      activity = 'stagnant';
+      base = 1;
+      reasons.push('village pop unchanged for ' + vGrowth.days + 'd (player +' + pGrowth.change + ')');
+    } else {
+      activity = 'active';
+      base = 0.3;
+      reasons.push('player +' + pGrowth.change + ' pop in ' + pGrowth.days + 'd');
+    }
+
+    var dist = village.distance || 0;
+    reasons.push(village.pop + ' pop, ' + dist + ' fields away');
+    var score = base * Math.sqrt(Math.max(1, village.pop)) / (1 + dist / 10);
+    return { activity: activity, score: Math.round(score * 100) / 100, reasons: reasons };
+  };
+
+  /**
+   * Rank raid targets around a point.
+   * @param {string} serverKey
+   * @param {{x: number, y: number}} center
+   * @param {number} radius - Fields (wrap-around distance)
+   * @param {Object} [filters]
+   * @param {number} [filters.maxPop=300]
+   * @param {number} [filters.minPop=1]
+   * @param {number} [filters.minDays=3] - Dumps of history needed; younger villages rank as 'unknown'
+   * @param {boolean} [filters.includeActive=false] - Also list villages of growing players
+   * @param {boolean} [filters.excludeNatars=true]
+   * @param {number} [filters.tribe]
+   * @param {number[]} [filters.excludeUids] - e.g. yourself
+   * @param {number[]} [filters.excludeAlliances] - e.g. your own alliance and its confeds
+   * @param {string[]} [filters.excludeCoords] - "x|y" already farmed or blacklisted
+   * @param {number} [filters.limit=50] - Capped at 200
+   * @returns {Promise<{ entries: Array<Object>, total: number, meta: Object|null }>}
+   *   Entries: { fieldId, x, y, name, uid, player, aid, alliance, tribe, pop, distance,
+   *   activity, score, reasons }, best first
+   * @throws {Error} If no map is stored for the server or the center is missing
+   */
+  TravianFarmFinder.findFarms = async function(serverKey, center, radius, filters) {
+    var f = Object.assign({}, DEFAULTS, filters || {});
+    if (!center || typeof center.x !== 'number' || typeof center.y !== 'number') {
+      throw new Error('center {x, y} required');
+    }
+    var meta = await root.TravianMapStore.getMeta(serverKey);
+    if (!meta) throw new Error('No map stored for ' + serverKey + ' — refresh the map first');
+
+    // Nearest first, so a crowded radius loses its far edge rather than random villages
+    var villages = await root.TravianMapStore.query(serverKey, {
+      kind: 'villages', center: center, radius: radius || 20, sort: 'distance',
+      minPop: f.minPop, maxPop: f.maxPop, tribe: f.tribe, limit: 500
+    });
+    var uids = {};
+    villages.entries.forEach(function(v) { if (v.uid > 0) uids[v.uid] = true; });
+    var players = await root.TravianMapStore.getPlayers(serverKey, Object.keys(uids));
+
+    var skipUids = _idSet(f.excludeUids);
+    var skipAlliances = _idSet(f.excludeAlliances);
+    var skipCoords = {};
+    (f.excludeCoords || []).forEach(function(k) { skipCoords[k] = true; });
+
+    var hits = [];
+    villages.entries.forEach(function(v) {
+      if (v.uid === 0) return;   // unoccupied field
+      if (f.excludeNatars && v.tribe === NATARS) return;
+      if (skipUids[v.uid] || (v.aid && skipAlliances[v.aid]) || skipCoords[v.x + '|' + v.y]) return;
+      var rating = TravianFarmFinder.rate(v, players[v.uid], f.minDays);
+      if (rating.activity === 'active' && !f.includeActive) return;
+      hits.push({
+        fieldId: v.fieldId, x: v.x, y: v.y, name: v.name, uid: v.uid, player: v.player,
+        aid: v.aid, alliance: v.alliance, tribe: v.tribe, pop: v.pop, distance: v.distance,
+        activity: rating.activity, score: rating.score, reasons: rating.reasons
+      });
+    });
+
+    hits.sort(function(a, b) { return b.score - a.score || a.distance - b.distance; });
+    var limit = Math.min(MAX_LIMIT, Math.max(1, f.limit));
+    return { entries: hits.slice(0, limit), total: hits.length, meta: meta };
+  };
+
+  root.TravianFarmFinder = TravianFarmFinder;
+})(typeof window !== 'undefined' ? window : self);
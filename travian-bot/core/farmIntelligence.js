@@ -234,6 +234,24 @@
       gs.totalLoot.iron += (entry.loot.iron || 0);
       gs.totalLoot.crop += (entry.loot.crop || 0);
       gs.totalTroopLosses += entry.totalLosses;
+
+      // Long-term raid history (FarmIntelligence keeps only MAX_HISTORY per target)
+      if (typeof self !== 'undefined' && self.TravianHistoryStore) {
+        self.TravianHistoryStore.recordRaid(this._serverKey, {
+          ts: entry.timestamp,
+          target: key,
+          x: x,
+          y: y,
+          name: t.name,
+          source: entry.source,
+          troopsSent: entry.troopsSent,
+          troopsLost: entry.troopsLost,
+          totalLosses: entry.totalLosses,
+          loot: entry.loot,
+          totalLoot: entry.totalLoot,
+          bountyFull: entry.bountyFull
+        });
+      }
     }
 
     // Recompute metrics and evaluate status
@@ -0,0 +1,974 @@
+/**
+ * HistoryStore — IndexedDB-backed history that survives service worker
+ * restarts.
+ *
+ * TravianLogger keeps only the last 500 entries (in memory + one
+ * chrome.storage key). Every log entry is also appended here, so past logs
+ * can be paged and filtered long after they scrolled out of the popup.
+ *
+ * Object stores (database 'travian_history'):
+ *   logs      — { id, ts, level, message, data, serverKey, terms }
+ *               indexes: ts, serverKey_ts [serverKey, ts], terms (multiEntry)
+ *               (terms = lower-cased words of the message, for searchLogs)
+ *   resources — { id, ts, serverKey, villageId, resources, production, capacity }
+ *               indexes: ts, village_ts [serverKey, villageId, ts]
+ *               (one sample per village every RESOURCE_SAMPLE_MS at most)
+ *   raids     — { id, ts, resultAt, serverKey, target, x, y, name, source,
+ *                 troopsSent, troopsLost, totalLosses, loot, totalLoot, bountyFull }
+ *               indexes: ts, serverKey_ts [serverKey, ts]
+ *               (one row per raid result reported to FarmIntelligence)
+ *   actions   — { id, ts, serverKey, villageId, taskId, type, params, status,
+ *                 reason, error, summary }
+ *               indexes: ts, serverKey_ts [serverKey, ts]
+ *               (one row per task that completed or permanently failed)
+ *   alerts    — { id, ts, serverKey, kind, severity, title, message,
+ *                 acknowledged (0|1), ackAt, repeats }
+ *               indexes: ts, ack_ts [acknowledged, ts]
+ *               (kept by TravianAlertCenter; written directly, not buffered,
+ *               because rows are updated when acknowledged)
+ *   screenshots — { id, ts, serverKey, reason, alertId, url, dataUrl, bytes }
+ *               indexes: ts, serverKey_ts [serverKey, ts]
+ *               (JPEG data URLs of the game tab, taken on request or as
+ *               evidence for attack/captcha alerts; written directly)
+ *
+ * Writes are buffered and committed in one transaction every couple of
+ * seconds. prune() applies RETENTION (max age + max rows per store); the
+ * service worker runs it from the daily 'historyPrune' alarm.
+ *
+ * Runs in service worker context. Exported via self.TravianHistoryStore.
+ */
+(function(root) {
+  'use strict';
+
+  var DB_NAME = 'travian_history';
+  var DB_VERSION = 7;
+  var FLUSH_DELAY_MS = 2000;
+  var FLUSH_MAX_BUFFER = 200;
+  var DAY_MS = 86400000;
+  var RESOURCE_SAMPLE_MS = 5 * 60000;
+  var MAX_POINTS = 500;
+  var MAX_TERMS = 64;            // indexed words per log message
+  var MAX_SEARCH_SCAN = 20000;   // candidate rows examined per search
+
+  var RESOURCE_KEYS = ['wood', 'clay', 'iron', 'crop'];
+  var CAPACITY_KEYS = ['warehouse', 'granary'];
+
+  /** Named resolutions for getResourceHistory (bucket size in ms) */
+  var RESOLUTIONS = { raw: 0, '5m': 300000, '15m': 900000, '1h': 3600000, '6h': 21600000, '1d': DAY_MS };
+
+  /** Retention policy per store */
+  var RETENTION = {
+    logs:      { maxAgeDays: 14, maxRows: 100000 },
+    resources: { maxAgeDays: 90, maxRows: 200000 },
+    raids:     { maxAgeDays: 180, maxRows: 200000 },
+    actions:   { maxAgeDays: 60, maxRows: 100000 },
+    alerts:    { maxAgeDays: 90, maxRows: 10000 },
+    screenshots: { maxAgeDays: 30, maxRows: 200 }   // ~100-300 KB each
+  };
+
+  var LOG_LEVELS = { DEBUG: 0, INFO: 1, WARN: 2, ERROR: 3 };
+
+  var _dbPromise = null;
+  var _buffer = [];      // pending { store, record }
+  var _flushTimer = null;
+  var _lastSampleAt = {}; // 'serverKey|villageId' → ms
+
+  var TravianHistoryStore = {};
+
+  TravianHistoryStore.RETENTION = RETENTION;
+  TravianHistoryStore.RESOLUTIONS = RESOLUTIONS;
+
+  // ── Database ────────────────────────────────────────────────────────
+
+  function _open() {
+    if (_dbPromise) return _dbPromise;
+    _dbPromise = new Promise(function(resolve, reject) {
+      var req = root.indexedDB.open(DB_NAME, DB_VERSION);
+      req.onupgradeneeded = function(e) {
+        var db = req.result;
+        if (e.oldVersion < 1) {
+          var logs = db.createObjectStore('logs', { keyPath: 'id', autoIncrement: true });
+          logs.createIndex('ts', 'ts');
+          logs.createIndex('serverKey_ts', ['serverKey', 'ts']);
+        }
+        if (e.oldVersion < 2) {
+          var resources = db.createObjectStore('resources', { keyPath: 'id', autoIncrement: true });
+          resources.createIndex('ts', 'ts');
+          resources.createIndex('village_ts', ['serverKey', 'villageId', 'ts']);
+        }
+        if (e.oldVersion < 3) {
+          var raids = db.createObjectStore('raids', { keyPath: 'id', autoIncrement: true });
+          raids.createIndex('ts', 'ts');
+          raids.createIndex('serverKey_ts', ['serverKey', 'ts']);
+        }
+        if (e.oldVersion < 4) {
+          var actions = db.createObjectStore('actions', { keyPath: 'id', autoIncrement: true });
+          actions.createIndex('ts', 'ts');
+          actions.createIndex('serverKey_ts', ['serverKey', 'ts']);
+        }
+        if (e.oldVersion < 5) {
+          var logStore = req.transaction.objectStore('logs');
+          logStore.createIndex('terms', 'terms', { multiEntry: true });
+          // Index logs written before v5
+          var backfill = logStore.openCursor();
+          backfill.onsuccess = function() {
+            var cursor = backfill.result;
+            if (!cursor) return;
+            if (!cursor.value.terms) {
+              var row = cursor.value;
+              row.terms = _terms(row.message);
+              cursor.update(row);
+            }
+            cursor.continue();
+          };
+        }
+        if (e.oldVersion < 6) {
+          var alerts = db.createObjectStore('alerts', { keyPath: 'id', autoIncrement: true });
+          alerts.createIndex('ts', 'ts');
+          alerts.createIndex('ack_ts', ['acknowledged', 'ts']);
+        }
+        if (e.oldVersion < 7) {
+          var shots = db.createObjectStore('screenshots', { keyPath: 'id', autoIncrement: true });
+          shots.createIndex('ts', 'ts');
+          shots.createIndex('serverKey_ts', ['serverKey', 'ts']);
+        }
+      };
+      req.onsuccess = function() { resolve(req.result); };
+      req.onerror = function() {
+        _dbPromise = null;
+        reject(req.error);
+      };
+    });
+    return _dbPromise;
+  }
+
+  function _done(tx) {
+    return new Promise(function(resolve, reject) {
+      tx.oncomplete = function() { resolve(); };
+      tx.onerror = tx.onabort = function() { reject(tx.error); };
+    });
+  }
+
+  /**
+   * Walk a store (or one of its indexes) with a cursor.
+   * @param {string} storeName
+   * @param {string|null} indexName
+   * @param {IDBKeyRange|null} range
+   * @param {string} direction - 'next' | 'prev'
+   * @param {function(Object, IDBCursor): boolean} visit - return false to stop
+   * @param {string} [mode='readonly']
+   * @returns {Promise<void>}
+   */
+  async function _iterate(storeName, indexName, range, direction, visit, mode) {
+    var db = await _open();
+    var tx = db.transaction(storeName, mode || 'readonly');
+    var source = tx.objectStore(storeName);
+    if (indexName) source = source.index(indexName);
+    var req = source.openCursor(range, direction);
+    req.onsuccess = function() {
+      var cursor = req.result;
+      if (!cursor) return;
+      if (visit(cursor.value, cursor) !== false) cursor.continue();
+    };
+    return _done(tx);
+  }
+
+  // ── Buffered writes ─────────────────────────────────────────────────
+
+  function _enqueue(store, record) {
+    _buffer.push({ store: store, record: record });
+    if (_buffer.length >= FLUSH_MAX_BUFFER) {
+      TravianHistoryStore.flush();
+    } else if (!_flushTimer) {
+      _flushTimer = setTimeout(TravianHistoryStore.flush, FLUSH_DELAY_MS);
+    }
+  }
+
+  /**
+   * Commit buffered records. Safe to call at any time.
+   * @returns {Promise<void>}
+   */
+  TravianHistoryStore.flush = async function() {
+    if (_flushTimer) {
+      clearTimeout(_flushTimer);
+      _flushTimer = null;
+    }
+    if (_buffer.length === 0) return;
+    var batch = _buffer;
+    _buffer = [];
+    try {
+      var db = await _open();
+      var stores = [];
+      batch.forEach(function(b) { if (stores.indexOf(b.store) === -1) stores.push(b.store); });
+      var tx = db.transaction(stores, 'readwrite');
+      batch.forEach(function(b) { tx.objectStore(b.store).add(b.record); });
+      await _done(tx);
+    } catch (err) {
+      console.warn('[HistoryStore] flush failed, dropped ' + batch.length + ' records: ' + (err && err.message));
+    }
+  };
+
+  /** Make `data` safe for structured clone (drops functions, DOM nodes, cycles). */
+  function _plain(data) {
+    if (data === null || data === undefined) return null;
+    if (typeof data !== 'object') return data;
+    try {
+      return JSON.parse(JSON.stringify(data));
+    } catch (_) {
+      return String(data);
+    }
+  }
+
+  // ── Logs ────────────────────────────────────────────────────────────
+
+  var WORD_PATTERN = /[\p{L}\p{N}]+/gu;
+
+  function _words(text) {
+    return String(text).toLowerCase().match(WORD_PATTERN) || [];
+  }
+
+  /** Distinct searchable words of a message (single letters dropped, numbers kept). */
+  function _terms(text) {
+    var seen = {};
+    var out = [];
+    _words(text).forEach(function(w) {
+      if (seen[w] || (w.length < 2 && !/^\d$/.test(w)) || out.length >= MAX_TERMS) return;
+      seen[w] = true;
+      out.push(w);
+    });
+    return out;
+  }
+
+  function _escapeHtml(s) {
+    return s.replace(/[&<>"']/g, function(c) {
+      return { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c];
+    });
+  }
+
+  /**
+   * Append a TravianLogger entry (buffered).
+   * @param {{ timestamp: string, level: string, message: string, data: *, serverKey?: string }} entry
+   */
+  TravianHistoryStore.appendLog = function(entry) {
+    _enqueue('logs', {
+      ts: Date.parse(entry.timestamp) || Date.now(),
+      level: entry.level,
+      message: String(entry.message),
+      data: _plain(entry.data),
+      serverKey: entry.serverKey || '',
+      terms: _terms(entry.message)
+    });
+  };
+
+  /**
+   * Page through stored logs, newest first.
+   * @param {Object} [filter]
+   * @param {string} [filter.serverKey] - Only this server
+   * @param {string} [filter.level] - Minimum level (DEBUG/INFO/WARN/ERROR)
+   * @param {number} [filter.since] - Epoch ms, inclusive
+   * @param {number} [filter.until] - Epoch ms, inclusive
+   * @param {string} [filter.text] - Case-insensitive substring of the message
+   * @param {number} [offset=0] - Matching entries to skip
+   * @param {number} [limit=100] - Max entries (capped at 1000)
+   * @returns {Promise<{ entries: Array<Object>, hasMore: boolean }>}
+   */
+  TravianHistoryStore.queryLogs = async function(filter, offset, limit) {
+    filter = filter || {};
+    offset = Math.max(0, offset || 0);
+    limit = Math.min(1000, Math.max(1, limit || 100));
+    await TravianHistoryStore.flush();
+
+    var since = filter.since || 0;
+    var until = filter.until || Date.now() + 1000;
+    var minLevel = filter.level ? LOG_LEVELS[String(filter.level).toUpperCase()] || 0 : 0;
+    var text = filter.text ? String(filter.text).toLowerCase() : null;
+
+    var indexName, range;
+    if (filter.serverKey) {
+      indexName = 'serverKey_ts';
+      range = IDBKeyRange.bound([filter.serverKey, since], [filter.serverKey, until]);
+    } else {
+      indexName = 'ts';
+      range = IDBKeyRange.bound(since, until);
+    }
+
+    var entries = [];
+    var skipped = 0;
+    var hasMore = false;
+    await _iterate('logs', indexName, range, 'prev', function(row) {
+      if ((LOG_LEVELS[row.level] || 0) < minLevel) return true;
+      if (text && row.message.toLowerCase().indexOf(text) === -1) return true;
+      if (skipped < offset) { skipped++; return true; }
+      if (entries.length === limit) { hasMore = true; return false; }
+      delete row.terms;
+      entries.push(row);
+      return true;
+    });
+    return { entries: entries, hasMore: hasMore };
+  };
+
+  function _prefixRange(term) {
+    return IDBKeyRange.bound(term, term + '\uffff');
+  }
+
+  function _countIndex(storeName, indexName, range) {
+    return _open().then(function(db) {
+      return new Promise(function(resolve, reject) {
+        var store = db.transaction(storeName, 'readonly').objectStore(storeName);
+        var req = indexName ? store.index(indexName).count(range) : store.count();
+        req.onsuccess = function() { resolve(req.result); };
+        req.onerror = function() { reject(req.error); };
+      });
+    });
+  }
+
+  /**
+   * Full-text search over stored logs. Every query word must match the
+   * start of a word in the message ("vill" finds "village"). Results are
+   * ranked by term rarity and frequency (BM25-style), newest first on ties.
+   * @param {string} query
+   * @param {Object} [filter]
+   * @param {string} [filter.serverKey]
+   * @param {string} [filter.level] - Minimum level (DEBUG/INFO/WARN/ERROR)
+   * @param {number} [filter.since] - Epoch ms, inclusive
+   * @param {number} [filter.until] - Epoch ms, inclusive
+   * @param {number} [limit=50] - Max matches (capped at 500)
+   * @returns {Promise<{ matches: Array<Object>, total: number, truncated: boolean }>}
+   *   Each match is the log row plus score, spans ([start, end] offsets of
+   *   matched words in message) and highlight (HTML-escaped message with
+   *   <mark> around matched words). truncated is set when more than
+   *   MAX_SEARCH_SCAN candidates had to be examined.
+   * @throws {Error} If the query has no searchable words
+   */
+  TravianHistoryStore.searchLogs = async function(query, filter, limit) {
+    filter = filter || {};
+    limit = Math.min(500, Math.max(1, limit || 50));
+    var qTerms = _terms(query || '');
+    if (qTerms.length === 0) throw new Error('Query has no searchable words');
+    await TravianHistoryStore.flush();
+
+    var since = filter.since || 0;
+    var until = filter.until || Date.now() + 1000;
+    var minLevel = filter.level ? LOG_LEVELS[String(filter.level).toUpperCase()] || 0 : 0;
+
+    // Document frequencies drive both idf and the choice of the rarest
+    // term, whose index range is walked to find candidates.
+    var total = await _countIndex('logs', null, null);
+    var df = await Promise.all(qTerms.map(function(t) {
+      return _countIndex('logs', 'terms', _prefixRange(t));
+    }));
+    var idf = df.map(function(n) { return Math.log(1 + (total - n + 0.5) / (n + 0.5)); });
+    var driver = df.indexOf(Math.min.apply(null, df));
+
+    var seen = {};
+    var scanned = 0;
+    var truncated = false;
+    var hits = [];
+    await _iterate('logs', 'terms', _prefixRange(qTerms[driver]), 'next', function(row) {
+      if (++scanned > MAX_SEARCH_SCAN) { truncated = true; return false; }
+      if (seen[row.id]) return true;   // one row per matching word in the index
+      seen[row.id] = true;
+      if (row.ts < since || row.ts > until) return true;
+      if (filter.serverKey && row.serverKey !== filter.serverKey) return true;
+      if ((LOG_LEVELS[row.level] || 0) < minLevel) return true;
+
+      var words = _words(row.message);
+      var score = 0;
+      for (var i = 0; i < qTerms.length; i++) {
+        var tf = 0;
+        for (var j = 0; j < words.length; j++) {
+          if (words[j].indexOf(qTerms[i]) === 0) tf++;
+        }
+        if (tf === 0) return true;
+        score += idf[i] * (tf * 2.2) / (tf + 1.2);
+      }
+      hits.push({ row: row, score: score });
+      return true;
+    });
+
+    hits.sort(function(a, b) { return b.score - a.score || b.row.ts - a.row.ts; });
+    var matches = hits.slice(0, limit).map(function(h) {
+      var spans = [];
+      var highlight = '';
+      var last = 0;
+      var msg = h.row.message;
+      var m;
+      WORD_PATTERN.lastIndex = 0;
+      while ((m = WORD_PATTERN.exec(msg)) !== null) {
+        var w = m[0].toLowerCase();
+        if (!qTerms.some(function(t) { return w.indexOf(t) === 0; })) continue;
+        var end = m.index + m[0].length;
+        spans.push([m.index, end]);
+        highlight += _escapeHtml(msg.slice(last, m.index)) + '<mark>' + _escapeHtml(m[0]) + '</mark>';
+        last = end;
+      }
+      highlight += _escapeHtml(msg.slice(last));
+      var out = Object.assign({}, h.row, { score: Math.round(h.score * 1000) / 1000, spans: spans, highlight: highlight });
+      delete out.terms;
+      return out;
+    });
+    return { matches: matches, total: hits.length, truncated: truncated };
+  };
+
+  // ── Resources ───────────────────────────────────────────────────────
+
+  function _pick(obj, keys) {
+    if (!obj) return null;
+    var out = {};
+    keys.forEach(function(k) { out[k] = typeof obj[k] === 'number' ? obj[k] : null; });
+    return out;
+  }
+
+  /**
+   * Record a village's resources, production and capacity from a scan.
+   * Samples closer than RESOURCE_SAMPLE_MS to the previous one are skipped.
+   * @param {string} serverKey
+   * @param {string|number} villageId
+   * @param {Object} gameState - Scanned state (resources, resourceProduction, resourceCapacity)
+   * @returns {boolean} True if a sample was queued
+   */
+  TravianHistoryStore.recordResources = function(serverKey, villageId, gameState) {
+    if (!serverKey || !villageId || !gameState || !gameState.resources) return false;
+    var key = serverKey + '|' + villageId;
+    var now = Date.now();
+    if (_lastSampleAt[key] && now - _lastSampleAt[key] < RESOURCE_SAMPLE_MS) return false;
+    _lastSampleAt[key] = now;
+
+    _enqueue('resources', {
+      ts: now,
+      serverKey: serverKey,
+      villageId: String(villageId),
+      resources: _pick(gameState.resources, RESOURCE_KEYS),
+      production: _pick(gameState.resourceProduction, RESOURCE_KEYS),
+      capacity: _pick(gameState.resourceCapacity, CAPACITY_KEYS)
+    });
+    return true;
+  };
+
+  /** Average each resources/production/capacity field over `rows` into one point. */
+  function _average(rows, ts) {
+    var point = { ts: ts, samples: rows.length };
+    [['resources', RESOURCE_KEYS], ['production', RESOURCE_KEYS], ['capacity', CAPACITY_KEYS]].forEach(function(g) {
+      var group = g[0];
+      var out = {};
+      g[1].forEach(function(k) {
+        var sum = 0, n = 0;
+        rows.forEach(function(r) {
+          var v = r[group] && r[group][k];
+          if (typeof v === 'number') { sum += v; n++; }
+        });
+        out[k] = n > 0 ? Math.round(sum / n) : null;
+      });
+      point[group] = out;
+    });
+    return point;
+  }
+
+  /**
+   * Resource history for one village, downsampled to buckets.
+   * @param {string} serverKey
+   * @param {string|number} villageId
+   * @param {{ since?: number, until?: number }} [range] - Epoch ms; default last 24h
+   * @param {string|number} [resolution] - Key of RESOLUTIONS, bucket ms, or
+   *   omitted to pick the smallest named bucket giving ≤ 500 points
+   * @returns {Promise<{ resolution: number, points: Array<{ ts, samples, resources, production, capacity }> }>}
+   */
+  TravianHistoryStore.getResourceHistory = async function(serverKey, villageId, range, resolution) {
+    range = range || {};
+    var until = range.until || Date.now();
+    var since = range.since || until - DAY_MS;
+    await TravianHistoryStore.flush();
+
+    var bucketMs;
+    if (typeof resolution === 'number') bucketMs = Math.max(0, resolution);
+    else if (resolution && RESOLUTIONS[resolution] !== undefined) bucketMs = RESOLUTIONS[resolution];
+    else if (resolution) throw new Error('Unknown resolution: ' + resolution);
+    else {
+      bucketMs = RESOLUTIONS['1d'];
+      ['5m', '15m', '1h', '6h'].some(function(name) {
+        if ((until - since) / RESOLUTIONS[name] <= MAX_POINTS) { bucketMs = RESOLUTIONS[name]; return true; }
+        return false;
+      });
+    }
+
+    var vid = String(villageId);
+    var points = [];
+    var bucket = [];
+    var bucketStart = null;
+    await _iterate('resources', 'village_ts', IDBKeyRange.bound([serverKey, vid, since], [serverKey, vid, until]), 'next', function(row) {
+      if (bucketMs === 0) {
+        points.push(_average([row], row.ts));
+        return true;
+      }
+      var start = Math.floor(row.ts / bucketMs) * bucketMs;
+      if (bucketStart !== null && start !== bucketStart) {
+        points.push(_average(bucket, bucketStart));
+        bucket = [];
+      }
+      bucketStart = start;
+      bucket.push(row);
+      return true;
+    });
+    if (bucket.length > 0) points.push(_average(bucket, bucketStart));
+    return { resolution: bucketMs, points: points };
+  };
+
+  // ── Raids ───────────────────────────────────────────────────────────
+
+  /**
+   * Append a completed raid (buffered). Called by FarmIntelligence when a
+   * pending raid gets its result.
+   * @param {string} serverKey
+   * @param {Object} raid - { ts, target, x, y, name, source, troopsSent, troopsLost, totalLosses, loot, totalLoot, bountyFull }
+   */
+  TravianHistoryStore.recordRaid = function(serverKey, raid) {
+    if (!serverKey || !raid) return;
+    _enqueue('raids', {
+      ts: raid.ts || Date.now(),
+      resultAt: Date.now(),
+      serverKey: serverKey,
+      target: raid.target,
+      x: raid.x,
+      y: raid.y,
+      name: raid.name || '',
+      source: raid.source || 'farmList',
+      troopsSent: _plain(raid.troopsSent) || {},
+      troopsLost: _plain(raid.troopsLost) || {},
+      totalLosses: raid.totalLosses || 0,
+      loot: _plain(raid.loot) || { wood: 0, clay: 0, iron: 0, crop: 0 },
+      totalLoot: raid.totalLoot || 0,
+      bountyFull: !!raid.bountyFull
+    });
+  };
+
+  function _raidRows(serverKey, range) {
+    range = range || {};
+    var until = range.until || Date.now();
+    var since = range.since || until - 7 * DAY_MS;
+    var rows = [];
+    return TravianHistoryStore.flush().then(function() {
+      return _iterate('raids', 'serverKey_ts', IDBKeyRange.bound([serverKey, since], [serverKey, until]), 'next', function(row) {
+        rows.push(row);
+        return true;
+      });
+    }).then(function() {
+      return { rows: rows, since: since, until: until };
+    });
+  }
+
+  function _raidTotals(rows) {
+    var t = { raids: rows.length, loot: { wood: 0, clay: 0, iron: 0, crop: 0 }, totalLoot: 0,
+      emptyRaids: 0, fullBounty: 0, raidsWithLosses: 0, troopsLost: 0 };
+    rows.forEach(function(r) {
+      RESOURCE_KEYS.forEach(function(k) { t.loot[k] += (r.loot && r.loot[k]) || 0; });
+      t.totalLoot += r.totalLoot || 0;
+      if (!r.totalLoot) t.emptyRaids++;
+      if (r.bountyFull) t.fullBounty++;
+      if (r.totalLosses > 0) t.raidsWithLosses++;
+      t.troopsLost += r.totalLosses || 0;
+    });
+    t.avgLoot = t.raids > 0 ? Math.round(t.totalLoot / t.raids) : 0;
+    t.lossRatio = t.raids > 0 ? Math.round(t.raidsWithLosses / t.raids * 1000) / 1000 : 0;
+    return t;
+  }
+
+  /**
+   * Raid totals, loot per hour and a per-bucket trend for one server.
+   * @param {string} serverKey
+   * @param {{ since?: number, until?: number }} [range] - Epoch ms; default last 7 days
+   * @param {string|number} [resolution='1d'] - Trend bucket (key of RESOLUTIONS or ms)
+   * @returns {Promise<{ summary: Object, trend: Array<Object> }>}
+   *   summary: totals + lootPerHour; trend: [{ ts, raids, totalLoot, avgLoot, lossRatio }]
+   */
+  TravianHistoryStore.getRaidStats = async function(serverKey, range, resolution) {
+    var bucketMs = typeof resolution === 'number' ? resolution : RESOLUTIONS[resolution || '1d'];
+    if (!bucketMs) throw new Error('Unknown resolution: ' + resolution);
+    var r = await _raidRows(serverKey, range);
+
+    var summary = _raidTotals(r.rows);
+    var hours = (r.until - r.since) / 3600000;
+    summary.lootPerHour = hours > 0 ? Math.round(summary.totalLoot / hours) : 0;
+    summary.since = r.since;
+    summary.until = r.until;
+
+    var buckets = {};
+    r.rows.forEach(function(row) {
+      var start = Math.floor(row.ts / bucketMs) * bucketMs;
+      (buckets[start] = buckets[start] || []).push(row);
+    });
+    var trend = Object.keys(buckets).map(Number).sort(function(a, b) { return a - b; }).map(function(start) {
+      var bt = _raidTotals(buckets[start]);
+      return { ts: start, raids: bt.raids, totalLoot: bt.totalLoot, avgLoot: bt.avgLoot, lossRatio: bt.lossRatio };
+    });
+    return { summary: summary, trend: trend };
+  };
+
+  /** Fields getFarmRankings can sort by */
+  var RANKING_SORTS = ['avgLoot', 'totalLoot', 'lootPerHour', 'lossRatio', 'emptyRate', 'raids'];
+  TravianHistoryStore.RANKING_SORTS = RANKING_SORTS;
+
+  /**
+   * Per-target raid performance sorted by `sortBy`. Descending by default:
+   * avgLoot lists the best farms first, lossRatio the riskiest.
+   * @param {string} serverKey
+   * @param {Object} [opts]
+   * @param {{ since?: number, until?: number }} [opts.range] - Default last 7 days
+   * @param {string} [opts.sortBy='avgLoot'] - One of RANKING_SORTS
+   * @param {string} [opts.order='desc'] - 'desc' | 'asc'
+   * @param {number} [opts.minRaids=1] - Skip targets with fewer raids
+   * @param {number} [opts.limit=50]
+   * @returns {Promise<Array<Object>>} [{ target, x, y, name, raids, totalLoot, avgLoot,
+   *   lootPerHour, emptyRate, lossRatio, fullBounty, lastRaidAt }]
+   */
+  TravianHistoryStore.getFarmRankings = async function(serverKey, opts) {
+    opts = opts || {};
+    var sortBy = opts.sortBy || 'avgLoot';
+    if (RANKING_SORTS.indexOf(sortBy) === -1) throw new Error('Unknown sortBy: ' + sortBy);
+    var r = await _raidRows(serverKey, opts.range);
+    var hours = (r.until - r.since) / 3600000;
+
+    var byTarget = {};
+    r.rows.forEach(function(row) {
+      (byTarget[row.target] = byTarget[row.target] || []).push(row);
+    });
+
+    var list = Object.keys(byTarget).map(function(target) {
+      var rows = byTarget[target];
+      var t = _raidTotals(rows);
+      var last = rows[rows.length - 1];
+      return {
+        target: target,
+        x: last.x,
+        y: last.y,
+        name: last.name,
+        raids: t.raids,
+        totalLoot: t.totalLoot,
+        avgLoot: t.avgLoot,
+        lootPerHour: hours > 0 ? Math.round(t.totalLoot / hours) : 0,
+        emptyRate: Math.round(t.emptyRaids / t.raids * 1000) / 1000,
+        lossRatio: t.lossRatio,
+        fullBounty: t.fullBounty,
+        lastRaidAt: last.ts
+      };
+    }).filter(function(t) { return t.raids >= (opts.minRaids || 1); });
+
+    var dir = opts.order === 'asc' ? 1 : -1;
+    list.sort(function(a, b) { return dir * (a[sortBy] - b[sortBy]) || b.raids - a.raids; });
+    return list.slice(0, opts.limit || 50);
+  };
+
+  // ── Actions ─────────────────────────────────────────────────────────
+
+  /**
+   * Append a finished task to the action timeline (buffered).
+   * @param {string} serverKey
+   * @param {{ taskId: string, type: string, villageId?: string, params?: Object,
+   *   status: 'completed'|'failed', reason?: string, error?: string, summary?: string }} action
+   */
+  TravianHistoryStore.recordAction = function(serverKey, action) {
+    if (!serverKey || !action) return;
+    _enqueue('actions', {
+      ts: Date.now(),
+      serverKey: serverKey,
+      villageId: action.villageId != null ? String(action.villageId) : null,
+      taskId: action.taskId,
+      type: action.type,
+      params: _plain(action.params) || {},
+      status: action.status,
+      reason: action.reason || null,
+      error: action.error || null,
+      summary: action.summary || null
+    });
+  };
+
+  /**
+   * Page through the action timeline, newest first.
+   * @param {string} serverKey
+   * @param {Object} [filter]
+   * @param {number} [filter.since] - Epoch ms (default: last 24h)
+   * @param {number} [filter.until] - Epoch ms
+   * @param {string[]} [filter.types] - Task types to include
+   * @param {string} [filter.status] - 'completed' | 'failed'
+   * @param {string} [filter.villageId]
+   * @param {number} [offset=0]
+   * @param {number} [limit=100] - Capped at 1000
+   * @returns {Promise<{ entries: Array<Object>, hasMore: boolean }>}
+   */
+  TravianHistoryStore.getActionTimeline = async function(serverKey, filter, offset, limit) {
+    filter = filter || {};
+    offset = Math.max(0, offset || 0);
+    limit = Math.min(1000, Math.max(1, limit || 100));
+    await TravianHistoryStore.flush();
+
+    var until = filter.until || Date.now() + 1000;
+    var since = filter.since || until - DAY_MS;
+    var types = Array.isArray(filter.types) && filter.types.length > 0 ? filter.types : null;
+    var village = filter.villageId != null ? String(filter.villageId) : null;
+
+    var entries = [];
+    var skipped = 0;
+    var hasMore = false;
+    await _iterate('actions', 'serverKey_ts', IDBKeyRange.bound([serverKey, since], [serverKey, until]), 'prev', function(row) {
+      if (types && types.indexOf(row.type) === -1) return true;
+      if (filter.status && row.status !== filter.status) return true;
+      if (village && row.villageId !== village) return true;
+      if (skipped < offset) { skipped++; return true; }
+      if (entries.length === limit) { hasMore = true; return false; }
+      entries.push(row);
+      return true;
+    });
+    return { entries: entries, hasMore: hasMore };
+  };
+
+  // ── Alerts ──────────────────────────────────────────────────────────
+
+  /**
+   * Insert or update an alert row (written immediately).
+   * @param {Object} alert - Without id to insert
+   * @returns {Promise<number>} Row id
+   */
+  TravianHistoryStore.putAlert = async function(alert) {
+    var db = await _open();
+    var tx = db.transaction('alerts', 'readwrite');
+    var req = tx.objectStore('alerts').put(alert);
+    await _done(tx);
+    return req.result;
+  };
+
+  /**
+   * @param {number} id
+   * @returns {Promise<Object|null>}
+   */
+  TravianHistoryStore.getAlert = async function(id) {
+    var db = await _open();
+    var tx = db.transaction('alerts');
+    var req = tx.objectStore('alerts').get(id);
+    await _done(tx);
+    return req.result || null;
+  };
+
+  /**
+   * Page through alerts, newest first.
+   * @param {Object} [filter]
+   * @param {string} [filter.serverKey]
+   * @param {boolean} [filter.acknowledged] - Only (un)acknowledged alerts
+   * @param {string} [filter.severity]
+   * @param {number} [offset=0]
+   * @param {number} [limit=50] - Capped at 500
+   * @returns {Promise<{ entries: Array<Object>, hasMore: boolean }>}
+   */
+  TravianHistoryStore.queryAlerts = async function(filter, offset, limit) {
+    filter = filter || {};
+    offset = Math.max(0, offset || 0);
+    limit = Math.min(500, Math.max(1, limit || 50));
+
+    var index = 'ts', range = null;
+    if (filter.acknowledged !== undefined) {
+      var ack = filter.acknowledged ? 1 : 0;
+      index = 'ack_ts';
+      range = IDBKeyRange.bound([ack, 0], [ack, Infinity]);
+    }
+    var entries = [];
+    var skipped = 0;
+    var hasMore = false;
+    await _iterate('alerts', index, range, 'prev', function(row) {
+      if (filter.serverKey && row.serverKey !== filter.serverKey) return true;
+      if (filter.severity && row.severity !== filter.severity) return true;
+      if (skipped < offset) { skipped++; return true; }
+      if (entries.length === limit) { hasMore = true; return false; }
+      entries.push(row);
+      return true;
+    });
+    return { entries: entries, hasMore: hasMore };
+  };
+
+  // ── Screenshots ─────────────────────────────────────────────────────
+
+  /**
+   * Store a screenshot.
+   * @param {{ ts: number, serverKey: string, reason: string, alertId: number|null,
+   *   url: string, dataUrl: string }} shot
+   * @returns {Promise<number>} Its id
+   */
+  TravianHistoryStore.putScreenshot = async function(shot) {
+    var db = await _open();
+    var tx = db.transaction('screenshots', 'readwrite');
+    var req = tx.objectStore('screenshots').put(Object.assign({ bytes: shot.dataUrl.length }, shot));
+    await _done(tx);
+    return req.result;
+  };
+
+  /**
+   * @param {number} id
+   * @returns {Promise<Object|null>} Including the image (dataUrl)
+   */
+  TravianHistoryStore.getScreenshot = async function(id) {
+    var db = await _open();
+    var tx = db.transaction('screenshots');
+    var req = tx.objectStore('screenshots').get(id);
+    await _done(tx);
+    return req.result || null;
+  };
+
+  /**
+   * Page through screenshots, newest first, without the image data.
+   * @param {{ serverKey?: string, alertId?: number, reason?: string }} [filter]
+   * @param {number} [offset=0]
+   * @param {number} [limit=50] - Capped at 200
+   * @returns {Promise<{ entries: Array<Object>, hasMore: boolean }>}
+   */
+  TravianHistoryStore.queryScreenshots = async function(filter, offset, limit) {
+    filter = filter || {};
+    offset = Math.max(0, offset || 0);
+    limit = Math.min(200, Math.max(1, limit || 50));
+
+    var index = 'ts', range = null;
+    if (filter.serverKey) {
+      index = 'serverKey_ts';
+      range = IDBKeyRange.bound([filter.serverKey, 0], [filter.serverKey, Infinity]);
+    }
+    var entries = [];
+    var skipped = 0;
+    var hasMore = false;
+    await _iterate('screenshots', index, range, 'prev', function(row) {
+      if (filter.alertId !== undefined && row.alertId !== Number(filter.alertId)) return true;
+      if (filter.reason && row.reason !== filter.reason) return true;
+      if (skipped < offset) { skipped++; return true; }
+      if (entries.length === limit) { hasMore = true; return false; }
+      var meta = Object.assign({}, row);
+      delete meta.dataUrl;
+      entries.push(meta);
+      return true;
+    });
+    return { entries: entries, hasMore: hasMore };
+  };
+
+  // ── Export ──────────────────────────────────────────────────────────
+
+  /** Stores exportData() can dump */
+  var EXPORT_KINDS = ['logs', 'resources', 'raids', 'actions'];
+  TravianHistoryStore.EXPORT_KINDS = EXPORT_KINDS;
+
+  function _csvCell(v) {
+    if (v === null || v === undefined) return '';
+    var str = typeof v === 'object' ? JSON.stringify(v) : String(v);
+    return /[",\r\n]/.test(str) ? '"' + str.replace(/"/g, '""') + '"' : str;
+  }
+
+  /** Flatten one level of nesting: { loot: { wood } } → { 'loot.wood' } */
+  function _flatten(row) {
+    var out = {};
+    for (var k in row) {
+      var v = row[k];
+      if (v && typeof v === 'object' && !Array.isArray(v) && k !== 'data' && k !== 'params') {
+        for (var sub in v) out[k + '.' + sub] = v[sub];
+      } else {
+        out[k] = v;
+      }
+    }
+    return out;
+  }
+
+  /**
+   * Render rows as CSV. Nested objects become dotted columns; free-form
+   * fields (log data, task params) stay as JSON in one cell.
+   * @param {Array<Object>} rows
+   * @returns {string}
+   */
+  TravianHistoryStore.toCSV = function(rows) {
+    var flat = rows.map(_flatten);
+    var columns = [];
+    flat.forEach(function(r) {
+      for (var k in r) if (columns.indexOf(k) === -1) columns.push(k);
+    });
+    var lines = [columns.map(_csvCell).join(',')];
+    flat.forEach(function(r) {
+      lines.push(columns.map(function(c) { return _csvCell(r[c]); }).join(','));
+    });
+    return lines.join('\r\n');
+  };
+
+  /**
+   * Dump one history store as CSV or JSON, oldest first.
+   * @param {string} kind - One of EXPORT_KINDS
+   * @param {Object} [opts]
+   * @param {string} [opts.serverKey] - Only this server (default: all)
+   * @param {{ since?: number, until?: number }} [opts.range] - Epoch ms (default: everything)
+   * @param {string} [opts.format='csv'] - 'csv' | 'json'
+   * @param {function({ rows: number })} [opts.onProgress] - Called every 1000 rows
+   * @returns {Promise<{ filename: string, mimeType: string, content: string, rows: number }>}
+   */
+  TravianHistoryStore.exportData = async function(kind, opts) {
+    opts = opts || {};
+    if (EXPORT_KINDS.indexOf(kind) === -1) throw new Error('Unknown export kind: ' + kind);
+    var format = opts.format || 'csv';
+    if (format !== 'csv' && format !== 'json') throw new Error('Unknown format: ' + format);
+    await TravianHistoryStore.flush();
+
+    var range = opts.range || {};
+    var keyRange = IDBKeyRange.bound(range.since || 0, range.until || Date.now() + 1000);
+    var rows = [];
+    await _iterate(kind, 'ts', keyRange, 'next', function(row) {
+      if (opts.serverKey && row.serverKey !== opts.serverKey) return true;
+      var out = Object.assign({ time: new Date(row.ts).toISOString() }, row);
+      delete out.id;
+      delete out.terms;
+      rows.push(out);
+      if (opts.onProgress && rows.length % 1000 === 0) opts.onProgress({ rows: rows.length });
+      return true;
+    });
+
+    var stamp = new Date().toISOString().slice(0, 10);
+    var server = opts.serverKey ? opts.serverKey.split('.')[0] + '-' : '';
+    return {
+      filename: 'travian-' + server + kind + '-' + stamp + '.' + format,
+      mimeType: format === 'csv' ? 'text/csv' : 'application/json',
+      content: format === 'csv' ? TravianHistoryStore.toCSV(rows) : JSON.stringify(rows, null, 2),
+      rows: rows.length
+    };
+  };
+
+  // ── Retention ───────────────────────────────────────────────────────
+
+  /**
+   * Delete rows older than each store's max age, then the oldest rows
+   * beyond its max row count.
+   * @returns {Promise<Object.<string, number>>} Rows deleted per store
+   */
+  TravianHistoryStore.prune = async function() {
+    await TravianHistoryStore.flush();
+    var db = await _open();
+    var deleted = {};
+
+    for (var store in RETENTION) {
+      var policy = RETENTION[store];
+      var count = 0;
+
+      var cutoff = Date.now() - policy.maxAgeDays * DAY_MS;
+      await _iterate(store, 'ts', IDBKeyRange.upperBound(cutoff, true), 'next', function(row, cursor) {
+        cursor.delete();
+        count++;
+        return true;
+      }, 'readwrite');
+
+      var total = await new Promise(function(resolve, reject) {
+        var req = db.transaction(store).objectStore(store).count();
+        req.onsuccess = function() { resolve(req.result); };
+        req.onerror = function() { reject(req.error); };
+      });
+      var excess = total - policy.maxRows;
+      if (excess > 0) {
+        await _iterate(store, 'ts', null, 'next', function(row, cursor) {
+          cursor.delete();
+          count++;
+          return --excess > 0;
+        }, 'readwrite');
+      }
+
+      deleted[store] = count;
+    }
+    return deleted;
+  };
+
+  root.TravianHistoryStore = TravianHistoryStore;
+})(typeof window !== 'undefined' ? window : self);
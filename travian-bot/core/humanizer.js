@@ -0,0 +1,171 @@
+/**
+ * Humanizer — Daily awake/asleep windows so a bot doesn't play 24/7.
+ *
+ * Each server gets a sleep block every night: it starts around
+ * activityConfig.sleepStart (local hour) and lasts about sleepHours, both
+ * shifted by up to jitterMinutes. On an off-day (offDayPercent chance per
+ * day) the player "doesn't log in": the bot stays asleep from the morning
+ * wake-up until that night's sleep is over.
+ *
+ * Randomness is seeded from the server key and the date, so the plan for a
+ * given day is the same on every call (and after a service worker restart)
+ * but differs between servers and from day to day.
+ *
+ * The service worker checks isAsleep() on each heartbeat and pauses or
+ * resumes the bot; a bot the user paused by hand is never resumed. A manual
+ * resume during a sleep block lasts until the next heartbeat — switch the
+ * windows off to keep playing through the night.
+ *
+ * Runs in service worker context. Exported via self.TravianHumanizer.
+ */
+(function(root) {
+  'use strict';
+
+  var HOUR_MS = 3600000;
+  var DAY_MS = 24 * HOUR_MS;
+
+  var DEFAULTS = { enabled: false, sleepStart: 0, sleepHours: 7, jitterMinutes: 45, offDayPercent: 5 };
+
+  var TravianHumanizer = {};
+
+  TravianHumanizer.DEFAULTS = DEFAULTS;
+
+  function _cfg(activityConfig) {
+    return Object.assign({}, DEFAULTS, activityConfig || {});
+  }
+
+  /** FNV-1a hash → 32-bit seed */
+  function _hash(str) {
+    var h = 0x811c9dc5;
+    for (var i = 0; i < str.length; i++) {
+      h ^= str.charCodeAt(i);
+      h = Math.imul(h, 0x01000193);
+    }
+    return h >>> 0;
+  }
+
+  /** mulberry32 PRNG → function returning [0, 1) */
+  function _rng(seed) {
+    return function() {
+      seed = (seed + 0x6d2b79f5) | 0;
+      var t = Math.imul(seed ^ (seed >>> 15), 1 | seed);
+      t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t;
+      return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    };
+  }
+
+  function _dateKey(dayStart) {
+    var d = new Date(dayStart);
+    return d.getFullYear() + '-' + String(d.getMonth() + 1).padStart(2, '0') + '-' + String(d.getDate()).padStart(2, '0');
+  }
+
+  function _startOfDay(ms) {
+    var d = new Date(ms);
+    d.setHours(0, 0, 0, 0);
+    return d.getTime();
+  }
+
+  function _addDays(dayStart, n) {
+    var d = new Date(dayStart);
+    d.setDate(d.getDate() + n);
+    return d.getTime();
+  }
+
+  /**
+   * The night that follows day `dayStart`, plus whether that day is an
+   * off-day. Evening hours (>= 12) belong to the same date, early-morning
+   * hours to the next one.
+   */
+  function _day(serverKey, cfg, dayStart) {
+    var rand = _rng(_hash(serverKey + '|' + _dateKey(dayStart)));
+    var offDay = rand() * 100 < cfg.offDayPercent;
+    var jitter = cfg.jitterMinutes * 60000;
+    var bedtime = dayStart + (cfg.sleepStart < 12 ? DAY_MS : 0) + cfg.sleepStart * HOUR_MS +
+      Math.round((rand() * 2 - 1) * jitter);
+    var length = cfg.sleepHours * HOUR_MS + Math.round((rand() * 2 - 1) * jitter / 2);
+    return { dayStart: dayStart, offDay: offDay, sleepStart: bedtime, sleepEnd: bedtime + length };
+  }
+
+  /** Merged asleep intervals covering the days from `firstDay` for `count` days. */
+  function _asleep(serverKey, cfg, firstDay, count) {
+    var days = [];
+    for (var i = -1; i < count; i++) days.push(_day(serverKey, cfg, _addDays(firstDay, i)));
+    var spans = [];
+    for (var j = 1; j < days.length; j++) {
+      spans.push([days[j - 1].sleepStart, days[j - 1].sleepEnd]);
+      if (days[j].offDay) spans.push([days[j - 1].sleepEnd, days[j].sleepStart]);
+    }
+    spans.push([days[days.length - 1].sleepStart, days[days.length - 1].sleepEnd]);
+    spans.sort(function(a, b) { return a[0] - b[0]; });
+    var merged = [];
+    spans.forEach(function(s) {
+      var last = merged[merged.length - 1];
+      if (last && s[0] <= last[1]) last[1] = Math.max(last[1], s[1]);
+      else merged.push([s[0], s[1]]);
+    });
+    return merged;
+  }
+
+  /**
+   * Whether the bot should be asleep at `now`, and when that changes.
+   * @param {string} serverKey
+   * @param {Object} [activityConfig]
+   * @param {number} [now=Date.now()]
+   * @returns {{ asleep: boolean, until: number }} until = epoch ms of the next switch
+   */
+  TravianHumanizer.state = function(serverKey, activityConfig, now) {
+    var cfg = _cfg(activityConfig);
+    now = now || Date.now();
+    var spans = _asleep(serverKey, cfg, _addDays(_startOfDay(now), -1), 4);
+    for (var i = 0; i < spans.length; i++) {
+      if (now < spans[i][0]) return { asleep: false, until: spans[i][0] };
+      if (now < spans[i][1]) return { asleep: true, until: spans[i][1] };
+    }
+    return { asleep: false, until: now + DAY_MS }; // not reached: the last span ends after tomorrow
+  };
+
+  /**
+   * @returns {boolean} True if activity windows are enabled and `now` is in a sleep block
+   */
+  TravianHumanizer.isAsleep = function(serverKey, activityConfig, now) {
+    if (!activityConfig || !activityConfig.enabled) return false;
+    return TravianHumanizer.state(serverKey, activityConfig, now).asleep;
+  };
+
+  /**
+   * Awake windows for the coming days, for display.
+   * @param {string} serverKey
+   * @param {Object} [activityConfig]
+   * @param {number} [days=7]
+   * @param {number} [now=Date.now()]
+   * @returns {{ enabled: boolean, asleep: boolean, until: number,
+   *   days: Array<{ date: string, offDay: boolean, awake: Array<{ start: number, end: number }> }> }}
+   */
+  TravianHumanizer.getPlan = function(serverKey, activityConfig, days, now) {
+    var cfg = _cfg(activityConfig);
+    days = Math.min(30, Math.max(1, days || 7));
+    now = now || Date.now();
+    var today = _startOfDay(now);
+    var spans = _asleep(serverKey, cfg, today, days + 1);
+
+    var out = [];
+    for (var d = 0; d < days; d++) {
+      var from = _addDays(today, d);
+      var to = _addDays(today, d + 1);
+      var awake = [];
+      var cursor = from;
+      spans.forEach(function(s) {
+        if (s[1] <= from || s[0] >= to) return;
+        if (s[0] > cursor) awake.push({ start: cursor, end: s[0] });
+        cursor = Math.max(cursor, s[1]);
+      });
+      if (cursor < to) awake.push({ start: cursor, end: to });
+      out.push({ date: _dateKey(from), offDay: _day(serverKey, cfg, from).offDay, awake: awake });
+    }
+
+    var st = TravianHumanizer.state(serverKey, cfg, now);
+    return { enabled: cfg.enabled, asleep: cfg.enabled && st.asleep, until: st.until, days: out };
+  };
+
+  root.TravianHumanizer = TravianHumanizer;
+})(typeof window !== 'undefined' ? window : self);
@@ -13,6 +13,15 @@
     constructor() {
       // Map<serverKey, { engine: TravianBotEngine, tabId: number, serverKey: string }>
       this.instances = new Map();
+
+      // Map<serverKey, { title, message, raisedAt, snoozedUntil }> — active attack alarms.
+      // Mirrored to chrome.storage.session so a service worker restart keeps them.
+      this._attackAlarms = new Map();
+      this._attackAlarmsLoaded = this._loadAttackAlarms();
+
+      // Set by the service worker: function(serverKey, data) called when a
+      // captcha pauses a bot (brings the game tab up)
+      this.onCaptcha = null;
     }
 
     /**
@@ -31,11 +40,17 @@
       var instance = {
         engine: engine,
         tabId: null,
-        serverKey: serverKey
+        serverKey: serverKey,
+        humanizerPaused: false,  // paused by activity windows (see humanizer.js)
+        resyncingSince: null     // set after system sleep until the next scan completes
       };
 
       // Wire EventBus → Chrome notifications for critical events
       this._wireNotifications(engine, serverKey);
+      // Forward subscribed EventBus events to webhook endpoints
+      this._wireWebhooks(engine, serverKey);
+      // Record resource samples and finished tasks into the persistent history
+      this._wireHistory(engine, serverKey);
 
       this.instances.set(serverKey, instance);
       console.log('[InstanceManager] Created instance for ' + serverKey);
@@ -82,6 +97,7 @@
         }
       } catch (_) {}
 
+      await this.acknowledgeAttackAlarm(serverKey);
       this.instances.delete(serverKey);
       console.log('[InstanceManager] Removed instance for ' + serverKey);
     }
@@ -98,6 +114,7 @@
           tabId: inst.tabId,
           running: inst.engine.running,
           paused: inst.engine.paused,
+          manualMode: !!inst.engine.manualMode,
           stats: inst.engine.stats
         });
       }
@@ -118,6 +135,24 @@
       console.log('[InstanceManager] All instances stopped');
     }
 
+    /**
+     * Emergency-stop every running instance.
+     * @param {string} reason
+     * @returns {Promise<string[]>} Server keys that were stopped
+     */
+    async emergencyStopAll(reason) {
+      var stopped = [];
+      var stopPromises = [];
+      for (var inst of this.instances.values()) {
+        if (inst.engine.running) {
+          stopped.push(inst.serverKey);
+          stopPromises.push(inst.engine.emergencyStop(reason));
+        }
+      }
+      await Promise.allSettled(stopPromises);
+      return stopped;
+    }
+
     /**
      * Get count of active (running) instances.
      * @returns {number}
@@ -131,65 +166,413 @@
     }
 
     /**
-     * Wire Chrome notifications to EventBus events.
-     * Shows desktop alerts for time-critical events (attacks, crop crisis).
+     * Turn EventBus events into alerts (attacks, crop crisis, lost session,
+     * emergency stop, idle build queue, completed raids/adventures) and hand
+     * them to TravianNotifier, which routes them to desktop/remote channels
+     * per config.notificationConfig. Urgent alerts are recorded in
+     * TravianAlertCenter first; attacks the user already acknowledged there
+     * (StateAnalyzer re-emits them every 5 minutes) are not dispatched
+     * again, so the attack alarm only comes back for a new attack.
      *
      * @param {TravianBotEngine} engine
      * @param {string} serverKey
      */
     _wireNotifications(engine, serverKey) {
       if (!engine.eventBus) return;
-      if (typeof chrome === 'undefined' || !chrome.notifications) return;
 
       var Events = self.TravianEventBus ? self.TravianEventBus.Events : {};
       var shortKey = serverKey.split('.')[0] || serverKey; // e.g., 'ts5' from 'ts5.x1.asia.travian.com'
+      var manager = this;
+
+      // Build a listener: format the alert and dispatch it. Rules are read
+      // live — SAVE_CONFIG swaps engine.config without re-wiring.
+      function alert(kind, format, urgent) {
+        return function(data) {
+          if (!self.TravianNotifier) return;
+          var a = format(data || {});
+          var recorded = Promise.resolve();
+          if (urgent && self.TravianAlertCenter) {
+            recorded = self.TravianAlertCenter.add({ serverKey: serverKey, kind: kind, title: a.title, message: a.message, refs: a.refs })
+              .catch(function(err) { console.warn('[InstanceManager] alert center:', err.message); });
+          }
+          recorded.then(function(row) {
+            if (row === null) return; // already acknowledged
+            self.TravianNotifier.dispatch({
+              serverKey: serverKey,
+              kind: kind,
+              title: a.title,
+              message: a.message,
+              urgent: !!urgent
+            }, engine.config && engine.config.notificationConfig);
+          });
+        };
+      }
 
       // ── Incoming attack notification ──────────────────────
       if (Events.ATTACK_INCOMING) {
-        engine.eventBus.on(Events.ATTACK_INCOMING, function(data) {
+        engine.eventBus.on(Events.ATTACK_INCOMING, alert('attack', function(data) {
           var count = data.count || 1;
           var soonest = data.soonest;
           var timeStr = soonest && soonest.timer ? soonest.timer : 'unknown';
           var attacker = soonest && soonest.attackerName ? soonest.attackerName : 'Unknown';
-
-          var title = '⚔️ INCOMING ATTACK! [' + shortKey + ']';
-          var message = count + (count === 1 ? ' attack' : ' attacks') + ' incoming!\n' +
-            'Attacker: ' + attacker + '\n' +
-            'Arrives in: ' + timeStr;
-
-          try {
-            chrome.notifications.create('attack_' + serverKey + '_' + Date.now(), {
-              type: 'basic',
-              iconUrl: '../icons/icon48.png',
-              title: title,
-              message: message,
-              priority: 2, // max urgency
-              requireInteraction: true // don't auto-dismiss
-            });
-          } catch (e) {
-            console.warn('[InstanceManager] Notification failed:', e.message);
-          }
-        }, { priority: 1 }); // highest priority
+          return {
+            title: '⚔️ INCOMING ATTACK! [' + shortKey + ']',
+            message: count + (count === 1 ? ' attack' : ' attacks') + ' incoming!\n' +
+              'Attacker: ' + attacker + '\n' +
+              'Arrives in: ' + timeStr,
+            refs: self.TravianAlertCenter ? self.TravianAlertCenter.attackRefs(data.attacks) : null
+          };
+        }, true), { priority: 1 }); // highest priority
       }
 
       // ── Crop crisis notification ──────────────────────────
       if (Events.CROP_CRISIS) {
-        engine.eventBus.on(Events.CROP_CRISIS, function(data) {
-          try {
-            chrome.notifications.create('crop_' + serverKey + '_' + Date.now(), {
-              type: 'basic',
-              iconUrl: '../icons/icon48.png',
-              title: '🌾 CROP CRISIS! [' + shortKey + ']',
-              message: 'Free crop: ' + (data.freeCrop || 0) +
-                '\nTroops may start dying. Upgrade croplands or sell troops!',
-              priority: 2,
-              requireInteraction: true
-            });
-          } catch (e) {
-            console.warn('[InstanceManager] Notification failed:', e.message);
-          }
+        engine.eventBus.on(Events.CROP_CRISIS, alert('crop', function(data) {
+          return {
+            title: '🌾 CROP CRISIS! [' + shortKey + ']',
+            message: 'Free crop: ' + (data.freeCrop || 0) +
+              '\nTroops may start dying. Upgrade croplands or sell troops!'
+          };
+        }, true), { priority: 1 });
+      }
+
+      // ── Session lost notification ─────────────────────────
+      if (Events.SESSION_LOST) {
+        engine.eventBus.on(Events.SESSION_LOST, alert('session', function(data) {
+          return {
+            title: '🔑 Logged out [' + shortKey + ']',
+            message: 'The game tab is no longer logged in. Log in again within ' +
+              (data.maxCount - data.notLoggedInCount) + ' cycles or the bot will stop.'
+          };
+        }, true), { priority: 1 });
+      }
+
+      // ── Emergency stop notification (errors, safety checks, ...) ─
+      if (Events.BOT_EMERGENCY) {
+        engine.eventBus.on(Events.BOT_EMERGENCY, alert('emergency', function(data) {
+          return {
+            title: '🛑 Bot stopped [' + shortKey + ']',
+            message: data.reason || 'Emergency stop'
+          };
+        }, true), { priority: 1 });
+      }
+
+      // ── Captcha: bot paused until the user solves it ──────
+      if (Events.CAPTCHA_DETECTED) {
+        var onCaptcha = alert('captcha', function(data) {
+          return {
+            title: '🧩 CAPTCHA — bot paused [' + shortKey + ']',
+            message: (data.reason || 'Captcha detected') +
+              '\nSolve it in the game tab, then resume the bot.'
+          };
+        }, true);
+        engine.eventBus.on(Events.CAPTCHA_DETECTED, function(data) {
+          onCaptcha(data);
+          if (typeof manager.onCaptcha === 'function') manager.onCaptcha(serverKey, data);
         }, { priority: 1 });
       }
+
+      // ── Completed raids / adventures ──────────────────────
+      if (Events.TASK_COMPLETED) {
+        var onRaid = alert('raid', function(data) {
+          var r = data.result || {};
+          var sent = (r.sent || 0) + (r.reRaidSent || 0);
+          return {
+            title: 'Raids sent [' + shortKey + ']',
+            message: sent + ' raid' + (sent === 1 ? '' : 's') + ' sent' +
+              (engine.stats ? ' (' + engine.stats.farmRaidsSent + ' total)' : '')
+          };
+        });
+        var onAdventure = alert('adventure', function() {
+          return { title: 'Hero adventure [' + shortKey + ']', message: 'Hero sent on an adventure.' };
+        });
+        engine.eventBus.on(Events.TASK_COMPLETED, function(data) {
+          var r = data.result || {};
+          if (data.type === 'send_farm' && (r.sent || r.reRaidSent)) onRaid(data);
+          else if (data.type === 'send_hero_adventure') onAdventure(data);
+        });
+      }
+
+      // ── Build queue idle notification ─────────────────────
+      if (Events.BUILD_IDLE) {
+        engine.eventBus.on(Events.BUILD_IDLE, alert('buildIdle', function() {
+          return {
+            title: '🏗️ Build queue empty [' + shortKey + ']',
+            message: 'Nothing is being built right now.'
+          };
+        }, false));
+      }
+    }
+
+    /**
+     * Forward EventBus events that webhook endpoints can subscribe to.
+     * Endpoint config is read per event (cached in TravianWebhook), so
+     * saving webhook settings takes effect without re-wiring.
+     *
+     * @param {TravianBotEngine} engine
+     * @param {string} serverKey
+     */
+    _wireWebhooks(engine, serverKey) {
+      if (!engine.eventBus || !self.TravianWebhook) return;
+      var Webhook = self.TravianWebhook;
+
+      Webhook.EVENTS.forEach(function(event) {
+        if (event === 'alert') return; // sent by the notifier's webhook channel
+        engine.eventBus.on(event, function(data) {
+          Webhook.getConfig().then(function(cfg) {
+            return Webhook.emit(cfg, event, serverKey, data);
+          }).catch(function(err) {
+            console.warn('[InstanceManager] webhook ' + event + ' failed:', err.message);
+          });
+        });
+      });
+    }
+
+    /**
+     * Feed TravianHistoryStore: village resources after each scan (the store
+     * throttles to one sample per village per few minutes) and every task
+     * that completed or permanently failed (action timeline).
+     *
+     * @param {TravianBotEngine} engine
+     * @param {string} serverKey
+     */
+    _wireHistory(engine, serverKey) {
+      if (!engine.eventBus || !self.TravianHistoryStore) return;
+      var History = self.TravianHistoryStore;
+      var Events = self.TravianEventBus ? self.TravianEventBus.Events : {};
+
+      engine.eventBus.on(Events.SCAN_COMPLETE || 'scan:complete', function(data) {
+        var vid = (data && data.villageId) || (engine.gameState && engine.gameState.currentVillageId);
+        History.recordResources(serverKey, vid, engine.gameState);
+      });
+
+      engine.eventBus.on(Events.TASK_COMPLETED || 'task:completed', function(data) {
+        var r = data.result || {};
+        History.recordAction(serverKey, {
+          taskId: data.taskId,
+          type: data.type,
+          villageId: data.villageId,
+          params: data.params,
+          status: 'completed',
+          reason: data.reason,
+          summary: typeof r.message === 'string' ? r.message
+            : (r.sent != null ? (r.sent + (r.reRaidSent || 0)) + ' raids sent' : null)
+        });
+      });
+
+      engine.eventBus.on(Events.TASK_FAILED || 'task:failed', function(data) {
+        History.recordAction(serverKey, {
+          taskId: data.taskId,
+          type: data.type,
+          villageId: data.villageId,
+          params: data.params,
+          status: 'failed',
+          reason: data.reason,
+          error: data.code ? data.code + ': ' + data.error : data.error
+        });
+      });
+    }
+
+    /**
+     * Desktop channel for TravianNotifier. Honours the per-kind desktop
+     * toggles in notificationConfig; attacks raise the repeating alarm
+     * unless attackRepeat is off.
+     * @param {{ serverKey: string, kind: string, title: string, message: string, urgent: boolean }} alert
+     * @param {Object} nc - notificationConfig
+     * @returns {boolean} True if shown
+     */
+    showDesktopAlert(alert, nc) {
+      if (typeof chrome === 'undefined' || !chrome.notifications) return false;
+      if (nc[alert.kind] === false) return false;
+      if (alert.kind === 'attack' && nc.attackRepeat !== false) {
+        this.raiseAttackAlarm(alert.serverKey, alert.title, alert.message);
+      } else {
+        TravianInstanceManager.notify(alert.kind + '_' + alert.serverKey, alert.title, alert.message, alert.urgent);
+      }
+      return true;
+    }
+
+    // ── Attack alarm (repeats until acknowledged) ─────────────────────
+
+    /**
+     * Raise (or refresh) the repeating attack alarm for a server.
+     * The notification carries Acknowledge / Snooze buttons and is re-shown
+     * every minute by the 'attackAlarm__<serverKey>' chrome alarm.
+     * @param {string} serverKey
+     * @param {string} title
+     * @param {string} message
+     */
+    async raiseAttackAlarm(serverKey, title, message) {
+      await this._attackAlarmsLoaded;
+      var existing = this._attackAlarms.get(serverKey);
+      this._attackAlarms.set(serverKey, {
+        title: title,
+        message: message,
+        raisedAt: existing ? existing.raisedAt : Date.now(),
+        snoozedUntil: existing ? existing.snoozedUntil : 0
+      });
+      if (!existing || Date.now() >= existing.snoozedUntil) this._showAttackAlarm(serverKey);
+      try {
+        chrome.alarms.create('attackAlarm__' + serverKey, { periodInMinutes: 1 });
+      } catch (_) {}
+      this._updateAlarmBadge();
+      await this._saveAttackAlarms();
+    }
+
+    /**
+     * Called by the 'attackAlarm__' chrome alarm. Re-shows the alarm unless
+     * snoozed; clears the chrome alarm if there is no saved state for it
+     * (session storage is gone after a browser restart).
+     * @param {string} serverKey
+     */
+    async repeatAttackAlarm(serverKey) {
+      await this._attackAlarmsLoaded;
+      var alarm = this._attackAlarms.get(serverKey);
+      if (!alarm) {
+        try { chrome.alarms.clear('attackAlarm__' + serverKey); } catch (_) {}
+        return;
+      }
+      if (Date.now() < alarm.snoozedUntil) return;
+      this._showAttackAlarm(serverKey);
+    }
+
+    /**
+     * Stop the attack alarm for a server.
+     * @param {string} serverKey
+     * @returns {boolean} True if an alarm was active
+     */
+    async acknowledgeAttackAlarm(serverKey) {
+      await this._attackAlarmsLoaded;
+      if (self.TravianAlertCenter) {
+        self.TravianAlertCenter.acknowledgeAll(serverKey, 'attack').catch(function() {});
+      }
+      if (!this._attackAlarms.delete(serverKey)) return false;
+      try {
+        chrome.alarms.clear('attackAlarm__' + serverKey);
+        chrome.notifications.clear('attackAlarm__' + serverKey);
+      } catch (_) {}
+      this._updateAlarmBadge();
+      await this._saveAttackAlarms();
+      return true;
+    }
+
+    /**
+     * Silence the attack alarm for a while; it resumes afterwards.
+     * @param {string} serverKey
+     * @param {number} [minutes] - Defaults to notificationConfig.snoozeMinutes
+     * @returns {boolean} True if an alarm was active
+     */
+    async snoozeAttackAlarm(serverKey, minutes) {
+      await this._attackAlarmsLoaded;
+      var alarm = this._attackAlarms.get(serverKey);
+      if (!alarm) return false;
+      if (!minutes) {
+        var inst = this.instances.get(serverKey);
+        var nc = inst && inst.engine.config && inst.engine.config.notificationConfig;
+        minutes = (nc && nc.snoozeMinutes) || 10;
+      }
+      alarm.snoozedUntil = Date.now() + minutes * 60000;
+      try { chrome.notifications.clear('attackAlarm__' + serverKey); } catch (_) {}
+      await this._saveAttackAlarms();
+      return true;
+    }
+
+    /**
+     * @returns {Promise<Array<{ serverKey: string, message: string, raisedAt: number, snoozedUntil: number }>>}
+     */
+    async getAttackAlarms() {
+      await this._attackAlarmsLoaded;
+      var list = [];
+      this._attackAlarms.forEach(function(a, key) {
+        list.push({ serverKey: key, message: a.message, raisedAt: a.raisedAt, snoozedUntil: a.snoozedUntil });
+      });
+      return list;
+    }
+
+    _showAttackAlarm(serverKey) {
+      var alarm = this._attackAlarms.get(serverKey);
+      if (!alarm) return;
+      try {
+        // Fixed ID: each repeat replaces the previous notification
+        chrome.notifications.clear('attackAlarm__' + serverKey, function() {
+          chrome.notifications.create('attackAlarm__' + serverKey, {
+            type: 'basic',
+            iconUrl: '../icons/icon48.png',
+            title: alarm.title,
+            message: alarm.message,
+            priority: 2,
+            requireInteraction: true,
+            buttons: [{ title: 'Acknowledge' }, { title: 'Snooze' }]
+          });
+        });
+      } catch (e) {
+        console.warn('[InstanceManager] Attack alarm failed:', e.message);
+      }
+    }
+
+    /** Restore alarms saved before a service worker restart (raised ones win) */
+    async _loadAttackAlarms() {
+      try {
+        var stored = await chrome.storage.session.get('attackAlarms');
+        var saved = stored.attackAlarms || {};
+        for (var key in saved) {
+          if (!this._attackAlarms.has(key)) this._attackAlarms.set(key, saved[key]);
+        }
+        if (this._attackAlarms.size > 0) this._updateAlarmBadge();
+      } catch (e) {
+        console.warn('[InstanceManager] Could not restore attack alarms:', e.message);
+      }
+    }
+
+    async _saveAttackAlarms() {
+      var saved = {};
+      this._attackAlarms.forEach(function(a, key) { saved[key] = a; });
+      try {
+        await chrome.storage.session.set({ attackAlarms: saved });
+      } catch (e) {
+        console.warn('[InstanceManager] Could not save attack alarms:', e.message);
+      }
+    }
+
+    /**
+     * Toolbar badge counts unacknowledged alerts (TravianAlertCenter), plus
+     * attack alarms not recorded there: red if any is critical, else orange.
+     */
+    _updateAlarmBadge() {
+      try {
+        if (!chrome.action) return;
+        var open = self.TravianAlertCenter ? self.TravianAlertCenter.unacknowledged() : [];
+        var extraAlarms = 0;
+        this._attackAlarms.forEach(function(a, key) {
+          if (!open.some(function(o) { return o.serverKey === key && o.kind === 'attack'; })) extraAlarms++;
+        });
+        var critical = extraAlarms > 0 || open.some(function(o) { return o.severity === 'critical'; });
+        var count = open.length + extraAlarms;
+        chrome.action.setBadgeBackgroundColor({ color: critical ? '#ff3366' : '#ffaa00' });
+        chrome.action.setBadgeText({ text: count > 0 ? String(count) : '' });
+      } catch (_) {}
+    }
+
+    /**
+     * Show a desktop notification.
+     * @param {string} idPrefix - Unique prefix; a timestamp is appended
+     * @param {string} title
+     * @param {string} message
+     * @param {boolean} urgent - Max priority and sticky until dismissed
+     */
+    static notify(idPrefix, title, message, urgent) {
+      try {
+        chrome.notifications.create(idPrefix + '_' + Date.now(), {
+          type: 'basic',
+          iconUrl: '../icons/icon48.png',
+          title: title,
+          message: message,
+          priority: urgent ? 2 : 0,
+          requireInteraction: !!urgent // don't auto-dismiss
+        });
+      } catch (e) {
+        console.warn('[InstanceManager] Notification failed:', e.message);
+      }
     }
   }
 
@@ -0,0 +1,386 @@
+/**
+ * MapStore — Daily map.sql snapshots per server, with population history.
+ *
+ * Travian servers publish the whole world as /map.sql once a day.
+ * refresh() downloads it (parsed by TravianMapScanner.parseSql) into
+ * IndexedDB (database 'travian_map'):
+ *   villages  — { serverKey, fieldId, vid, x, y, tribe, name, uid, player, aid,
+ *                 alliance, pop, capital, firstSeen, history }
+ *               key [serverKey, fieldId]; index serverKey
+ *   players   — { serverKey, uid, name, tribe, aid, alliance, villages, pop,
+ *                 firstSeen, history }
+ *               key [serverKey, uid]; index serverKey
+ *   alliances — { serverKey, aid, name, players, villages, pop, firstSeen, history }
+ *               key [serverKey, aid]; index serverKey
+ *
+ * history is [[day, pop], ...] for the last HISTORY_DAYS dumps (day =
+ * 'YYYY-MM-DD' in UTC), oldest first, so villages and players whose
+ * population stopped growing can be found. Villages missing from a new
+ * dump were destroyed and are removed; a conquered village keeps its row
+ * (same map field) under its new owner.
+ *
+ * Per-server metadata is kept under 'map_meta':
+ *   { <serverKey>: { fetchedAt, day, bytes, villages, players, alliances, mapRadius,
+ *                    delta: { newVillages, removedVillages, conquered, newPlayers, grown, shrunk } } }
+ * Servers refreshed once are refreshed again every day by the 'mapRefresh'
+ * alarm (see the service worker).
+ *
+ * Runs in service worker context. Exported via self.TravianMapStore.
+ */
+(function(root) {
+  'use strict';
+
+  var DB_NAME = 'travian_map';
+  var DB_VERSION = 1;
+  var META_KEY = 'map_meta';
+  var HISTORY_DAYS = 14;
+  var MAX_QUERY_LIMIT = 500;
+
+  /** Which store each query kind reads */
+  var KINDS = { villages: 'villages', players: 'players', alliances: 'alliances' };
+
+  var _dbPromise = null;
+  var _refreshing = {};   // serverKey → in-flight refresh promise
+
+  var TravianMapStore = {};
+
+  TravianMapStore.HISTORY_DAYS = HISTORY_DAYS;
+
+  // ── Database ────────────────────────────────────────────────────────
+
+  function _open() {
+    if (_dbPromise) return _dbPromise;
+    _dbPromise = new Promise(function(resolve, reject) {
+      var req = root.indexedDB.open(DB_NAME, DB_VERSION);
+      req.onupgradeneeded = function(e) {
+        var db = req.result;
+        if (e.oldVersion < 1) {
+          db.createObjectStore('villages', { keyPath: ['serverKey', 'fieldId'] }).createIndex('serverKey', 'serverKey');
+          db.createObjectStore('players', { keyPath: ['serverKey', 'uid'] }).createIndex('serverKey', 'serverKey');
+          db.createObjectStore('alliances', { keyPath: ['serverKey', 'aid'] }).createIndex('serverKey', 'serverKey');
+        }
+      };
+      req.onsuccess = function() { resolve(req.result); };
+      req.onerror = function() {
+        _dbPromise = null;
+        reject(req.error);
+      };
+    });
+    return _dbPromise;
+  }
+
+  function _done(tx) {
+    return new Promise(function(resolve, reject) {
+      tx.oncomplete = function() { resolve(); };
+      tx.onerror = tx.onabort = function() { reject(tx.error); };
+    });
+  }
+
+  /** All rows of one server in a store */
+  async function _all(store, serverKey) {
+    var db = await _open();
+    var tx = db.transaction(store);
+    var req = tx.objectStore(store).index('serverKey').getAll(serverKey);
+    await _done(tx);
+    return req.result || [];
+  }
+
+  // ── Helpers ─────────────────────────────────────────────────────────
+
+  function _day(ms) {
+    return new Date(ms).toISOString().slice(0, 10);
+  }
+
+  /** Append (or replace today's) population to a history, keeping HISTORY_DAYS entries. */
+  function _pushHistory(history, day, pop) {
+    var h = Array.isArray(history) ? history.slice() : [];
+    if (h.length > 0 && h[h.length - 1][0] === day) h.pop();
+    h.push([day, pop]);
+    return h.length > HISTORY_DAYS ? h.slice(h.length - HISTORY_DAYS) : h;
+  }
+
+  /**
+   * Population change over the recorded history.
+   * @param {Array<Array>} history - [[day, pop], ...]
+   * @param {number} [days] - Only look this many dumps back
+   * @returns {{ days: number, change: number }} days = dumps compared (0 if only one)
+   */
+  TravianMapStore.growth = function(history, days) {
+    var h = Array.isArray(history) ? history : [];
+    if (days) h = h.slice(-(days + 1));
+    if (h.length < 2) return { days: 0, change: 0 };
+    return { days: h.length - 1, change: h[h.length - 1][1] - h[0][1] };
+  };
+
+  /**
+   * Distance between two fields on a map that wraps around at the edges.
+   * @param {{x: number, y: number}} a
+   * @param {{x: number, y: number}} b
+   * @param {number} [mapRadius=200] - Coordinates run from -mapRadius to +mapRadius
+   * @returns {number}
+   */
+  TravianMapStore.distance = function(a, b, mapRadius) {
+    var size = 2 * (mapRadius || 200) + 1;
+    var dx = Math.abs(a.x - b.x);
+    var dy = Math.abs(a.y - b.y);
+    dx = Math.min(dx, size - dx);
+    dy = Math.min(dy, size - dy);
+    return Math.sqrt(dx * dx + dy * dy);
+  };
+
+  /** Known map sizes: ±100, ±200, ±400 */
+  function _mapRadius(tiles) {
+    var max = 0;
+    tiles.forEach(function(t) { max = Math.max(max, Math.abs(t.x), Math.abs(t.y)); });
+    return max <= 100 ? 100 : max <= 200 ? 200 : 400;
+  }
+
+  // ── Metadata ────────────────────────────────────────────────────────
+
+  /**
+   * @param {string} [serverKey]
+   * @returns {Promise<Object|null>} One server's metadata, or all of them if no key
+   */
+  TravianMapStore.getMeta = async function(serverKey) {
+    var meta = await root.TravianStorage.get(META_KEY, {}) || {};
+    return serverKey ? (meta[serverKey] || null) : meta;
+  };
+
+  async function _saveMeta(serverKey, entry) {
+    var meta = await root.TravianStorage.get(META_KEY, {}) || {};
+    meta[serverKey] = entry;
+    await root.TravianStorage.set(META_KEY, meta);
+  }
+
+  // ── Refresh ─────────────────────────────────────────────────────────
+
+  /**
+   * Download and store today's map.sql for a server.
+   * @param {string} serverKey - Server hostname
+   * @param {{ force?: boolean }} [opts] - force = download even if today's dump is stored
+   * @returns {Promise<Object>} Server metadata (skipped: true if today's dump was already stored)
+   * @throws {Error} On download failure or an empty dump
+   */
+  TravianMapStore.refresh = function(serverKey, opts) {
+    if (!_refreshing[serverKey]) {
+      _refreshing[serverKey] = _refresh(serverKey, opts || {}).finally(function() {
+        delete _refreshing[serverKey];
+      });
+    }
+    return _refreshing[serverKey];
+  };
+
+  async function _refresh(serverKey, opts) {
+    var now = Date.now();
+    var day = _day(now);
+    var prevMeta = await TravianMapStore.getMeta(serverKey);
+    if (prevMeta && prevMeta.day === day && !opts.force) return Object.assign({ skipped: true }, prevMeta);
+
+    var resp = await fetch('https://' + serverKey + '/map.sql', { cache: 'no-store', credentials: 'omit' });
+    if (!resp.ok) throw new Error('HTTP ' + resp.status + ' fetching map.sql');
+    var sql = await resp.text();
+    var tiles = root.TravianMapScanner.parseSql(sql).filter(function(t) { return t.population > 0 || t.userId > 0; });
+    if (tiles.length === 0) throw new Error('map.sql from ' + serverKey + ' has no villages');
+
+    var oldVillages = {};
+    (await _all('villages', serverKey)).forEach(function(v) { oldVillages[v.fieldId] = v; });
+    var oldPlayers = {};
+    (await _all('players', serverKey)).forEach(function(p) { oldPlayers[p.uid] = p; });
+    var oldAlliances = {};
+    (await _all('alliances', serverKey)).forEach(function(a) { oldAlliances[a.aid] = a; });
+
+    var delta = { newVillages: 0, removedVillages: 0, conquered: 0, newPlayers: 0, grown: 0, shrunk: 0 };
+    var villages = [], players = {}, alliances = {};
+
+    tiles.forEach(function(t) {
+      var prev = oldVillages[t.tileId];
+      if (!prev) delta.newVillages++;
+      else {
+        delete oldVillages[t.tileId];
+        if (prev.uid !== t.userId) delta.conquered++;
+        else if (t.population > prev.pop) delta.grown++;
+        else if (t.population < prev.pop) delta.shrunk++;
+      }
+      villages.push({
+        serverKey: serverKey,
+        fieldId: t.tileId,
+        vid: t.playerId,   // MapScanner's "playerId" column is the village id
+        x: t.x,
+        y: t.y,
+        tribe: t.tribe,
+        name: t.villageName,
+        uid: t.userId,
+        player: t.playerName,
+        aid: t.allianceId,
+        alliance: t.allianceName,
+        pop: t.population,
+        capital: t.isCapital,
+        firstSeen: prev ? prev.firstSeen : day,
+        history: _pushHistory(prev && prev.uid === t.userId ? prev.history : [], day, t.population)
+      });
+
+      if (t.userId > 0) {
+        var p = players[t.userId] || (players[t.userId] = {
+          serverKey: serverKey, uid: t.userId, name: t.playerName, tribe: t.tribe,
+          aid: t.allianceId, alliance: t.allianceName, villages: 0, pop: 0
+        });
+        p.villages++;
+        p.pop += t.population;
+      }
+      if (t.allianceId > 0) {
+        var a = alliances[t.allianceId] || (alliances[t.allianceId] = {
+          serverKey: serverKey, aid: t.allianceId, name: t.allianceName, players: {}, villages: 0, pop: 0
+        });
+        a.players[t.userId] = true;
+        a.villages++;
+        a.pop += t.population;
+      }
+    });
+    delta.removedVillages = Object.keys(oldVillages).length;
+
+    var playerRows = Object.keys(players).map(function(uid) {
+      var p = players[uid];
+      var prev = oldPlayers[uid];
+      if (!prev) delta.newPlayers++;
+      p.firstSeen = prev ? prev.firstSeen : day;
+      p.history = _pushHistory(prev ? prev.history : [], day, p.pop);
+      delete oldPlayers[uid];
+      return p;
+    });
+    var allianceRows = Object.keys(alliances).map(function(aid) {
+      var a = alliances[aid];
+      var prev = oldAlliances[aid];
+      a.players = Object.keys(a.players).length;
+      a.firstSeen = prev ? prev.firstSeen : day;
+      a.history = _pushHistory(prev ? prev.history : [], day, a.pop);
+      delete oldAlliances[aid];
+      return a;
+    });
+
+    var db = await _open();
+    var tx = db.transaction(['villages', 'players', 'alliances'], 'readwrite');
+    var vStore = tx.objectStore('villages');
+    villages.forEach(function(v) { vStore.put(v); });
+    Object.keys(oldVillages).forEach(function(fid) { vStore.delete([serverKey, Number(fid)]); });
+    var pStore = tx.objectStore('players');
+    playerRows.forEach(function(p) { pStore.put(p); });
+    Object.keys(oldPlayers).forEach(function(uid) { pStore.delete([serverKey, Number(uid)]); });
+    var aStore = tx.objectStore('alliances');
+    allianceRows.forEach(function(a) { aStore.put(a); });
+    Object.keys(oldAlliances).forEach(function(aid) { aStore.delete([serverKey, Number(aid)]); });
+    await _done(tx);
+
+    var meta = {
+      fetchedAt: now,
+      day: day,
+      bytes: sql.length,
+      villages: villages.length,
+      players: playerRows.length,
+      alliances: allianceRows.length,
+      mapRadius: _mapRadius(tiles),
+      delta: prevMeta ? delta : null   // first dump: nothing to compare with
+    };
+    await _saveMeta(serverKey, meta);
+    return meta;
+  }
+
+  // ── Query ───────────────────────────────────────────────────────────
+
+  function _matches(value, id, wanted) {
+    if (wanted === undefined || wanted === null || wanted === '') return true;
+    if (typeof wanted === 'number') return id === wanted;
+    return String(value || '').toLowerCase().indexOf(String(wanted).toLowerCase()) !== -1;
+  }
+
+  /**
+   * Search the stored map of a server.
+   * @param {string} serverKey
+   * @param {Object} [filter]
+   * @param {'villages'|'players'|'alliances'} [filter.kind='villages']
+   * @param {{x: number, y: number}} [filter.center] - Adds distance (villages); needed for radius
+   * @param {number} [filter.radius]
+   * @param {number|string} [filter.player] - uid, or part of the name
+   * @param {number|string} [filter.alliance] - aid, or part of the name
+   * @param {number} [filter.tribe]
+   * @param {number} [filter.minPop]
+   * @param {number} [filter.maxPop]
+   * @param {'distance'|'pop'|'-pop'|'growth'} [filter.sort] - Default: distance with a
+   *   center, else biggest first
+   * @param {number} [filter.offset=0]
+   * @param {number} [filter.limit=100] - Capped at 500
+   * @returns {Promise<{ entries: Array<Object>, total: number, meta: Object|null }>}
+   *   Entries carry distance (with a center) and growth ({ days, change } over the history)
+   * @throws {Error} On an unknown kind
+   */
+  TravianMapStore.query = async function(serverKey, filter) {
+    filter = filter || {};
+    var kind = filter.kind || 'villages';
+    if (!KINDS[kind]) throw new Error('Unknown map kind: ' + kind);
+    var meta = await TravianMapStore.getMeta(serverKey);
+    var rows = await _all(KINDS[kind], serverKey);
+    var center = filter.center && typeof filter.center.x === 'number' && typeof filter.center.y === 'number'
+      ? filter.center : null;
+    var radiusMap = meta ? meta.mapRadius : 200;
+
+    var hits = [];
+    rows.forEach(function(r) {
+      if (kind === 'alliances') {
+        if (!_matches(r.name, r.aid, filter.alliance)) return;
+      } else {
+        if (!_matches(kind === 'players' ? r.name : r.player, r.uid, filter.player)) return;
+        if (!_matches(r.alliance, r.aid, filter.alliance)) return;
+        if (filter.tribe !== undefined && filter.tribe !== null && r.tribe !== Number(filter.tribe)) return;
+      }
+      if (filter.minPop !== undefined && r.pop < filter.minPop) return;
+      if (filter.maxPop !== undefined && r.pop > filter.maxPop) return;
+      var out = Object.assign({}, r, { growth: TravianMapStore.growth(r.history) });
+      if (center && kind === 'villages') {
+        out.distance = Math.round(TravianMapStore.distance(center, r, radiusMap) * 10) / 10;
+        if (filter.radius !== undefined && out.distance > filter.radius) return;
+      }
+      hits.push(out);
+    });
+
+    var sort = filter.sort || (center && kind === 'villages' ? 'distance' : '-pop');
+    hits.sort(function(a, b) {
+      if (sort === 'distance') return (a.distance || 0) - (b.distance || 0);
+      if (sort === 'pop') return a.pop - b.pop;
+      if (sort === 'growth') return a.growth.change - b.growth.change;
+      return b.pop - a.pop;
+    });
+
+    var offset = Math.max(0, filter.offset || 0);
+    var limit = Math.min(MAX_QUERY_LIMIT, Math.max(1, filter.limit || 100));
+    return { entries: hits.slice(offset, offset + limit), total: hits.length, meta: meta };
+  };
+
+  /**
+   * Player rows by uid.
+   * @param {string} serverKey
+   * @param {number[]} uids
+   * @returns {Promise<Object.<number, Object>>} uid → row (unknown uids left out)
+   */
+  TravianMapStore.getPlayers = async function(serverKey, uids) {
+    var db = await _open();
+    var tx = db.transaction('players');
+    var store = tx.objectStore('players');
+    var reqs = uids.map(function(uid) { return store.get([serverKey, Number(uid)]); });
+    await _done(tx);
+    var out = {};
+    reqs.forEach(function(r) { if (r.result) out[r.result.uid] = r.result; });
+    return out;
+  };
+
+  /**
+   * Servers to refresh from the daily alarm: those with a stored map that
+   * isn't from today.
+   * @returns {Promise<string[]>}
+   */
+  TravianMapStore.staleServers = async function() {
+    var meta = await TravianMapStore.getMeta();
+    var today = _day(Date.now());
+    return Object.keys(meta).filter(function(k) { return meta[k].day !== today; });
+  };
+
+  root.TravianMapStore = TravianMapStore;
+})(typeof window !== 'undefined' ? window : self);
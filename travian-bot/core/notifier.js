@@ -0,0 +1,281 @@
+/**
+ * Notifier — Routes bot alerts to pluggable notification channels.
+ *
+ * Alerts come from InstanceManager as { serverKey, kind, title, message,
+ * urgent }. Which channels receive an alert is decided by routing rules in
+ * the server's config.notificationConfig:
+ *
+ *   routes:     { <kind>: ['desktop', 'telegram', ...] }  — per-kind channel list
+ *   thresholds: { <channel>: 'info'|'warning'|'critical' } — minimum severity
+ *   quietHours: { enabled, start, end, allow,              — local hours; inside the
+ *                 channels: { <channel>: { enabled, start,  window only alerts at or
+ *                                          end, allow } } }  above `allow` (default
+ *               'critical', so attacks still get through) are delivered. A
+ *               channels entry overrides the global window for that channel;
+ *               alerts suppressed by quiet hours are dropped, not held.
+ *
+ * Kinds or channels missing from the rules fall back to DEFAULT_ROUTES and
+ * DEFAULT_THRESHOLDS, so an empty notificationConfig behaves sensibly.
+ *
+ * Channels are registered by the service worker:
+ *   TravianNotifier.register('desktop', { send(alert, nc), test() })
+ * send() resolves true if delivered, false if the channel skipped it.
+ *
+ * Runs in service worker context. Exported via self.TravianNotifier.
+ */
+(function(root) {
+  'use strict';
+
+  var SEVERITIES = ['info', 'warning', 'critical'];
+
+  /** Default severity of each alert kind */
+  var KIND_SEVERITY = {
+    attack:    'critical',
+    emergency: 'critical',
+    captcha:   'critical',
+    session:   'warning',
+    crop:      'warning',
+    buildIdle: 'info',
+    raid:      'info',
+    adventure: 'info'
+  };
+
+  /** Channels each kind goes to when notificationConfig.routes has no entry */
+  var DEFAULT_ROUTES = {
+    attack:    ['desktop', 'telegram', 'discord', 'webhook'],
+    emergency: ['desktop', 'telegram', 'discord', 'webhook'],
+    captcha:   ['desktop', 'telegram', 'discord', 'webhook'],
+    session:   ['desktop', 'telegram', 'discord', 'webhook'],
+    crop:      ['desktop', 'telegram', 'discord', 'webhook'],
+    buildIdle: ['desktop'],
+    raid:      ['discord', 'webhook'],
+    adventure: ['discord', 'webhook']
+  };
+
+  /** Minimum severity per channel when notificationConfig.thresholds has no entry */
+  var DEFAULT_THRESHOLDS = {
+    desktop:  'info',
+    telegram: 'warning',
+    discord:  'info',
+    webhook:  'info'
+  };
+
+  var _channels = {};
+
+  var TravianNotifier = {};
+
+  TravianNotifier.SEVERITIES = SEVERITIES;
+  TravianNotifier.KIND_SEVERITY = KIND_SEVERITY;
+  TravianNotifier.DEFAULT_ROUTES = DEFAULT_ROUTES;
+  TravianNotifier.DEFAULT_THRESHOLDS = DEFAULT_THRESHOLDS;
+
+  // ── Channel registry ────────────────────────────────────────────────
+
+  /**
+   * Register (or replace) a channel.
+   * @param {string} name
+   * @param {{ send: function(Object, Object): Promise<boolean>, test: function(): Promise<*> }} channel
+   */
+  TravianNotifier.register = function(name, channel) {
+    if (!channel || typeof channel.send !== 'function' || typeof channel.test !== 'function') {
+      throw new Error('Channel "' + name + '" must implement send() and test()');
+    }
+    _channels[name] = channel;
+  };
+
+  /** @returns {string[]} Registered channel names */
+  TravianNotifier.listChannels = function() {
+    return Object.keys(_channels);
+  };
+
+  // ── Routing ─────────────────────────────────────────────────────────
+
+  function _rank(severity) {
+    var i = SEVERITIES.indexOf(severity);
+    return i === -1 ? 0 : i;
+  }
+
+  /**
+   * True if `date` falls inside the quiet-hours window. The window may
+   * wrap midnight (start 23, end 7); start === end means no window.
+   */
+  TravianNotifier.inQuietHours = function(quietHours, date) {
+    if (!quietHours || !quietHours.enabled) return false;
+    var start = quietHours.start, end = quietHours.end;
+    if (start === end) return false;
+    var hour = (date || new Date()).getHours();
+    return start < end ? (hour >= start && hour < end) : (hour >= start || hour < end);
+  };
+
+  /**
+   * Effective quiet-hours rule for one channel: the global window with the
+   * channel's own entry (if any) laid over it.
+   * @param {Object} [nc] - notificationConfig
+   * @param {string} channel
+   * @returns {{ enabled: boolean, start: number, end: number, allow: string }}
+   */
+  TravianNotifier.quietRule = function(nc, channel) {
+    var qh = (nc && nc.quietHours) || {};
+    var own = (qh.channels && qh.channels[channel]) || {};
+    return {
+      enabled: own.enabled !== undefined ? !!own.enabled : !!qh.enabled,
+      start: own.start !== undefined ? own.start : (qh.start !== undefined ? qh.start : 23),
+      end: own.end !== undefined ? own.end : (qh.end !== undefined ? qh.end : 7),
+      allow: own.allow || qh.allow || 'critical'
+    };
+  };
+
+  /**
+   * When the quiet window containing `date` ends.
+   * @returns {number|null} Epoch ms, or null if `date` is not in quiet hours
+   */
+  TravianNotifier.quietUntil = function(quietHours, date) {
+    date = date || new Date();
+    if (!TravianNotifier.inQuietHours(quietHours, date)) return null;
+    var end = new Date(date.getTime());
+    end.setMinutes(0, 0, 0);
+    end.setHours(quietHours.end);
+    if (end <= date) end.setDate(end.getDate() + 1);
+    return end.getTime();
+  };
+
+  function _quieted(severity, rule, now) {
+    return TravianNotifier.inQuietHours(rule, now) && _rank(severity) < _rank(rule.allow);
+  }
+
+  /**
+   * Channels an alert should go to under the given rules.
+   * @param {{ kind: string, severity?: string }} alert
+   * @param {Object} [nc] - notificationConfig
+   * @param {Date} [now]
+   * @returns {string[]}
+   */
+  TravianNotifier.resolveChannels = function(alert, nc, now) {
+    nc = nc || {};
+    var severity = alert.severity || KIND_SEVERITY[alert.kind] || 'info';
+
+    var routes = nc.routes && Array.isArray(nc.routes[alert.kind])
+      ? nc.routes[alert.kind]
+      : (DEFAULT_ROUTES[alert.kind] || []);
+    var thresholds = nc.thresholds || {};
+
+    return routes.filter(function(name) {
+      if (!_channels[name]) return false;
+      var min = thresholds[name] || DEFAULT_THRESHOLDS[name] || 'info';
+      if (_rank(severity) < _rank(min)) return false;
+      return !_quieted(severity, TravianNotifier.quietRule(nc, name), now);
+    });
+  };
+
+  /**
+   * What would happen to each alert kind on each routed channel right now,
+   * and when suppressed ones would get through again.
+   * @param {Object} [nc] - notificationConfig
+   * @param {Date} [now]
+   * @returns {{ at: number,
+   *   channels: Object.<string, { registered: boolean, threshold: string, quiet: { active: boolean, until: number|null, allow: string } }>,
+   *   kinds: Object.<string, { severity: string, channels: Object.<string, { status: string, until?: number }> }> }}
+   *   status: 'deliver' | 'quiet' (dropped until `until`) | 'belowThreshold' | 'unregistered'
+   */
+  TravianNotifier.snapshot = function(nc, now) {
+    nc = nc || {};
+    now = now || new Date();
+    var thresholds = nc.thresholds || {};
+    var channels = {};
+    Object.keys(DEFAULT_THRESHOLDS).forEach(function(name) {
+      var rule = TravianNotifier.quietRule(nc, name);
+      var until = TravianNotifier.quietUntil(rule, now);
+      channels[name] = {
+        registered: !!_channels[name],
+        threshold: thresholds[name] || DEFAULT_THRESHOLDS[name],
+        quiet: { active: until !== null, until: until, allow: rule.allow }
+      };
+    });
+
+    var kinds = {};
+    Object.keys(KIND_SEVERITY).forEach(function(kind) {
+      var severity = KIND_SEVERITY[kind];
+      var routes = nc.routes && Array.isArray(nc.routes[kind]) ? nc.routes[kind] : (DEFAULT_ROUTES[kind] || []);
+      var out = {};
+      routes.forEach(function(name) {
+        var ch = channels[name];
+        if (!ch || !ch.registered) out[name] = { status: 'unregistered' };
+        else if (_rank(severity) < _rank(ch.threshold)) out[name] = { status: 'belowThreshold' };
+        else if (ch.quiet.active && _rank(severity) < _rank(ch.quiet.allow)) out[name] = { status: 'quiet', until: ch.quiet.until };
+        else out[name] = { status: 'deliver' };
+      });
+      kinds[kind] = { severity: severity, channels: out };
+    });
+    return { at: now.getTime(), channels: channels, kinds: kinds };
+  };
+
+  /**
+   * Check routing rules for unknown kinds, channels and severities.
+   * @param {Object} [nc] - notificationConfig
+   * @returns {string[]} Error messages (empty if valid)
+   */
+  TravianNotifier.checkRules = function(nc) {
+    var errors = [];
+    if (!nc) return errors;
+    var known = Object.keys(DEFAULT_THRESHOLDS);
+    for (var kind in (nc.routes || {})) {
+      if (!KIND_SEVERITY[kind]) errors.push('routes.' + kind + ': unknown alert kind');
+      else if (!Array.isArray(nc.routes[kind])) errors.push('routes.' + kind + ': expected array of channels');
+      else nc.routes[kind].forEach(function(ch) {
+        if (known.indexOf(ch) === -1) errors.push('routes.' + kind + ': unknown channel "' + ch + '"');
+      });
+    }
+    for (var ch in (nc.thresholds || {})) {
+      if (known.indexOf(ch) === -1) errors.push('thresholds.' + ch + ': unknown channel');
+      else if (SEVERITIES.indexOf(nc.thresholds[ch]) === -1) errors.push('thresholds.' + ch + ': "' + nc.thresholds[ch] + '" not a severity');
+    }
+    var qh = nc.quietHours || {};
+    if (qh.allow !== undefined && SEVERITIES.indexOf(qh.allow) === -1) errors.push('quietHours.allow: "' + qh.allow + '" not a severity');
+    for (var qc in (qh.channels || {})) {
+      var own = qh.channels[qc] || {};
+      if (known.indexOf(qc) === -1) { errors.push('quietHours.channels.' + qc + ': unknown channel'); continue; }
+      ['start', 'end'].forEach(function(f) {
+        if (own[f] !== undefined && !(own[f] >= 0 && own[f] <= 23 && own[f] % 1 === 0)) errors.push('quietHours.channels.' + qc + '.' + f + ': expected hour 0-23');
+      });
+      if (own.allow !== undefined && SEVERITIES.indexOf(own.allow) === -1) errors.push('quietHours.channels.' + qc + '.allow: "' + own.allow + '" not a severity');
+    }
+    return errors;
+  };
+
+  // ── Delivery ────────────────────────────────────────────────────────
+
+  /**
+   * Send an alert to every channel its rules select. Channel failures are
+   * logged and reported, never thrown.
+   * @param {{ serverKey: string, kind: string, title: string, message: string, urgent?: boolean }} alert
+   * @param {Object} [nc] - notificationConfig of the alert's server
+   * @returns {Promise<Object.<string, string>>} channel → 'sent' | 'skipped' | 'error: ...'
+   */
+  TravianNotifier.dispatch = async function(alert, nc) {
+    alert.severity = alert.severity || KIND_SEVERITY[alert.kind] || 'info';
+    var names = TravianNotifier.resolveChannels(alert, nc);
+    var results = {};
+    await Promise.all(names.map(function(name) {
+      return Promise.resolve()
+        .then(function() { return _channels[name].send(alert, nc || {}); })
+        .then(function(sent) { results[name] = sent === false ? 'skipped' : 'sent'; })
+        .catch(function(err) {
+          results[name] = 'error: ' + err.message;
+          console.warn('[Notifier] ' + name + ' failed for ' + alert.kind + ': ' + err.message);
+        });
+    }));
+    return results;
+  };
+
+  /**
+   * Send a test message through one channel, bypassing routing.
+   * @param {string} name
+   * @returns {Promise<*>} Whatever the channel's test() resolves to
+   */
+  TravianNotifier.test = function(name) {
+    if (!_channels[name]) return Promise.reject(new Error('Unknown channel: ' + name));
+    return _channels[name].test();
+  };
+
+  root.TravianNotifier = TravianNotifier;
+})(typeof window !== 'undefined' ? window : self);
@@ -5,6 +5,11 @@
  * Sliding windows give accurate counts at any moment (unlike fixed-window counters
  * that can miss bursts at boundaries).
  *
+ * The per-minute, per-hour and per-day action ceilings are enforced by
+ * pacing: nextAllowedAt() says when the fullest window next has room, and
+ * SafetyEngine holds the bot back (or pauses it) until then. report()
+ * gives current pacing against each ceiling for the risk report.
+ *
  * Exported: self.TravianRateLimiter
  */
 (function(root) {
@@ -49,6 +54,17 @@
     }
   };
 
+  /**
+   * When the window will next hold fewer than `limit` events.
+   * @param {number} limit
+   * @returns {number|null} Epoch ms, or null if already below the limit
+   */
+  SlidingWindowCounter.prototype.freeAt = function(limit) {
+    this._prune();
+    if (this.timestamps.length < limit) return null;
+    return this.timestamps[this.timestamps.length - limit] + this.windowMs;
+  };
+
   /** Serialize for persistence */
   SlidingWindowCounter.prototype.serialize = function() {
     this._prune();
@@ -63,11 +79,19 @@
 
   // ---- RateLimiter ----
 
+  /** Ceilings enforced by pacing: [tier, counter, limit] */
+  var PACED = [
+    ['minute', 'actionsPerMinute', 'actionsPerMinute'],
+    ['hour', 'actionsPerHour', 'actionsPerHour'],
+    ['day', 'actionsPerDay', 'actionsPerDay']
+  ];
+
   function TravianRateLimiter() {
     // Multi-tier sliding window counters
+    // Caps are the highest configurable limits (see safetyConfig in configSchema)
     this.actionsPerMinute = new SlidingWindowCounter(60000, 20);         // 1 min
-    this.actionsPerHour = new SlidingWindowCounter(3600000, 200);        // 1 hour
-    this.actionsPerDay = new SlidingWindowCounter(86400000, 1000);       // 24 hours
+    this.actionsPerHour = new SlidingWindowCounter(3600000, 300);        // 1 hour
+    this.actionsPerDay = new SlidingWindowCounter(86400000, 3000);       // 24 hours
     this.navigationsPerWindow = new SlidingWindowCounter(300000, 50);    // 5 min
     this.retriesPerHour = new SlidingWindowCounter(3600000, 100);        // 1 hour
     this.farmRaidsPerHour = new SlidingWindowCounter(3600000, 100);      // 1 hour
@@ -167,6 +191,39 @@
     return { allowed: violations.length === 0, violations: violations };
   };
 
+  /**
+   * Earliest time every paced ceiling (minute, hour, day) has room again.
+   * @returns {number|null} Epoch ms, or null if an action is allowed now
+   */
+  TravianRateLimiter.prototype.nextAllowedAt = function() {
+    var at = null;
+    for (var i = 0; i < PACED.length; i++) {
+      var free = this[PACED[i][1]].freeAt(this.limits[PACED[i][2]]);
+      if (free !== null && (at === null || free > at)) at = free;
+    }
+    return at;
+  };
+
+  /**
+   * Current pacing against each ceiling.
+   * @returns {Array<{ tier: string, count: number, limit: number, percent: number, freeAt: number|null }>}
+   */
+  TravianRateLimiter.prototype.report = function() {
+    var self = this;
+    return PACED.map(function(p) {
+      var counter = self[p[1]];
+      var limit = self.limits[p[2]];
+      var count = counter.count();
+      return {
+        tier: p[0],
+        count: count,
+        limit: limit,
+        percent: limit > 0 ? Math.round(count / limit * 100) : 0,
+        freeAt: counter.freeAt(limit)
+      };
+    });
+  };
+
   /**
    * Check if a specific action type is rate-limited.
    * @param {string} actionType
@@ -39,6 +39,9 @@
     // Auto-pause escalation state
     this._autoPauseCount = 0;
     this._lastRiskResult = null;
+
+    // Set while an action ceiling holds the bot back (epoch ms it lifts)
+    this._throttledUntil = null;
   }
 
   // ---- Storage key ----
@@ -183,6 +186,11 @@
       // Other rate limit violations inform risk scoring but don't block the cycle
     }
 
+    // 8b. Action ceilings (per minute / hour / day): hold the bot back until
+    // the window has room, or pause it if safetyConfig.onRateLimit says so
+    var throttle = this._checkPacing(config);
+    if (throttle) return throttle;
+
     // 9. Reset auto-pause count when risk is LOW
     if (risk.level === 'LOW' && this._autoPauseCount > 0) {
       this._autoPauseCount = 0;
@@ -245,6 +253,12 @@
       return { block: true, reason: 'rate_limit:' + task.type };
     }
 
+    // 2b. Global action ceilings — the task waits, it isn't dropped
+    var retryAt = this.rateLimiter.nextAllowedAt();
+    if (retryAt) {
+      return { block: true, reason: 'rate_limit:pacing', retryAt: retryAt };
+    }
+
     // 3. Policy evaluation
     var context = {
       actionType: task.type,
@@ -297,6 +311,37 @@
     this._dirty = true;
   };
 
+  // ════════════════════════════════════════════════
+  //  Pacing (global action ceilings)
+  // ════════════════════════════════════════════════
+
+  /**
+   * @param {object} config
+   * @returns {{ block: boolean, action: string, reason: string, resumeAt: number }|null}
+   *   null if an action is allowed now
+   */
+  TravianSafetyEngine.prototype._checkPacing = function(config) {
+    var resumeAt = this.rateLimiter.nextAllowedAt();
+    if (!resumeAt) {
+      if (this._throttledUntil) Logger.log('INFO', '[Safety] Action ceiling cleared — pacing resumed');
+      this._throttledUntil = null;
+      return null;
+    }
+
+    var full = this.rateLimiter.report().filter(function(t) { return t.freeAt !== null; })
+      .map(function(t) { return t.count + '/' + t.limit + ' per ' + t.tier; });
+    var reason = 'Action limit reached (' + full.join(', ') + ')';
+    var pause = config && config.safetyConfig && config.safetyConfig.onRateLimit === 'pause';
+
+    if (!this._throttledUntil) {
+      Logger.log('WARN', '[Safety] ' + reason + ' — ' + (pause ? 'pausing' : 'waiting until ' +
+        new Date(resumeAt).toLocaleTimeString()));
+      if (pause) this._notify('Paused [' + this._serverKey.split('.')[0] + ']', reason);
+    }
+    this._throttledUntil = resumeAt;
+    return { block: true, action: pause ? 'pause' : 'delay', reason: reason, resumeAt: resumeAt };
+  };
+
   // ════════════════════════════════════════════════
   //  Auto-pause escalation
   // ════════════════════════════════════════════════
@@ -347,6 +392,7 @@
   TravianSafetyEngine.prototype.updateLimits = function(safetyConfig) {
     if (!safetyConfig) return;
     var limits = this.rateLimiter.limits;
+    if (safetyConfig.maxActionsPerMinute) limits.actionsPerMinute = safetyConfig.maxActionsPerMinute;
     if (safetyConfig.maxActionsPerHour) limits.actionsPerHour = safetyConfig.maxActionsPerHour;
     if (safetyConfig.maxActionsPerDay) limits.actionsPerDay = safetyConfig.maxActionsPerDay;
     if (safetyConfig.maxSessionHours) {
@@ -397,6 +443,34 @@
     };
   };
 
+  /**
+   * Current pacing versus the configured ceilings, plus the overall risk.
+   * @returns {{ serverKey: string, generatedAt: number, riskScore: number, riskLevel: string,
+   *   safeMode: boolean, throttled: boolean, throttledUntil: number|null,
+   *   tiers: Array<{ tier: string, count: number, limit: number, percent: number, freeAt: number|null }>,
+   *   farmRaidsPerHour: { count: number, limit: number }, trainCommandsPerHour: { count: number, limit: number },
+   *   sessionMs: number|null, sessionLimitMs: number, violations: Array }}
+   */
+  TravianSafetyEngine.prototype.getRiskReport = function() {
+    var rl = this.rateLimiter;
+    var throttledUntil = rl.nextAllowedAt();
+    return {
+      serverKey: this._serverKey,
+      generatedAt: Date.now(),
+      riskScore: this.riskEvaluator.currentScore,
+      riskLevel: this.riskEvaluator.currentLevel,
+      safeMode: this.safeModeController.active,
+      throttled: throttledUntil !== null,
+      throttledUntil: throttledUntil,
+      tiers: rl.report(),
+      farmRaidsPerHour: { count: rl.farmRaidsPerHour.count(), limit: rl.limits.farmRaidsPerHour },
+      trainCommandsPerHour: { count: rl.trainCommandsPerHour.count(), limit: rl.limits.trainCommandsPerHour },
+      sessionMs: rl.sessionStartTime ? Date.now() - rl.sessionStartTime : null,
+      sessionLimitMs: rl.limits.maxSessionDurationMs,
+      violations: this.activityMonitor.violations
+    };
+  };
+
   /** @returns {boolean} true if state needs persistence */
   TravianSafetyEngine.prototype.isDirty = function() {
     return this._dirty;
@@ -0,0 +1,256 @@
+/**
+ * Schedules — Cron-style jobs that drive the bot on a timetable.
+ *
+ * Each job runs one action for one server when its cron expression matches:
+ *   pause          — pause a running bot
+ *   resume         — resume a paused bot
+ *   scan           — request a game-state scan
+ *   sendFarmLists  — queue a send_farm task (rally point farm lists)
+ *
+ * A window such as "pause 02:00–07:00" is two jobs: pause at "0 2 * * *"
+ * and resume at "0 7 * * *". Expressions use the usual five fields
+ * (minute hour day-of-month month day-of-week) in local time, with *, lists,
+ * ranges and steps ("0 0-23/4 * * *" = every 4h, "15 * * * *" = :15 past
+ * each hour). Day-of-week 0 and 7 are Sunday; when both day fields are
+ * restricted, either one matching is enough (as in cron). A job with
+ * clock: 'server' reads the expression on the game server's wall clock
+ * (see TravianServerClock.wallShift) instead of local time.
+ *
+ * The service worker keeps a single chrome alarm at the earliest nextRunAt
+ * and executes due jobs when it fires. A run missed while the browser was
+ * closed happens once on the next alarm, not once per missed slot.
+ *
+ * Jobs are global, stored under 'schedules':
+ *   [{ id, serverKey, name, cron, clock, action, enabled, lastRunAt, lastResult, nextRunAt }]
+ *
+ * Runs in service worker context. Exported via self.TravianSchedules.
+ */
+(function(root) {
+  'use strict';
+
+  var STORAGE_KEY = 'schedules';
+  var MAX_JOBS = 50;
+  var SEARCH_LIMIT_MS = 5 * 366 * 86400000; // leap days can be 4 years out; Feb 30 never comes
+
+  /** Actions a job can run */
+  var ACTIONS = ['pause', 'resume', 'scan', 'sendFarmLists'];
+
+  /** Which clock a cron expression is read on */
+  var CLOCKS = ['local', 'server'];
+
+  /** [min, max] per cron field */
+  var FIELDS = [[0, 59], [0, 23], [1, 31], [1, 12], [0, 7]];
+  var FIELD_NAMES = ['minute', 'hour', 'day of month', 'month', 'day of week'];
+
+  var TravianSchedules = {};
+
+  TravianSchedules.ACTIONS = ACTIONS;
+  TravianSchedules.CLOCKS = CLOCKS;
+
+  // ── Cron expressions ────────────────────────────────────────────────
+
+  function _parseField(text, idx) {
+    var min = FIELDS[idx][0], max = FIELDS[idx][1];
+    var allowed = {};
+    text.split(',').forEach(function(part) {
+      var m = /^(\*|\d+(?:-\d+)?)(?:\/(\d+))?$/.exec(part);
+      if (!m) throw new Error(FIELD_NAMES[idx] + ': cannot parse "' + part + '"');
+      var lo = min, hi = max;
+      if (m[1] !== '*') {
+        var bounds = m[1].split('-');
+        lo = parseInt(bounds[0], 10);
+        hi = bounds.length > 1 ? parseInt(bounds[1], 10) : (m[2] ? max : lo);
+      }
+      var step = m[2] ? parseInt(m[2], 10) : 1;
+      if (lo < min || hi > max || lo > hi || step < 1) {
+        throw new Error(FIELD_NAMES[idx] + ': "' + part + '" out of range ' + min + '-' + max);
+      }
+      for (var v = lo; v <= hi; v += step) allowed[idx === 4 ? v % 7 : v] = true;
+    });
+    return { allowed: allowed, any: text === '*' };
+  }
+
+  /**
+   * Parse a five-field cron expression.
+   * @param {string} expr
+   * @returns {Array<{allowed: Object.<number, boolean>, any: boolean}>}
+   * @throws {Error} Naming the field that is invalid
+   */
+  TravianSchedules.parseCron = function(expr) {
+    var parts = String(expr || '').trim().split(/\s+/);
+    if (parts.length !== 5) throw new Error('Cron expression needs 5 fields (minute hour day month weekday)');
+    return parts.map(_parseField);
+  };
+
+  function _dayMatches(f, date) {
+    var dom = f[2].allowed[date.getDate()] === true;
+    var dow = f[4].allowed[date.getDay()] === true;
+    if (f[2].any || f[4].any) return (f[2].any || dom) && (f[4].any || dow);
+    return dom || dow;
+  }
+
+  /**
+   * Next time strictly after `from` that matches the expression.
+   * @param {string} expr
+   * @param {number} [from=Date.now()] - Epoch ms
+   * @param {number} [shiftMs=0] - Evaluate on a clock this far ahead of local time
+   * @returns {number|null} Epoch ms, or null if the expression can never match
+   */
+  TravianSchedules.nextRun = function(expr, from, shiftMs) {
+    var f = TravianSchedules.parseCron(expr);
+    var shift = shiftMs || 0;
+    var start = (from || Date.now()) + shift;
+    var d = new Date(start);
+    d.setSeconds(0, 0);
+    d.setMinutes(d.getMinutes() + 1);
+
+    while (d.getTime() - start <= SEARCH_LIMIT_MS) {
+      if (!f[3].allowed[d.getMonth() + 1]) {
+        d.setMonth(d.getMonth() + 1, 1);
+        d.setHours(0, 0, 0, 0);
+        continue;
+      }
+      if (!_dayMatches(f, d)) {
+        d.setDate(d.getDate() + 1);
+        d.setHours(0, 0, 0, 0);
+        continue;
+      }
+      if (!f[1].allowed[d.getHours()]) {
+        d.setHours(d.getHours() + 1, 0, 0, 0);
+        continue;
+      }
+      if (!f[0].allowed[d.getMinutes()]) {
+        d.setMinutes(d.getMinutes() + 1, 0, 0);
+        continue;
+      }
+      return d.getTime() - shift;
+    }
+    return null;
+  };
+
+  // ── Jobs ────────────────────────────────────────────────────────────
+
+  function _next(job, from) {
+    var shift = job.clock === 'server' && root.TravianServerClock
+      ? root.TravianServerClock.wallShift(job.serverKey) : 0;
+    return TravianSchedules.nextRun(job.cron, from, shift);
+  }
+
+  /** @returns {Promise<Array<Object>>} All jobs */
+  TravianSchedules.list = async function() {
+    var jobs = await root.TravianStorage.get(STORAGE_KEY, []);
+    return Array.isArray(jobs) ? jobs : [];
+  };
+
+  function _validate(job) {
+    if (!job.serverKey) throw new Error('serverKey required');
+    if (ACTIONS.indexOf(job.action) === -1) throw new Error('Unknown action: ' + job.action);
+    if (CLOCKS.indexOf(job.clock) === -1) throw new Error('Unknown clock: ' + job.clock);
+    TravianSchedules.parseCron(job.cron);
+  }
+
+  /**
+   * Create a job, or update the one with the same id.
+   * @param {{ id?: string, serverKey: string, name?: string, cron: string, clock?: 'local'|'server',
+   *   action: string, enabled?: boolean }} input
+   * @returns {Promise<Object>} Saved job (with nextRunAt)
+   * @throws {Error} On invalid cron, action or missing serverKey
+   */
+  TravianSchedules.save = async function(input) {
+    var jobs = await TravianSchedules.list();
+    var idx = input.id ? jobs.findIndex(function(j) { return j.id === input.id; }) : -1;
+    if (input.id && idx === -1) throw new Error('Unknown schedule: ' + input.id);
+
+    var prev = idx === -1 ? {} : jobs[idx];
+    var job = {
+      id: prev.id || 'sch_' + Date.now().toString(36) + Math.random().toString(36).slice(2, 6),
+      serverKey: input.serverKey !== undefined ? input.serverKey : prev.serverKey,
+      name: input.name !== undefined ? String(input.name) : (prev.name || ''),
+      cron: input.cron !== undefined ? String(input.cron).trim() : prev.cron,
+      clock: input.clock !== undefined ? input.clock : (prev.clock || 'local'),
+      action: input.action !== undefined ? input.action : prev.action,
+      enabled: input.enabled !== undefined ? !!input.enabled : (prev.enabled !== false),
+      lastRunAt: prev.lastRunAt || null,
+      lastResult: prev.lastResult || null,
+      nextRunAt: null
+    };
+    _validate(job);
+    job.nextRunAt = job.enabled ? _next(job) : null;
+    if (job.enabled && !job.nextRunAt) throw new Error('Cron expression never matches: ' + job.cron);
+
+    if (idx === -1) {
+      if (jobs.length >= MAX_JOBS) throw new Error('At most ' + MAX_JOBS + ' schedules');
+      jobs.push(job);
+    } else {
+      jobs[idx] = job;
+    }
+    await root.TravianStorage.set(STORAGE_KEY, jobs);
+    return job;
+  };
+
+  /**
+   * @param {string} id
+   * @returns {Promise<boolean>} False if no such job
+   */
+  TravianSchedules.remove = async function(id) {
+    var jobs = await TravianSchedules.list();
+    var rest = jobs.filter(function(j) { return j.id !== id; });
+    if (rest.length === jobs.length) return false;
+    await root.TravianStorage.set(STORAGE_KEY, rest);
+    return true;
+  };
+
+  /**
+   * Run every enabled job whose nextRunAt has passed, then advance it.
+   * @param {function(Object): Promise<string>} execute - Runs one job, resolves to a short result
+   * @param {number} [now=Date.now()]
+   * @returns {Promise<Array<{ job: Object, ok: boolean, result: string }>>}
+   */
+  TravianSchedules.runDue = async function(execute, now) {
+    now = now || Date.now();
+    var jobs = await TravianSchedules.list();
+    var ran = [];
+    for (var i = 0; i < jobs.length; i++) {
+      var job = jobs[i];
+      if (!job.enabled || !job.nextRunAt || job.nextRunAt > now) continue;
+      var ok = true, result;
+      try {
+        result = await execute(job);
+      } catch (err) {
+        ok = false;
+        result = err.message;
+      }
+      job.lastRunAt = now;
+      job.lastResult = (ok ? '' : 'failed: ') + result;
+      job.nextRunAt = _next(job, now);
+      ran.push({ job: job, ok: ok, result: result });
+    }
+    // Re-read so jobs saved while we were executing aren't overwritten
+    if (ran.length > 0) {
+      var latest = await TravianSchedules.list();
+      latest.forEach(function(j, k) {
+        var done = ran.find(function(r) { return r.job.id === j.id; });
+        if (done) latest[k] = Object.assign({}, j, {
+          lastRunAt: done.job.lastRunAt, lastResult: done.job.lastResult, nextRunAt: done.job.nextRunAt
+        });
+      });
+      await root.TravianStorage.set(STORAGE_KEY, latest);
+    }
+    return ran;
+  };
+
+  /**
+   * Earliest nextRunAt among enabled jobs (for the wake-up alarm).
+   * @returns {Promise<number|null>}
+   */
+  TravianSchedules.nextWake = async function() {
+    var jobs = await TravianSchedules.list();
+    var next = null;
+    jobs.forEach(function(j) {
+      if (j.enabled && j.nextRunAt && (next === null || j.nextRunAt < next)) next = j.nextRunAt;
+    });
+    return next;
+  };
+
+  root.TravianSchedules = TravianSchedules;
+})(typeof window !== 'undefined' ? window : self);
@@ -0,0 +1,168 @@
+/**
+ * ServerClock — Offset between the local clock and each game server.
+ *
+ * Travian timings (arrivals, building ends, scheduled sends) are server
+ * time. Two sources are combined:
+ *   - the HTTP Date header of a HEAD request to the server, bracketed by
+ *     local timestamps → clock offset (ms, ±0.5s header resolution + rtt/2)
+ *   - the "HH:MM:SS" server clock scraped from the page → the server's
+ *     timezone (UTC offset, rounded to 15 minutes)
+ *
+ * The service worker measures every active server from the 'clockSync'
+ * alarm. An offset beyond DRIFT_WARN_MS means the computer's clock is off
+ * (NTP not syncing); a jump between measurements beyond JUMP_WARN_MS means
+ * it was changed or corrected. Both are reported as 'clock:drift' events.
+ *
+ * Measurements are kept under 'server_clock' so getServerTime() works right
+ * after a service worker restart:
+ *   { <serverKey>: { offsetMs, rttMs, measuredAt, tzOffsetMinutes } }
+ *
+ * Runs in service worker context. Exported via self.TravianServerClock.
+ */
+(function(root) {
+  'use strict';
+
+  var STORAGE_KEY = 'server_clock';
+  var DRIFT_WARN_MS = 3000;
+  var JUMP_WARN_MS = 2000;
+  var MAX_RTT_MS = 5000;   // slower round trips say nothing useful about the clock
+
+  var _clocks = null;      // serverKey → measurement (lazy-loaded)
+
+  var TravianServerClock = {};
+
+  TravianServerClock.DRIFT_WARN_MS = DRIFT_WARN_MS;
+
+  async function _load() {
+    if (!_clocks) _clocks = await root.TravianStorage.get(STORAGE_KEY, {}) || {};
+    return _clocks;
+  }
+
+  function _save() {
+    return root.TravianStorage.set(STORAGE_KEY, _clocks);
+  }
+
+  /**
+   * Measure one server's clock offset from its HTTP Date header.
+   * @param {string} serverKey - Server hostname
+   * @returns {Promise<{ offsetMs: number, rttMs: number, measuredAt: number,
+   *   tzOffsetMinutes: number|null, warnings: string[] }>}
+   * @throws {Error} On network failure, missing Date header or a too slow round trip
+   */
+  TravianServerClock.measure = async function(serverKey) {
+    var clocks = await _load();
+    var t0 = Date.now();
+    var resp = await fetch('https://' + serverKey + '/', { method: 'HEAD', cache: 'no-store', credentials: 'omit' });
+    var t1 = Date.now();
+    var header = resp.headers.get('Date');
+    var serverMs = header ? Date.parse(header) : NaN;
+    if (isNaN(serverMs)) throw new Error('No Date header from ' + serverKey);
+    if (t1 - t0 > MAX_RTT_MS) throw new Error('Round trip too slow (' + (t1 - t0) + 'ms)');
+
+    // The header is truncated to whole seconds: its true value is, on
+    // average, half a second later.
+    var offsetMs = Math.round(serverMs + 500 - (t0 + t1) / 2);
+    var prev = clocks[serverKey];
+    var entry = {
+      offsetMs: offsetMs,
+      rttMs: t1 - t0,
+      measuredAt: t1,
+      tzOffsetMinutes: prev ? prev.tzOffsetMinutes : null
+    };
+
+    var warnings = [];
+    if (Math.abs(offsetMs) > DRIFT_WARN_MS) {
+      warnings.push('Local clock is ' + (offsetMs > 0 ? 'behind' : 'ahead of') + ' ' + serverKey + ' by ' +
+        (Math.abs(offsetMs) / 1000).toFixed(1) + 's — check time sync (NTP)');
+    }
+    if (prev && Math.abs(offsetMs - prev.offsetMs) > JUMP_WARN_MS) {
+      warnings.push('Clock offset to ' + serverKey + ' jumped by ' +
+        ((offsetMs - prev.offsetMs) / 1000).toFixed(1) + 's since the last measurement');
+    }
+
+    clocks[serverKey] = entry;
+    await _save();
+    return Object.assign({ warnings: warnings }, entry);
+  };
+
+  /**
+   * Learn the server's timezone from its on-page clock.
+   * @param {string} serverKey
+   * @param {string} text - Scanned server time, "HH:MM" or "HH:MM:SS"
+   * @param {number} [scannedAt=Date.now()] - Local ms when it was read
+   * @returns {Promise<number|null>} UTC offset in minutes, or null if unparseable
+   */
+  TravianServerClock.observeWallClock = async function(serverKey, text, scannedAt) {
+    var m = /(\d{1,2}):(\d{2})(?::(\d{2}))?/.exec(text || '');
+    if (!m) return null;
+    var clocks = await _load();
+    var entry = clocks[serverKey] || { offsetMs: 0, rttMs: null, measuredAt: null, tzOffsetMinutes: null };
+    var serverNow = new Date((scannedAt || Date.now()) + entry.offsetMs);
+    var wallSec = parseInt(m[1], 10) * 3600 + parseInt(m[2], 10) * 60 + (m[3] ? parseInt(m[3], 10) : 0);
+    var utcSec = serverNow.getUTCHours() * 3600 + serverNow.getUTCMinutes() * 60 + serverNow.getUTCSeconds();
+    var diffMin = (wallSec - utcSec) / 60;
+    if (diffMin > 14 * 60) diffMin -= 1440;
+    if (diffMin < -12 * 60) diffMin += 1440;
+    var tz = Math.round(diffMin / 15) * 15;
+    if (entry.tzOffsetMinutes !== tz) {
+      entry.tzOffsetMinutes = tz;
+      clocks[serverKey] = entry;
+      await _save();
+    }
+    return tz;
+  };
+
+  /**
+   * Current server time.
+   * @param {string} serverKey
+   * @returns {Promise<{ serverKey: string, now: number, offsetMs: number, rttMs: number|null,
+   *   measuredAt: number|null, tzOffsetMinutes: number|null, wallClock: string|null }>}
+   *   now = server epoch ms; wallClock = "HH:MM:SS" as the game shows it
+   *   (null until the timezone has been seen on a scanned page)
+   */
+  TravianServerClock.getServerTime = async function(serverKey) {
+    var clocks = await _load();
+    var entry = clocks[serverKey] || { offsetMs: 0, rttMs: null, measuredAt: null, tzOffsetMinutes: null };
+    var now = Date.now() + entry.offsetMs;
+    var wallClock = null;
+    if (entry.tzOffsetMinutes !== null && entry.tzOffsetMinutes !== undefined) {
+      wallClock = new Date(now + entry.tzOffsetMinutes * 60000).toISOString().slice(11, 19);
+    }
+    return {
+      serverKey: serverKey,
+      now: now,
+      offsetMs: entry.offsetMs,
+      rttMs: entry.rttMs,
+      measuredAt: entry.measuredAt,
+      tzOffsetMinutes: entry.tzOffsetMinutes,
+      wallClock: wallClock
+    };
+  };
+
+  /**
+   * Milliseconds to add to a local timestamp so that a local Date built
+   * from it reads the server's wall clock (offset + timezone difference).
+   * Used by schedules that run on server time. Synchronous: uses whatever
+   * was loaded by the last measurement or lookup (0 before that).
+   * @param {string} serverKey
+   * @param {number} [at=Date.now()]
+   * @returns {number}
+   */
+  TravianServerClock.wallShift = function(serverKey, at) {
+    var entry = _clocks && _clocks[serverKey];
+    if (!entry) return 0;
+    var shift = entry.offsetMs || 0;
+    if (entry.tzOffsetMinutes !== null && entry.tzOffsetMinutes !== undefined) {
+      var localTz = -new Date(at || Date.now()).getTimezoneOffset();
+      shift += (entry.tzOffsetMinutes - localTz) * 60000;
+    }
+    return shift;
+  };
+
+  /** Load stored measurements (so wallShift works after a restart). */
+  TravianServerClock.init = function() {
+    return _load();
+  };
+
+  root.TravianServerClock = TravianServerClock;
+})(typeof window !== 'undefined' ? window : self);
@@ -33,6 +33,9 @@
     // Debounce: don't fire the same event type more than once per 5 minutes
     this._lastEmitTime = {};
     this._DEBOUNCE_MS = 5 * 60 * 1000;
+
+    // Last seen construction queue length per village (for build:idle)
+    this._lastBuildCount = {};
   }
 
   /**
@@ -49,6 +52,7 @@
     this._checkIncomingAttacks(gameState);
     this._checkClaimableQuests(gameState);
     this._checkCropCrisis(gameState);
+    this._checkBuildIdle(gameState);
 
     // Always emit scan:complete so subscribers know fresh data arrived
     this._eventBus.emit(_getEvents().SCAN_COMPLETE || 'scan:complete', {
@@ -154,6 +158,29 @@
     Logger.log('WARN', '[StateAnalyzer] Crop crisis! Free crop: ' + freeCrop);
   };
 
+  // ── Build queue ran empty ──────────────────────────────────────
+
+  TravianStateAnalyzer.prototype._checkBuildIdle = function(gameState) {
+    // The construction list is only rendered on dorf1/dorf2 — elsewhere
+    // the scanner reports an empty queue that means nothing.
+    if (gameState.page !== 'resources' && gameState.page !== 'village') return;
+    var queue = gameState.constructionQueue;
+    if (!queue || typeof queue.count !== 'number') return;
+
+    var villageId = gameState.currentVillageId || '_';
+    var previous = this._lastBuildCount[villageId];
+    this._lastBuildCount[villageId] = queue.count;
+
+    // Only the transition busy → empty within the same village counts
+    if (!(previous > 0) || queue.count !== 0) return;
+    if (this._isDebounced('build_' + villageId)) return;
+
+    this._debounceEmit('build_' + villageId, _getEvents().BUILD_IDLE || 'build:idle', {
+      previousCount: previous,
+      villageId: gameState.currentVillageId || null
+    });
+  };
+
   // ── Helpers ────────────────────────────────────────────────────
 
   /**
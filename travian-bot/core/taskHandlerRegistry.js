@@ -90,6 +90,56 @@
     return Object.keys(this._handlers);
   };
 
+  /**
+   * Params each task type cannot run without. Types not listed take no
+   * required params (their handlers fall back to defaults).
+   */
+  TaskHandlerRegistry.REQUIRED_PARAMS = {
+    upgrade_resource: ['fieldId'],
+    upgrade_building: ['slot'],
+    train_troops:     ['troopType', 'count'],
+    build_new:        ['slot', 'gid'],
+    send_attack:      ['target'],
+    switch_village:   ['targetVillageId'],
+    navigate:         ['page']
+  };
+
+  /**
+   * Validate a manually submitted task spec before it is queued.
+   * @param {{type: string, params?: Object, priority?: number, villageId?: string|null}} spec
+   * @returns {string[]} - Error messages, empty if the spec is valid
+   */
+  TaskHandlerRegistry.prototype.validateSpec = function(spec) {
+    var errors = [];
+    if (!spec || typeof spec.type !== 'string' || !spec.type) {
+      return ['type: required'];
+    }
+    if (!this._handlers[spec.type]) {
+      errors.push('type: unknown task type "' + spec.type + '"');
+    }
+    var params = spec.params;
+    if (params != null && (typeof params !== 'object' || Array.isArray(params))) {
+      errors.push('params: must be an object');
+      params = null;
+    }
+    var required = TaskHandlerRegistry.REQUIRED_PARAMS[spec.type] || [];
+    for (var i = 0; i < required.length; i++) {
+      if (!params || params[required[i]] == null || params[required[i]] === '') {
+        errors.push('params.' + required[i] + ': required for ' + spec.type);
+      }
+    }
+    if (spec.priority != null) {
+      var p = spec.priority;
+      if (typeof p !== 'number' || Math.floor(p) !== p || p < 1 || p > 10) {
+        errors.push('priority: must be an integer from 1 to 10');
+      }
+    }
+    if (spec.villageId != null && typeof spec.villageId !== 'string' && typeof spec.villageId !== 'number') {
+      errors.push('villageId: must be a string or number');
+    }
+    return errors;
+  };
+
   // ── Static factory: build registry from TravianTaskHandlers ─────────────
 
   /**
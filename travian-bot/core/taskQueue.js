@@ -132,6 +132,54 @@ class TaskQueue {
     return true;
   }
 
+  /**
+   * Reorder the pending tasks. taskIds must list every pending task exactly
+   * once; they keep the (priority, createdAt) slots the pending tasks
+   * occupy now but are assigned to them in the given order, so running,
+   * paused and finished tasks are unaffected.
+   * @param {string[]} taskIds - All pending task IDs in desired execution order
+   * @returns {boolean} False (and nothing changes) if taskIds isn't the pending set
+   */
+  reorder(taskIds) {
+    if (!Array.isArray(taskIds)) return false;
+    const pending = this.queue.filter(t => t.status === 'pending');
+    if (taskIds.length !== pending.length) return false;
+    const tasks = [];
+    for (const id of taskIds) {
+      const task = pending.find(t => t.id === id);
+      if (!task || tasks.includes(task)) return false;
+      tasks.push(task);
+    }
+
+    const slots = tasks
+      .map(t => ({ priority: t.priority, createdAt: t.createdAt }))
+      .sort((a, b) => (a.priority - b.priority) || (a.createdAt - b.createdAt));
+
+    tasks.forEach((task, i) => {
+      task.priority = slots[i].priority;
+      task.createdAt = slots[i].createdAt;
+    });
+    this._dirtyAt = Date.now();
+    return true;
+  }
+
+  /**
+   * Pause or resume a single task. Paused tasks stay in the queue (and still
+   * block duplicates) but are skipped by getNext() until resumed.
+   * @param {string} taskId
+   * @param {boolean} paused
+   * @returns {boolean} True if the task was found in a pausable state
+   */
+  setPaused(taskId, paused) {
+    const task = this.queue.find(t => t.id === taskId);
+    if (!task) return false;
+    const from = paused ? 'pending' : 'paused';
+    if (task.status !== from) return false;
+    task.status = paused ? 'paused' : 'pending';
+    this._dirtyAt = Date.now();
+    return true;
+  }
+
   /**
    * Get the highest priority ready task and mark it as running.
    * A task is ready if it is pending and its scheduledFor time has passed (or is null).
@@ -198,7 +246,7 @@ class TaskQueue {
     if (!task) return false;
 
     // Only allow updating safe fields
-    const allowedFields = ['priority', 'params', 'status', 'scheduledFor', 'villageId', 'error', 'retries', 'maxRetries'];
+    const allowedFields = ['priority', 'params', 'status', 'scheduledFor', 'villageId', 'error', 'retries', 'maxRetries', 'reason'];
     let changed = false;
     for (const key of Object.keys(updates)) {
       if (allowedFields.includes(key)) {
@@ -421,13 +469,13 @@ class TaskQueue {
    * Useful to prevent duplicate task creation.
    * @param {string} type - Task type to check
    * @param {string|null} [villageId=null] - Village ID to check (null matches tasks with no village)
-   * @returns {boolean} True if a matching pending/running task exists
+   * @returns {boolean} True if a matching pending/paused/running task exists
    */
   hasTaskOfType(type, villageId = null) {
     return this.queue.some(t =>
       t.type === type &&
       t.villageId === villageId &&
-      (t.status === 'pending' || t.status === 'running')
+      (t.status === 'pending' || t.status === 'paused' || t.status === 'running')
     );
   }
 
@@ -436,12 +484,12 @@ class TaskQueue {
    * regardless of villageId. Prevents dedup mismatches when AI scoring path
    * queues with null but fallback path uses actual villageId (or vice versa).
    * @param {string} type - Task type to check
-   * @returns {boolean} True if ANY matching pending/running task exists
+   * @returns {boolean} True if ANY matching pending/paused/running task exists
    */
   hasAnyTaskOfType(type) {
     return this.queue.some(t =>
       t.type === type &&
-      (t.status === 'pending' || t.status === 'running')
+      (t.status === 'pending' || t.status === 'paused' || t.status === 'running')
     );
   }
 }
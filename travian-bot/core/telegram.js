@@ -0,0 +1,184 @@
+/**
+ * Telegram — Remote alerts and a small command set via the Telegram Bot API.
+ *
+ * Pushes critical events (attacks, captchas, bot stopped) to one chat and
+ * polls for commands from that same chat. Commands are parsed here but
+ * executed by the service worker, which routes them through the normal
+ * message handler so they behave exactly like popup actions.
+ *
+ * Settings are global (one bot/chat for all servers), stored under
+ * 'telegram_config':
+ *   { enabled, botToken, chatId, commandsEnabled, screenshots, lastUpdateId }
+ *
+ * /screenshot needs the optional <all_urls> host permission (captureVisibleTab
+ * has no user gesture to ride on here), so it is off until the user turns on
+ * `screenshots`; UIClient.saveTelegramConfig requests the permission then.
+ *
+ * Runs in service worker context. Exported via self.TravianTelegram.
+ */
+(function(root) {
+  'use strict';
+
+  var API_BASE = 'https://api.telegram.org/bot';
+  var STORAGE_KEY = 'telegram_config';
+  var MAX_MESSAGE_LENGTH = 4000; // Telegram limit is 4096
+
+  /** Commands accepted from the chat (anything else gets the help text) */
+  var COMMANDS = ['status', 'pause', 'resume', 'stop', 'screenshot', 'help'];
+
+  var HELP_TEXT = 'Commands:\n' +
+    '/status — all servers\n' +
+    '/pause [server|all] — pause bot(s)\n' +
+    '/resume [server|all] — resume bot(s)\n' +
+    '/stop [server|all] — stop bot(s)\n' +
+    '/screenshot [server] — capture the game tab';
+
+  var TravianTelegram = {};
+
+  TravianTelegram.COMMANDS = COMMANDS;
+  TravianTelegram.HELP_TEXT = HELP_TEXT;
+
+  // ── Config ──────────────────────────────────────────────────────────
+
+  /**
+   * @returns {Promise<{enabled: boolean, botToken: string|null, chatId: string|null,
+   *   commandsEnabled: boolean, screenshots: boolean, lastUpdateId: number}>}
+   */
+  TravianTelegram.getConfig = async function() {
+    var stored = await root.TravianStorage.get(STORAGE_KEY, {});
+    return {
+      enabled: !!stored.enabled,
+      botToken: stored.botToken || null,
+      chatId: stored.chatId != null ? String(stored.chatId) : null,
+      commandsEnabled: !!stored.commandsEnabled,
+      screenshots: !!stored.screenshots,
+      lastUpdateId: stored.lastUpdateId || 0
+    };
+  };
+
+  /**
+   * Merge and save Telegram settings. A missing botToken, or the masked one
+   * redact() handed to the UI, keeps the stored token.
+   * @param {Object} patch - Any of enabled, botToken, chatId, commandsEnabled, screenshots
+   * @returns {Promise<Object>} Saved config
+   */
+  TravianTelegram.saveConfig = async function(patch) {
+    var current = await TravianTelegram.getConfig();
+    var next = Object.assign({}, current);
+    ['enabled', 'commandsEnabled', 'screenshots'].forEach(function(k) {
+      if (patch[k] !== undefined) next[k] = !!patch[k];
+    });
+    var masked = current.botToken && patch.botToken === _mask(current.botToken);
+    if (patch.botToken !== undefined && !masked) next.botToken = patch.botToken ? String(patch.botToken).trim() : null;
+    if (patch.chatId !== undefined) next.chatId = patch.chatId != null && patch.chatId !== '' ? String(patch.chatId).trim() : null;
+    if (patch.lastUpdateId !== undefined) next.lastUpdateId = patch.lastUpdateId;
+    await root.TravianStorage.set(STORAGE_KEY, next);
+    return next;
+  };
+
+  /**
+   * Config safe to hand to the UI (token masked).
+   * @param {Object} cfg
+   * @returns {Object}
+   */
+  TravianTelegram.redact = function(cfg) {
+    var out = Object.assign({}, cfg);
+    if (out.botToken) out.botToken = _mask(out.botToken);
+    delete out.lastUpdateId;
+    return out;
+  };
+
+  function _mask(token) {
+    return token.slice(0, 4) + '…' + token.slice(-4);
+  }
+
+  /** @returns {boolean} True if cfg has everything needed to send */
+  TravianTelegram.isReady = function(cfg) {
+    return !!(cfg && cfg.enabled && cfg.botToken && cfg.chatId);
+  };
+
+  // ── API calls ───────────────────────────────────────────────────────
+
+  async function _call(cfg, method, body) {
+    var resp = await fetch(API_BASE + cfg.botToken + '/' + method, body instanceof FormData
+      ? { method: 'POST', body: body }
+      : { method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(body || {}) });
+    var json = await resp.json().catch(function() { return null; });
+    if (!json || !json.ok) {
+      throw new Error('Telegram ' + method + ' failed: ' + ((json && json.description) || ('HTTP ' + resp.status)));
+    }
+    return json.result;
+  }
+
+  /**
+   * Send a text message to the configured chat.
+   * @param {Object} cfg
+   * @param {string} text
+   * @returns {Promise<void>}
+   */
+  TravianTelegram.send = async function(cfg, text) {
+    text = String(text);
+    if (text.length > MAX_MESSAGE_LENGTH) text = text.slice(0, MAX_MESSAGE_LENGTH) + '…';
+    await _call(cfg, 'sendMessage', { chat_id: cfg.chatId, text: text, disable_web_page_preview: true });
+  };
+
+  /**
+   * Send a PNG screenshot (data URL from chrome.tabs.captureVisibleTab).
+   * @param {Object} cfg
+   * @param {string} dataUrl
+   * @param {string} [caption]
+   * @returns {Promise<void>}
+   */
+  TravianTelegram.sendPhoto = async function(cfg, dataUrl, caption) {
+    var blob = await (await fetch(dataUrl)).blob();
+    var form = new FormData();
+    form.append('chat_id', cfg.chatId);
+    form.append('photo', blob, 'screenshot.png');
+    if (caption) form.append('caption', caption);
+    await _call(cfg, 'sendPhoto', form);
+  };
+
+  /**
+   * Fetch pending commands from the configured chat and advance the offset.
+   * Messages from any other chat are dropped.
+   * @param {Object} cfg
+   * @returns {Promise<Array<{command: string, args: string[]}>>}
+   */
+  TravianTelegram.pollCommands = async function(cfg) {
+    var updates = await _call(cfg, 'getUpdates', {
+      offset: cfg.lastUpdateId + 1,
+      timeout: 0,
+      allowed_updates: ['message']
+    });
+    if (!updates || updates.length === 0) return [];
+
+    var commands = [];
+    var lastId = cfg.lastUpdateId;
+    for (var i = 0; i < updates.length; i++) {
+      var u = updates[i];
+      if (u.update_id > lastId) lastId = u.update_id;
+      var msg = u.message;
+      if (!msg || !msg.chat || String(msg.chat.id) !== cfg.chatId) continue;
+      var parsed = TravianTelegram.parseCommand(msg.text);
+      if (parsed) commands.push(parsed);
+    }
+    await TravianTelegram.saveConfig({ lastUpdateId: lastId });
+    cfg.lastUpdateId = lastId;
+    return commands;
+  };
+
+  /**
+   * Parse "/pause@MyBot ts5" into { command: 'pause', args: ['ts5'] }.
+   * @param {string} text
+   * @returns {{command: string, args: string[]}|null}
+   */
+  TravianTelegram.parseCommand = function(text) {
+    if (typeof text !== 'string' || text.charAt(0) !== '/') return null;
+    var parts = text.trim().split(/\s+/);
+    var command = parts[0].slice(1).split('@')[0].toLowerCase();
+    if (COMMANDS.indexOf(command) === -1) command = 'help';
+    return { command: command, args: parts.slice(1) };
+  };
+
+  root.TravianTelegram = TravianTelegram;
+})(typeof window !== 'undefined' ? window : self);
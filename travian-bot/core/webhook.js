@@ -0,0 +1,259 @@
+/**
+ * Webhook — POSTs bot events as JSON to user-configured URLs.
+ *
+ * Generic sink for tools like n8n, Zapier or Home Assistant. Each endpoint
+ * picks the events it wants from EVENTS ('alert' is the notifier alert,
+ * the rest are EventBus events forwarded by InstanceManager). Payload:
+ *   { id, event, serverKey, timestamp, data }
+ *
+ * Request headers:
+ *   X-Travian-Event, X-Travian-Delivery (= id), X-Travian-Timestamp (ms)
+ *   X-Travian-Signature: sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">
+ *     — only when the endpoint has a secret
+ *
+ * Network errors, 429 and 5xx are retried with exponential backoff
+ * (1s, 4s, 16s); other 4xx responses fail immediately.
+ *
+ * The extension has no blanket host permission, so the UI must obtain an
+ * optional host permission for each endpoint origin before saving it
+ * (see UIClient.saveWebhookConfig).
+ *
+ * Settings are global, stored under 'webhook_config':
+ *   { enabled, endpoints: [{ url, secret, events: [...] }] }
+ *
+ * Runs in service worker context. Exported via self.TravianWebhook.
+ */
+(function(root) {
+  'use strict';
+
+  var STORAGE_KEY = 'webhook_config';
+  var MAX_ENDPOINTS = 5;
+  var MAX_ATTEMPTS = 4;
+  var BACKOFF_BASE_MS = 1000;
+  var URL_PATTERN = /^https?:\/\/[^\s/]+/;
+
+  /** Events an endpoint can subscribe to */
+  var EVENTS = [
+    'alert',
+    'attack:incoming',
+    'crop:crisis',
+    'session:lost',
+    'bot:emergency',
+    'bot:captcha',
+    'build:idle',
+    'task:completed',
+    'task:failed',
+    'overflow:imminent',
+    'quest:claimable',
+    'schedule:fired',
+    'clock:drift',
+    'system:resumed',
+    'network:offline',
+    'network:online'
+  ];
+
+  var _cache = null;   // last loaded/saved config (storage reads on every event add up)
+  var _stats = { delivered: 0, failed: 0, retried: 0, lastError: null, lastErrorAt: null };
+
+  var TravianWebhook = {};
+
+  TravianWebhook.EVENTS = EVENTS;
+
+  // ── Config ──────────────────────────────────────────────────────────
+
+  /**
+   * @returns {Promise<{enabled: boolean, endpoints: Array<{url: string, secret: string|null, events: string[]}>}>}
+   */
+  TravianWebhook.getConfig = async function() {
+    if (_cache) return _cache;
+    var stored = await root.TravianStorage.get(STORAGE_KEY, {});
+    var endpoints = Array.isArray(stored.endpoints) ? stored.endpoints : [];
+    // Single-URL config from before endpoints existed
+    if (endpoints.length === 0 && stored.url) {
+      endpoints = [{ url: stored.url, secret: null, events: ['alert'] }];
+    }
+    _cache = { enabled: !!stored.enabled, endpoints: endpoints };
+    return _cache;
+  };
+
+  /**
+   * Merge and save webhook settings. An endpoint saved without a `secret`
+   * field keeps the secret it already had for the same URL, so redacted
+   * configs can be round-tripped through the UI.
+   * @param {{ enabled?: boolean, endpoints?: Array<Object> }} patch
+   * @returns {Promise<Object>} Saved config
+   * @throws {Error} On invalid URLs, unknown events or too many endpoints
+   */
+  TravianWebhook.saveConfig = async function(patch) {
+    var current = await TravianWebhook.getConfig();
+    var next = { enabled: current.enabled, endpoints: current.endpoints };
+    if (patch.enabled !== undefined) next.enabled = !!patch.enabled;
+
+    if (patch.endpoints !== undefined) {
+      if (!Array.isArray(patch.endpoints)) throw new Error('endpoints must be an array');
+      if (patch.endpoints.length > MAX_ENDPOINTS) throw new Error('At most ' + MAX_ENDPOINTS + ' endpoints');
+      next.endpoints = patch.endpoints.map(function(ep, i) {
+        var url = ep && ep.url ? String(ep.url).trim() : '';
+        if (!URL_PATTERN.test(url)) throw new Error('Endpoint ' + (i + 1) + ': URL must start with http:// or https://');
+        var events = Array.isArray(ep.events) ? ep.events : ['alert'];
+        var unknown = events.filter(function(e) { return EVENTS.indexOf(e) === -1; });
+        if (unknown.length > 0) throw new Error('Endpoint ' + (i + 1) + ': unknown event ' + unknown.join(', '));
+
+        var secret;
+        if (ep.secret === undefined) {
+          var prev = current.endpoints.find(function(p) { return p.url === url; });
+          secret = prev ? prev.secret : null;
+        } else {
+          secret = ep.secret ? String(ep.secret) : null;
+        }
+        return { url: url, secret: secret, events: events };
+      });
+    }
+
+    await root.TravianStorage.set(STORAGE_KEY, next);
+    _cache = next;
+    return next;
+  };
+
+  /** Config safe to hand to the UI (secrets replaced by hasSecret). */
+  TravianWebhook.redact = function(cfg) {
+    return {
+      enabled: cfg.enabled,
+      endpoints: cfg.endpoints.map(function(ep) {
+        return { url: ep.url, hasSecret: !!ep.secret, events: ep.events.slice() };
+      })
+    };
+  };
+
+  /** @returns {{ delivered: number, failed: number, retried: number, lastError: string|null, lastErrorAt: number|null }} */
+  TravianWebhook.getStats = function() {
+    return Object.assign({}, _stats);
+  };
+
+  // ── Delivery ────────────────────────────────────────────────────────
+
+  async function _sign(secret, text) {
+    var enc = new TextEncoder();
+    var key = await crypto.subtle.importKey('raw', enc.encode(secret),
+      { name: 'HMAC', hash: 'SHA-256' }, false, ['sign']);
+    var sig = new Uint8Array(await crypto.subtle.sign('HMAC', key, enc.encode(text)));
+    return Array.from(sig, function(b) { return b.toString(16).padStart(2, '0'); }).join('');
+  }
+
+  function _retryable(status) {
+    return status === 429 || status >= 500;
+  }
+
+  /**
+   * POST one payload to one endpoint, retrying transient failures.
+   * @param {{url: string, secret: string|null}} endpoint
+   * @param {Object} payload
+   * @param {number} [maxAttempts]
+   * @returns {Promise<void>}
+   * @throws {Error} After the last failed attempt
+   */
+  async function _deliver(endpoint, payload, maxAttempts) {
+    var body = JSON.stringify(payload);
+    var ts = String(Date.now());
+    var headers = {
+      'Content-Type': 'application/json',
+      'X-Travian-Event': payload.event,
+      'X-Travian-Delivery': payload.id,
+      'X-Travian-Timestamp': ts
+    };
+    if (endpoint.secret) {
+      headers['X-Travian-Signature'] = 'sha256=' + await _sign(endpoint.secret, ts + '.' + body);
+    }
+
+    var attempts = maxAttempts || MAX_ATTEMPTS;
+    for (var attempt = 1; ; attempt++) {
+      var error;
+      try {
+        var resp = await fetch(endpoint.url, { method: 'POST', headers: headers, body: body });
+        if (resp.ok) {
+          _stats.delivered++;
+          return;
+        }
+        error = new Error('HTTP ' + resp.status);
+        if (!_retryable(resp.status)) attempt = attempts;
+      } catch (err) {
+        error = err;
+      }
+      if (attempt >= attempts) {
+        _stats.failed++;
+        _stats.lastError = endpoint.url + ': ' + error.message;
+        _stats.lastErrorAt = Date.now();
+        throw error;
+      }
+      _stats.retried++;
+      await root.TravianDelay.wait(BACKOFF_BASE_MS * Math.pow(4, attempt - 1));
+    }
+  }
+
+  function _payload(event, serverKey, data) {
+    return {
+      id: (crypto.randomUUID ? crypto.randomUUID() : Date.now().toString(36) + Math.random().toString(36).slice(2)),
+      event: event,
+      serverKey: serverKey || null,
+      timestamp: new Date().toISOString(),
+      data: data || {}
+    };
+  }
+
+  /**
+   * Deliver an event to every endpoint subscribed to it. Failures are
+   * logged, never thrown.
+   * @param {Object} cfg - From getConfig()
+   * @param {string} event - One of EVENTS
+   * @param {string|null} serverKey
+   * @param {Object} data
+   * @returns {Promise<boolean>} False if no endpoint takes this event
+   */
+  TravianWebhook.emit = async function(cfg, event, serverKey, data) {
+    if (!cfg.enabled) return false;
+    var targets = cfg.endpoints.filter(function(ep) { return ep.events.indexOf(event) !== -1; });
+    if (targets.length === 0) return false;
+    var payload = _payload(event, serverKey, data);
+    await Promise.all(targets.map(function(ep) {
+      return _deliver(ep, payload).catch(function(err) {
+        console.warn('[Webhook] ' + event + ' → ' + ep.url + ' failed: ' + err.message);
+      });
+    }));
+    return true;
+  };
+
+  /**
+   * Post a notifier alert to endpoints subscribed to 'alert'.
+   * @param {Object} cfg
+   * @param {{ serverKey: string, kind: string, severity: string, title: string, message: string }} alert
+   * @returns {Promise<boolean>}
+   */
+  TravianWebhook.sendAlert = function(cfg, alert) {
+    return TravianWebhook.emit(cfg, 'alert', alert.serverKey, {
+      kind: alert.kind,
+      severity: alert.severity,
+      title: alert.title,
+      message: alert.message
+    });
+  };
+
+  /**
+   * Post a test payload to every endpoint, once each (no retries, ignores
+   * the enabled flag and event selection).
+   * @param {Object} cfg
+   * @returns {Promise<boolean>}
+   * @throws {Error} Listing the endpoints that failed
+   */
+  TravianWebhook.sendTest = async function(cfg) {
+    if (cfg.endpoints.length === 0) throw new Error('No webhook endpoints configured');
+    var payload = _payload('test', null, { message: 'Travian Assistant connected.' });
+    var failures = [];
+    await Promise.all(cfg.endpoints.map(function(ep) {
+      return _deliver(ep, payload, 1).catch(function(err) { failures.push(ep.url + ': ' + err.message); });
+    }));
+    if (failures.length > 0) throw new Error(failures.join('; '));
+    return true;
+  };
+
+  root.TravianWebhook = TravianWebhook;
+})(typeof window !== 'undefined' ? window : self);
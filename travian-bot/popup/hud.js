@@ -0,0 +1,46 @@
+/**
+ * Travian Bot - HUD
+ *
+ * Mini status window (opened with the "toggle-hud" shortcut or
+ * TOGGLE_HUD): one line per server with a status light, the countdown to
+ * the next action and incoming attacks. Only polls GET_OVERVIEW, so it
+ * stays cheap to leave open next to the game.
+ */
+
+var HUD_POLL_MS = 3000;
+
+var hudList = document.getElementById('hudList');
+var hudRows = [];
+
+function renderHud() {
+  if (hudRows.length === 0) {
+    hudList.innerHTML = '<span class="hud-empty">No servers</span>';
+    return;
+  }
+  var now = Date.now();
+  hudList.innerHTML = hudRows.map(function (r) {
+    var next = r.manualMode ? '✋ manual'
+      : r.state === 'running' && r.nextActionTime ? formatCountdown(r.nextActionTime - now)
+      : r.state;
+    return '<div class="hud-row" title="' + escapeHtml(r.lastError || r.serverKey) + '">' +
+      '<span class="hud-dot ' + escapeHtml(r.manualMode ? 'manual' : r.state) + '"></span>' +
+      '<span class="hud-label">' + escapeHtml(r.label) + '</span>' +
+      (r.incomingAttacks ? '<span class="hud-attack">⚔ ' + r.incomingAttacks + '</span>' : '') +
+      '<span class="hud-next">' + escapeHtml(next) + '</span>' +
+      '</div>';
+  }).join('');
+}
+
+async function pollHud() {
+  var resp = await UIClient.getOverview();
+  if (resp && resp.success) {
+    hudRows = resp.data.servers;
+    var attacks = hudRows.reduce(function (sum, r) { return sum + (r.incomingAttacks || 0); }, 0);
+    document.title = attacks > 0 ? '⚔ ' + attacks + ' incoming' : 'Travian HUD';
+  }
+  renderHud();
+}
+
+pollHud();
+setInterval(pollHud, HUD_POLL_MS);
+setInterval(renderHud, 1000); // tick the countdowns between polls
@@ -0,0 +1,131 @@
+/**
+ * Travian Bot - Logs window
+ *
+ * Detached log viewer (OPEN_LOGS_WINDOW) for a second monitor. By default
+ * it follows the live log tail under the server / level / text filter;
+ * pressing Enter in the query box runs a full-text search of the persisted
+ * history instead (clearing the box returns to the live tail).
+ */
+
+var LOGS_MAX_LINES = 2000;
+
+var logsDom = {
+  server: document.getElementById('logsServer'),
+  level: document.getElementById('logsLevel'),
+  query: document.getElementById('logsQuery'),
+  status: document.getElementById('logsStatus'),
+  list: document.getElementById('logsList')
+};
+
+var logsTail = null;
+var logsSearching = false;
+
+// Preselect a server passed as ?server=<key>
+var logsInitialServer = new URLSearchParams(location.search).get('server') || '';
+
+function logsFilter() {
+  return {
+    serverKey: logsDom.server.value || undefined,
+    level: logsDom.level.value || undefined,
+    text: logsDom.query.value.trim() || undefined
+  };
+}
+
+/**
+ * @param {{ level: string, serverKey?: string }} entry
+ * @param {number|string} time - Epoch ms or ISO string
+ * @param {string} messageHtml - Already escaped
+ */
+function logLine(entry, time, messageHtml) {
+  var div = document.createElement('div');
+  div.className = 'log-line ' + entry.level;
+  div.innerHTML = '<span class="log-time">' + escapeHtml(new Date(time).toLocaleTimeString()) + '</span> ' +
+    (entry.serverKey ? '<span class="log-server">[' + escapeHtml(formatServerLabel(entry.serverKey)) + ']</span> ' : '') +
+    '<span class="log-msg">' + messageHtml + '</span>';
+  return div;
+}
+
+function appendTailEntry(entry) {
+  var list = logsDom.list;
+  var atBottom = list.scrollTop + list.clientHeight >= list.scrollHeight - 20;
+  list.appendChild(logLine(entry, entry.timestamp, escapeHtml(entry.message)));
+  while (list.childElementCount > LOGS_MAX_LINES) list.removeChild(list.firstChild);
+  if (atBottom) list.scrollTop = list.scrollHeight;
+}
+
+function startTail() {
+  logsSearching = false;
+  logsDom.status.textContent = 'live';
+  var filter = logsFilter();
+  if (logsTail) {
+    logsTail.setFilter(filter);
+    return;
+  }
+  logsTail = UIClient.tailLogs(filter, {
+    backfill: 500,
+    onBackfill: function (entries) {
+      logsDom.list.innerHTML = '';
+      entries.forEach(appendTailEntry);
+    },
+    onEntry: function (entry) {
+      if (!logsSearching) appendTailEntry(entry);
+    },
+    onClose: function () {
+      // Service worker restarted: reconnect
+      logsTail = null;
+      if (!logsSearching) setTimeout(startTail, 1000);
+    }
+  });
+}
+
+async function runSearch() {
+  var query = logsDom.query.value.trim();
+  if (!query) {
+    startTail();
+    return;
+  }
+  logsSearching = true;
+  logsDom.status.textContent = 'searching…';
+  var filter = logsFilter();
+  var resp = await UIClient.searchLogs(query, { serverKey: filter.serverKey, level: filter.level }, 200);
+  if (!logsSearching) return;
+  if (!resp || !resp.success) {
+    logsDom.status.textContent = 'search failed: ' + ((resp && resp.error) || 'no response');
+    return;
+  }
+  logsDom.list.innerHTML = '';
+  resp.data.matches.forEach(function (m) {
+    logsDom.list.appendChild(logLine(m, m.ts, m.highlight));
+  });
+  logsDom.list.scrollTop = 0;
+  logsDom.status.textContent = resp.data.total + ' match(es)' + (resp.data.truncated ? ' (showing best ' + resp.data.matches.length + ')' : '');
+}
+
+async function loadServers() {
+  var resp = await UIClient.getOverview();
+  var rows = (resp && resp.success) ? resp.data.servers : [];
+  rows.forEach(function (r) {
+    var opt = document.createElement('option');
+    opt.value = r.serverKey;
+    opt.textContent = r.label;
+    logsDom.server.appendChild(opt);
+  });
+  if (logsInitialServer) logsDom.server.value = logsInitialServer;
+}
+
+function refresh() {
+  if (logsSearching) runSearch();
+  else startTail();
+}
+
+logsDom.server.addEventListener('change', refresh);
+logsDom.level.addEventListener('change', refresh);
+logsDom.query.addEventListener('keydown', function (e) {
+  if (e.key === 'Enter') runSearch();
+});
+logsDom.query.addEventListener('input', function () {
+  // Typing narrows the live tail; an emptied box leaves search mode
+  if (!logsSearching || !logsDom.query.value.trim()) startTail();
+});
+
+loadServers().then(startTail);
@@ -271,6 +271,8 @@ let refreshInterval = null;
 let currentServerKey = null; // Set on popup open from active tab URL
 let lastTargetRefreshTs = 0; // Throttle upgrade targets refresh (ms)
 let configTabDirty = false;   // Dirty-state flag: suppress auto-refresh while user edits config
+let captchaBlocked = null;    // { reason, since } while the bot waits for a captcha to be solved
+let manualMode = null;        // { since } while the user has taken over the game tab
 
 // GID_NAMES is provided by shared/constants.js (loaded before popup.js)
 
@@ -639,6 +641,12 @@ function updateStatus(status) {
   if (state === 'stopped' && status.emergencyReason) {
     dom.statusText.textContent = 'Emergency: ' + status.emergencyReason;
     dom.statusText.title = status.emergencyReason; // full text on hover
+  } else if (manualMode) {
+    dom.statusText.textContent = '✋ Manual control — bot paused';
+    dom.statusText.title = 'Since ' + new Date(manualMode.since).toLocaleTimeString();
+  } else if (state === 'paused' && captchaBlocked) {
+    dom.statusText.textContent = 'Captcha — solve it, then Resume';
+    dom.statusText.title = captchaBlocked.reason;
   } else if (state === 'running' && status.botState && fsmLabels[status.botState]) {
     var lockSuffix = status.cycleLock ? ' [' + status.cycleLock + ']' : '';
     dom.statusText.textContent = 'Running: ' + fsmLabels[status.botState] + lockSuffix;
@@ -842,7 +850,7 @@ function updateQueue(tasks) {
     var name = task.name || task.type || 'Unknown';
     var village = task.village ? ' @ ' + task.village : '';
     var retries = task.retries ? ' (retry ' + task.retries + ')' : '';
-    var status = task.status === 'running' ? ' ▶' : '';
+    var status = task.status === 'running' ? ' ▶' : (task.status === 'paused' ? ' ⏸' : '');
 
     var label = document.createElement('span');
     label.className = 'queue-item-label';
@@ -850,6 +858,22 @@ function updateQueue(tasks) {
 
     entry.appendChild(label);
 
+    // Pause/resume toggle (waiting tasks only)
+    if ((task.status === 'pending' || task.status === 'paused') && task.id) {
+      var pauseBtn = document.createElement('button');
+      pauseBtn.className = 'btn-icon btn-queue-remove';
+      pauseBtn.title = task.status === 'paused' ? 'Resume task' : 'Pause task';
+      pauseBtn.textContent = task.status === 'paused' ? '\u25B6' : '\u23F8';
+      pauseBtn.dataset.taskId = task.id;
+      pauseBtn.dataset.paused = task.status === 'paused' ? '1' : '';
+      pauseBtn.addEventListener('click', function() {
+        sendMessage({ type: 'PAUSE_TASK', taskId: this.dataset.taskId, data: { paused: !this.dataset.paused } })
+          .then(function() { refreshStatus(); })
+          .catch(console.warn);
+      });
+      entry.appendChild(pauseBtn);
+    }
+
     // Remove button (not for running tasks)
     if (task.status !== 'running' && task.id) {
       var removeBtn = document.createElement('button');
@@ -2660,7 +2684,13 @@ function saveAllConfig() {
   // Previously also wrote directly via chrome.storage.local.set which could race with
   // the service worker's atomicMerge on the same key.
   sendMessage({ type: 'SAVE_CONFIG', config })
-    .then(() => {
+    .then((res) => {
+      if (res && res.success === false) {
+        var fieldErrors = (res.data && res.data.errors) || [];
+        throw new Error(res.error + (fieldErrors.length
+          ? ': ' + fieldErrors.map(function(e) { return e.path + ' ' + e.message; }).join('; ')
+          : ''));
+      }
       configTabDirty = false;
       dom.btnSaveAll.textContent = '✓ Saved!';
       dom.btnSaveAll.classList.add('btn-saved');
@@ -2711,72 +2741,7 @@ function refreshStatus() {
   sendMessage({ type: 'GET_STATUS' })
     .then((response) => {
       if (response && response.success && response.data) {
-        // Map botEngine.getStatus() format to UI format
-        const s = response.data;
-        let state = 'stopped';
-        if (s.running && !s.paused) state = 'running';
-        else if (s.running && s.paused) state = 'paused';
-        else if (s.emergencyStopped) state = 'stopped';
-
-        updateStatus({
-          state: state,
-          botState: s.botState || null, // FSM granular state
-          cycleLock: s.cycleLock || null, // Lock phase detail
-          emergencyReason: s.emergencyReason || null, // SAF-5 FIX
-          stats: {
-            completed: s.stats ? s.stats.tasksCompleted : 0,
-            failed: s.stats ? s.stats.tasksFailed : 0,
-            startTime: s.stats ? s.stats.startTime : null,
-            actionsPerHour: s.actionsThisHour || 0,
-          },
-          currentTask: s.taskQueue && s.taskQueue.tasks
-            ? s.taskQueue.tasks.find(t => t.status === 'running')
-            : null,
-          villages: s.gameState ? s.gameState.villages : null,
-        });
-
-        // Update queue display
-        if (s.taskQueue && s.taskQueue.tasks) {
-          updateQueue(s.taskQueue.tasks);
-        }
-
-        // Auto-populate/refresh upgrade list from gameState (throttled to 10s)
-        // Skip when user is actively editing config to prevent wiping their edits
-        if (!configTabDirty && s.gameState && s.gameState.resourceFields && s.gameState.resourceFields.length > 0) {
-          var now = Date.now();
-          if (now - lastTargetRefreshTs > 10000) {
-            lastTargetRefreshTs = now;
-            applyScannedState(s.gameState);
-          }
-        }
-
-        // --- Dashboard game-state displays ---
-        if (s.gameState) {
-          updateResources(s.gameState);
-          updateBuildQueue(s.gameState.constructionQueue);
-          updateTroopSummary(s.gameState.troops);
-        }
-        updateNextAction(s.nextActionTime);
-        if (s.stats) {
-          updateFarmStats(s.stats, s.farmCycle);
-        }
-
-        // AI + Trapper + Quest + Attack status
-        if (s.gameState) {
-          updateTrapperStatus(s.gameState.trapperInfo || null);
-          updateQuestDisplay(s.gameState.quests || null);
-          updateAttackAlert(s.gameState.incomingAttacks || []);
-        }
-        updateAIReason(s.lastAIAction || null);
-
-        // --- Diagnostics tab (lazy: only render when active) ---
-        var diagPanel = document.getElementById('panelDiag');
-        if (diagPanel && diagPanel.classList.contains('active')) {
-          renderDiagnostics(s);
-          renderCooldowns(s.cooldowns || null);
-          renderPrereqs(s.prereqResolutions || []);
-          renderDebugJson(s);
-        }
+        applyStatus(response.data);
       }
     })
     .catch(() => {
@@ -2784,6 +2749,82 @@ function refreshStatus() {
     });
 }
 
+/**
+ * Render a botEngine.getStatus() payload into the popup.
+ * Shared by refreshStatus() and the batched fullRefresh().
+ * @param {object} s - Status payload
+ */
+function applyStatus(s) {
+  captchaBlocked = s.captchaBlocked || null;
+  manualMode = s.manualMode || null;
+
+  // Map botEngine.getStatus() format to UI format
+  let state = 'stopped';
+  if (s.running && !s.paused) state = 'running';
+  else if (s.running && s.paused) state = 'paused';
+  else if (s.emergencyStopped) state = 'stopped';
+
+  updateStatus({
+    state: state,
+    botState: s.botState || null, // FSM granular state
+    cycleLock: s.cycleLock || null, // Lock phase detail
+    emergencyReason: s.emergencyReason || null, // SAF-5 FIX
+    stats: {
+      completed: s.stats ? s.stats.tasksCompleted : 0,
+      failed: s.stats ? s.stats.tasksFailed : 0,
+      startTime: s.stats ? s.stats.startTime : null,
+      actionsPerHour: s.actionsThisHour || 0,
+    },
+    currentTask: s.taskQueue && s.taskQueue.tasks
+      ? s.taskQueue.tasks.find(t => t.status === 'running')
+      : null,
+    villages: s.gameState ? s.gameState.villages : null,
+  });
+
+  // Update queue display
+  if (s.taskQueue && s.taskQueue.tasks) {
+    updateQueue(s.taskQueue.tasks);
+  }
+
+  // Auto-populate/refresh upgrade list from gameState (throttled to 10s)
+  // Skip when user is actively editing config to prevent wiping their edits
+  if (!configTabDirty && s.gameState && s.gameState.resourceFields && s.gameState.resourceFields.length > 0) {
+    var now = Date.now();
+    if (now - lastTargetRefreshTs > 10000) {
+      lastTargetRefreshTs = now;
+      applyScannedState(s.gameState);
+    }
+  }
+
+  // --- Dashboard game-state displays ---
+  if (s.gameState) {
+    updateResources(s.gameState);
+    updateBuildQueue(s.gameState.constructionQueue);
+    updateTroopSummary(s.gameState.troops);
+  }
+  updateNextAction(s.nextActionTime);
+  if (s.stats) {
+    updateFarmStats(s.stats, s.farmCycle);
+  }
+
+  // AI + Trapper + Quest + Attack status
+  if (s.gameState) {
+    updateTrapperStatus(s.gameState.trapperInfo || null);
+    updateQuestDisplay(s.gameState.quests || null);
+    updateAttackAlert(s.gameState.incomingAttacks || []);
+  }
+  updateAIReason(s.lastAIAction || null);
+
+  // --- Diagnostics tab (lazy: only render when active) ---
+  var diagPanel = document.getElementById('panelDiag');
+  if (diagPanel && diagPanel.classList.contains('active')) {
+    renderDiagnostics(s);
+    renderCooldowns(s.cooldowns || null);
+    renderPrereqs(s.prereqResolutions || []);
+    renderDebugJson(s);
+  }
+}
+
 /**
  * Fetch logs from background and update the viewer.
  */
@@ -3089,13 +3130,25 @@ function escapeHtml(str) {
 }
 
 /**
- * Full refresh: status, logs, queue, and strategy.
+ * Full refresh: status (incl. queue), logs, and strategy in one GET_DASHBOARD
+ * round-trip. Strategy is only requested when its 30s throttle has elapsed.
  */
 function fullRefresh() {
-  refreshStatus();
-  refreshLogs();
-  refreshQueue();
-  refreshStrategy();
+  var now = Date.now();
+  var wantStrategy = now - _lastStrategyRefreshTs >= 30000;
+  if (wantStrategy) _lastStrategyRefreshTs = now;
+
+  sendMessage({ type: 'GET_DASHBOARD', data: { includeStrategy: wantStrategy } })
+    .then(function (response) {
+      if (!response || !response.success || !response.data) return;
+      var d = response.data;
+      if (d.status) applyStatus(d.status);
+      if (d.logs) updateLogs(d.logs);
+      if (d.strategy) renderStrategyDashboard(d.strategy);
+    })
+    .catch(function () {
+      stopRefreshInterval();
+    });
 }
 
 /**
@@ -3286,6 +3339,24 @@ function bindEvents() {
   });
 
   dom.btnPause.addEventListener('click', () => {
+    if (manualMode) {
+      // Resume = hand control back to the bot
+      sendMessage({ type: 'SET_MANUAL_MODE', data: { enabled: false } })
+        .then(() => { manualMode = null; refreshStatus(); })
+        .catch(console.warn);
+      return;
+    }
+    if (captchaBlocked) {
+      // Resume only once the page is clear of the captcha
+      sendMessage({ type: 'RESUME_AFTER_CAPTCHA' })
+        .then((response) => {
+          if (response && !response.success) alert(response.error);
+          else captchaBlocked = null;
+          refreshStatus();
+        })
+        .catch(console.warn);
+      return;
+    }
     sendMessage({ type: 'PAUSE_BOT' })
       .then((response) => {
         if (response && response.data) updateStatus(response.data);
@@ -16,6 +16,14 @@ const UIClient = {
     return this._sendMessage({ type: 'GET_SERVERS' });
   },
 
+  /**
+   * Get a compact status row for every known server
+   * @returns {Promise<object>} { generatedAt, servers: [{ serverKey, state, queuePending, nextActionTime, lastError, ... }] }
+   */
+  async getOverview() {
+    return this._sendMessage({ type: 'GET_OVERVIEW' });
+  },
+
   /**
    * Get full bot status from background
    * @param {string} [serverKey] - Optional server key
@@ -25,6 +33,16 @@ const UIClient = {
     return this._sendMessage({ type: 'GET_STATUS', serverKey });
   },
 
+  /**
+   * Get status, logs and (optionally) strategy analysis in one round-trip
+   * @param {string} serverKey
+   * @param {boolean} [includeStrategy=false]
+   * @returns {Promise<object>} { status, logs, strategy? }
+   */
+  async getDashboard(serverKey, includeStrategy = false) {
+    return this._sendMessage({ type: 'GET_DASHBOARD', serverKey, data: { includeStrategy } });
+  },
+
   /**
    * Start the bot
    * @param {string} serverKey - The server key to start
@@ -49,6 +67,34 @@ const UIClient = {
     return this._sendMessage({ type: 'PAUSE_BOT', serverKey });
   },
 
+  /**
+   * Take over a server by hand (bot paused, game tab brought up) or hand it back
+   * @param {string} serverKey
+   * @param {boolean} enabled
+   * @returns {Promise<object>} data: { manualMode: { since }|null }
+   */
+  async setManualMode(serverKey, enabled) {
+    return this._sendMessage({ type: 'SET_MANUAL_MODE', serverKey, data: { enabled } });
+  },
+
+  /**
+   * Resume a bot that a captcha paused, after checking the page is clear
+   * @param {string} serverKey
+   * @returns {Promise<object>} Fails if the captcha is still showing
+   */
+  async resumeAfterCaptcha(serverKey) {
+    return this._sendMessage({ type: 'RESUME_AFTER_CAPTCHA', serverKey });
+  },
+
+  /**
+   * Current action pacing versus the configured ceilings (per minute/hour/day)
+   * @param {string} serverKey
+   * @returns {Promise<object>} { riskLevel, throttled, throttledUntil, tiers: [{ tier, count, limit, percent, freeAt }], ... }
+   */
+  async getRiskReport(serverKey) {
+    return this._sendMessage({ type: 'GET_RISK_REPORT', serverKey });
+  },
+
   /**
    * Emergency Stop
    * @param {string} serverKey
@@ -58,6 +104,33 @@ const UIClient = {
     return this._sendMessage({ type: 'EMERGENCY_STOP', serverKey, data: { reason } });
   },
 
+  /**
+   * Start (or resume) bots on every server with an open tab
+   * @returns {Promise<object>} { results: { [serverKey]: { success, error? } } }
+   */
+  async startAll() {
+    return this._sendMessage({ type: 'START_ALL_BOTS' });
+  },
+
+  /**
+   * Stop every running bot
+   * @param {boolean} [graceful=false] - Let running actions finish first; resolves
+   *   right away with { stopping: [serverKey], busy: [serverKey mid-action] } and a
+   *   notification follows once every bot has stopped
+   * @returns {Promise<object>} { results: { [serverKey]: { success, error? } } }
+   */
+  async stopAll(graceful = false) {
+    return this._sendMessage({ type: 'STOP_ALL_BOTS', data: { graceful } });
+  },
+
+  /**
+   * Pause every running bot (does not resume paused ones)
+   * @returns {Promise<object>} { results: { [serverKey]: { success, error? } } }
+   */
+  async pauseAll() {
+    return this._sendMessage({ type: 'PAUSE_ALL_BOTS' });
+  },
+
   /**
    * Update bot configuration
    * @param {string} serverKey
@@ -67,6 +140,324 @@ const UIClient = {
     return this._sendMessage({ type: 'SAVE_CONFIG', serverKey, config: newConfig });
   },
 
+  /**
+   * List saved config profiles and the active one
+   * @param {string} serverKey
+   */
+  async listProfiles(serverKey) {
+    return this._sendMessage({ type: 'LIST_PROFILES', serverKey });
+  },
+
+  /**
+   * Save the current config as a named profile, or duplicate an existing one
+   * @param {string} serverKey
+   * @param {string} name
+   * @param {{ from?: string, overwrite?: boolean }} [options]
+   */
+  async createProfile(serverKey, name, options = {}) {
+    return this._sendMessage({ type: 'CREATE_PROFILE', serverKey, data: { name, from: options.from, overwrite: options.overwrite } });
+  },
+
+  /**
+   * Apply a saved profile to the server config
+   * @param {string} serverKey
+   * @param {string} name
+   */
+  async switchProfile(serverKey, name) {
+    return this._sendMessage({ type: 'SWITCH_PROFILE', serverKey, data: { name } });
+  },
+
+  /**
+   * Delete a saved profile
+   * @param {string} serverKey
+   * @param {string} name
+   */
+  async deleteProfile(serverKey, name) {
+    return this._sendMessage({ type: 'DELETE_PROFILE', serverKey, data: { name } });
+  },
+
+  /**
+   * List active attack alarms across all servers
+   */
+  async getAttackAlarms() {
+    return this._sendMessage({ type: 'GET_ATTACK_ALARMS' });
+  },
+
+  /**
+   * Stop the repeating attack alarm for a server
+   * @param {string} serverKey
+   */
+  async acknowledgeAlarm(serverKey) {
+    return this._sendMessage({ type: 'ACKNOWLEDGE_ALARM', serverKey });
+  },
+
+  /**
+   * Silence the attack alarm for a while
+   * @param {string} serverKey
+   * @param {number} [minutes] - Defaults to notificationConfig.snoozeMinutes
+   */
+  async snoozeAlarm(serverKey, minutes) {
+    return this._sendMessage({ type: 'SNOOZE_ALARM', serverKey, data: { minutes } });
+  },
+
+  /**
+   * Page through persisted alerts (attacks, captcha/emergency stops, lost
+   * sessions, crop crises), newest first. Unacknowledged ones drive the badge.
+   * @param {{ serverKey?: string, acknowledged?: boolean, severity?: 'critical'|'warning'|'info' }} [filter]
+   * @param {number} [offset=0]
+   * @param {number} [limit=50]
+   * @returns {Promise<object>} data: { entries: [{ id, ts, serverKey, kind, severity, title, message, acknowledged, ackAt, repeats, screenshotId? }], hasMore }
+   */
+  async getAlerts(filter, offset, limit) {
+    return this._sendMessage({ type: 'GET_ALERTS', filter: filter || {}, offset, limit });
+  },
+
+  /**
+   * Acknowledge one alert (an attack alert also stops the attack alarm)
+   * @param {number} alertId
+   */
+  async ackAlert(alertId) {
+    return this._sendMessage({ type: 'ACK_ALERT', alertId });
+  },
+
+  /**
+   * Acknowledge every open alert (call when the alerts panel is viewed)
+   * @param {string} [serverKey] - Only this server's alerts
+   * @returns {Promise<object>} data: { acknowledged }
+   */
+  async ackAllAlerts(serverKey) {
+    return this._sendMessage({ type: 'ACK_ALL_ALERTS', serverKey });
+  },
+
+  /**
+   * Troop travel time between two fields (tribe and server speed default to the server's config)
+   * @param {string|null} serverKey
+   * @param {{ from?: {x, y}, to: {x, y}, unit: string|string[]|object, tribe?: string,
+   *   speedArtifact?: number, ts?: number, boots?: number, serverSpeed?: number, departAt?: number }} params
+   *   from defaults to the farm scanner's coordinates, else the active village
+   * @returns {Promise<object>} data: { distance, unit, slot, speed, seconds, duration, departAt, arrivalAt }
+   */
+  async calcTravelTime(serverKey, params) {
+    return this._sendMessage(Object.assign({ type: 'CALC_TRAVEL_TIME', serverKey }, params));
+  },
+
+  /**
+   * Simulate an attack or raid (attacker tribe defaults to the server's config)
+   * @param {string|null} serverKey
+   * @param {{ tribe?: string, troops: object, upgrades?: object, type?: 'attack'|'raid', population?: number,
+   *   bonus?: number, catapultTargets?: Array<number|'random'> }} attacker
+   * @param {{ tribe: string|number, troops?: object, upgrades?: object, reinforcements?: Array<object>,
+   *   wall?: number, residence?: number, stonemason?: number, population?: number, bonus?: number,
+   *   buildings?: object }} defender
+   * @returns {Promise<object>} data: { winner, attack, defense, attacker: { losses, survivors, lossRatio, carry },
+   *   defender: { losses, survivors, lossRatio, reinforcements }, wall, catapults }
+   */
+  async simulateBattle(serverKey, attacker, defender) {
+    return this._sendMessage({ type: 'SIMULATE_BATTLE', serverKey, attacker, defender });
+  },
+
+  /**
+   * Screenshot a tab and keep it (30 days / 200 shots)
+   * @param {string} serverKey
+   * @param {'game'|'current'} [target='game'] - The server's game tab (must be on screen), or the visible tab
+   * @returns {Promise<object>} data: { id, ts, serverKey, reason, url, bytes }
+   */
+  async captureScreenshot(serverKey, target = 'game') {
+    return this._sendMessage({ type: 'CAPTURE_SCREENSHOT', serverKey, data: { target } });
+  },
+
+  /**
+   * Page through stored screenshots (without image data), newest first
+   * @param {{ serverKey?: string, alertId?: number, reason?: string }} [filter]
+   * @param {number} [offset=0]
+   * @param {number} [limit=50]
+   * @returns {Promise<object>} data: { entries: [{ id, ts, serverKey, reason, alertId, url, bytes }], hasMore }
+   */
+  async getScreenshots(filter, offset, limit) {
+    return this._sendMessage({ type: 'GET_SCREENSHOTS', filter: filter || {}, offset, limit });
+  },
+
+  /**
+   * One screenshot with its image
+   * @param {number} screenshotId
+   * @returns {Promise<object>} data: { id, ts, serverKey, reason, alertId, url, dataUrl, bytes }
+   */
+  async getScreenshot(screenshotId) {
+    return this._sendMessage({ type: 'GET_SCREENSHOT', screenshotId });
+  },
+
+  /**
+   * Windows holding game tabs and whether any is on screen
+   * @returns {Promise<object>} data: { visible, windows: [{ id, state, tabs }] }
+   */
+  async getGameWindows() {
+    return this._sendMessage({ type: 'GET_GAME_WINDOWS' });
+  },
+
+  /**
+   * Minimize or restore every window holding a game tab
+   * @param {boolean} [visible] - Omit to toggle
+   * @returns {Promise<object>} data: { visible, windows }
+   */
+  async setGameWindowsVisible(visible) {
+    return this._sendMessage({ type: 'SET_GAME_WINDOWS_VISIBLE', data: { visible } });
+  },
+
+  /**
+   * Open or close the mini status window (HUD)
+   * @returns {Promise<object>} data: { open }
+   */
+  async toggleHud() {
+    return this._sendMessage({ type: 'TOGGLE_HUD' });
+  },
+
+  /**
+   * Open (or focus) the detached logs window: live tail plus history search
+   * @param {string} [serverKey] - Preselect this server
+   * @returns {Promise<object>} data: { windowId }
+   */
+  async openLogsWindow(serverKey) {
+    return this._sendMessage({ type: 'OPEN_LOGS_WINDOW', serverKey });
+  },
+
+  /**
+   * Ask the browser to check for an extension update. A found update is
+   * downloaded and then applied between tasks; bots restart afterwards.
+   * @returns {Promise<object>} data: { status: 'no_update'|'update_available'|'update_pending'|'throttled', version, currentVersion }
+   */
+  async checkForUpdates() {
+    return this._sendMessage({ type: 'CHECK_FOR_UPDATES' });
+  },
+
+  /**
+   * Get toolbar settings
+   * @returns {Promise<object>} data: { countdown, menu: { summary, controls, alerts, servers, windows } }
+   */
+  async getToolbarConfig() {
+    return this._sendMessage({ type: 'GET_TOOLBAR_CONFIG' });
+  },
+
+  /**
+   * @param {{ countdown?: boolean, menu?: object }} patch - countdown: badge counts
+   *   down to the next incoming attack or bot action; menu: toolbar menu sections
+   *   to show, e.g. { servers: true, windows: false }
+   */
+  async setToolbarConfig(patch) {
+    return this._sendMessage({ type: 'SET_TOOLBAR_CONFIG', data: patch });
+  },
+
+  /**
+   * Get autostart settings (reopen running servers when the browser starts)
+   * @returns {Promise<object>} data: { enabled, startMinimized }
+   */
+  async getAutostart() {
+    return this._sendMessage({ type: 'GET_AUTOSTART' });
+  },
+
+  /**
+   * @param {boolean} enabled
+   * @param {boolean} [startMinimized] - Open the tabs in a minimized window
+   */
+  async setAutostart(enabled, startMinimized) {
+    return this._sendMessage({ type: 'SET_AUTOSTART', data: { enabled, startMinimized } });
+  },
+
+  /**
+   * Get Telegram settings (bot token masked)
+   */
+  async getTelegramConfig() {
+    return this._sendMessage({ type: 'GET_TELEGRAM_CONFIG' });
+  },
+
+  /**
+   * Save Telegram settings. Turning on screenshots asks for the <all_urls>
+   * host permission first, so call this from a user gesture (click handler).
+   * Omit botToken (or pass back the masked one) to keep the current token.
+   * @param {{ enabled?: boolean, botToken?: string, chatId?: string, commandsEnabled?: boolean, screenshots?: boolean }} cfg
+   */
+  async saveTelegramConfig(cfg) {
+    if (cfg.screenshots && chrome.permissions) {
+      var granted = await chrome.permissions.request({ origins: ['<all_urls>'] });
+      if (!granted) return { success: false, error: 'Permission to capture the game tab was denied' };
+    }
+    return this._sendMessage({ type: 'SAVE_TELEGRAM_CONFIG', data: cfg });
+  },
+
+  /**
+   * Send a test message to the configured Telegram chat
+   */
+  async testTelegram() {
+    return this._sendMessage({ type: 'TEST_TELEGRAM' });
+  },
+
+  /**
+   * Get generic webhook settings (secrets masked), subscribable events and delivery stats
+   */
+  async getWebhookConfig() {
+    return this._sendMessage({ type: 'GET_WEBHOOK_CONFIG' });
+  },
+
+  /**
+   * Save generic webhook settings. Asks for host permission on each
+   * endpoint origin first, so call this from a user gesture (click handler).
+   * Omit an endpoint's `secret` to keep its current one.
+   * @param {{ enabled?: boolean, endpoints?: Array<{ url: string, secret?: string|null, events?: string[] }> }} cfg
+   */
+  async saveWebhookConfig(cfg) {
+    var origins = [];
+    (cfg.endpoints || []).forEach(function(ep) {
+      try { origins.push(new URL(ep.url).origin + '/*'); } catch (_) { /* SW rejects bad URLs */ }
+    });
+    if (origins.length > 0 && chrome.permissions) {
+      var granted = await chrome.permissions.request({ origins: origins });
+      if (!granted) return { success: false, error: 'Permission to reach the webhook URL was denied' };
+    }
+    return this._sendMessage({ type: 'SAVE_WEBHOOK_CONFIG', data: cfg });
+  },
+
+  /**
+   * Which alert kinds each channel would deliver right now for a server,
+   * and when quiet hours end for suppressed ones
+   * @param {string} serverKey
+   * @returns {Promise<object>} data: { at, channels: { [name]: { registered, threshold, quiet } },
+   *   kinds: { [kind]: { severity, channels: { [name]: { status, until? } } } } }
+   */
+  async getNotificationsSnapshot(serverKey) {
+    return this._sendMessage({ type: 'GET_NOTIFICATIONS_SNAPSHOT', serverKey });
+  },
+
+  /**
+   * Show a test desktop notification
+   * @param {string} [serverKey]
+   */
+  async testNotification(serverKey) {
+    return this._sendMessage({ type: 'TEST_NOTIFICATION', serverKey });
+  },
+
+  /**
+   * Get login/session health (logged-in signal, cookie expiry)
+   * @param {string} serverKey
+   */
+  async getSessionHealth(serverKey) {
+    return this._sendMessage({ type: 'GET_SESSION_HEALTH', serverKey });
+  },
+
+  /**
+   * Export every server's config, strategy and profiles as one JSON bundle
+   */
+  async exportConfig() {
+    return this._sendMessage({ type: 'EXPORT_CONFIG' });
+  },
+
+  /**
+   * Import a bundle from exportConfig()
+   * @param {object} bundle
+   * @param {string} [serverKey] - Target server when importing a bare legacy config
+   */
+  async importConfig(bundle, serverKey) {
+    return this._sendMessage({ type: 'IMPORT_CONFIG', serverKey, data: { bundle } });
+  },
+
   /**
    * Get system logs
    * @returns {Promise<object>}
@@ -75,6 +466,164 @@ const UIClient = {
     return this._sendMessage({ type: 'GET_LOGS' });
   },
 
+  /**
+   * Page through persisted log history, newest first
+   * @param {{ serverKey?: string, level?: string, since?: number, until?: number, text?: string }} [filter]
+   * @param {number} [offset=0]
+   * @param {number} [limit=100]
+   * @returns {Promise<object>} data: { entries, hasMore }
+   */
+  async queryLogs(filter, offset, limit) {
+    return this._sendMessage({ type: 'QUERY_LOGS', filter: filter || {}, offset, limit });
+  },
+
+  /**
+   * Follow new log entries live. The service worker filters entries and
+   * sends a backfill of matching recent entries first.
+   * @param {{ serverKey?: string, level?: string, text?: string }} [filter]
+   * @param {{ onBackfill?: function(Array<object>), onEntry: function(object),
+   *   onClose?: function(), backfill?: number }} handlers
+   * @returns {{ setFilter: function(object), stop: function() }}
+   *   setFilter restarts the tail (with a new backfill) under another filter
+   */
+  tailLogs(filter, handlers) {
+    const port = chrome.runtime.connect({ name: 'logTail' });
+    const backfill = handlers.backfill;
+    port.onMessage.addListener((msg) => {
+      if (msg.type === 'backfill') {
+        if (handlers.onBackfill) handlers.onBackfill(msg.entries);
+      } else if (msg.type === 'logs:tail') {
+        handlers.onEntry(msg.entry);
+      }
+    });
+    port.onDisconnect.addListener(() => {
+      if (handlers.onClose) handlers.onClose();
+    });
+    port.postMessage({ filter: filter || {}, backfill });
+    return {
+      setFilter: (next) => port.postMessage({ filter: next || {}, backfill }),
+      stop: () => port.disconnect()
+    };
+  },
+
+  /**
+   * Full-text search of persisted logs, best matches first
+   * @param {string} query - Words to find; each matches word prefixes
+   * @param {{ serverKey?: string, level?: string, since?: number, until?: number }} [filter]
+   * @param {number} [limit=50]
+   * @returns {Promise<object>} data: { matches: [{ ...entry, score, spans, highlight }], total, truncated }
+   */
+  async searchLogs(query, filter, limit) {
+    return this._sendMessage({ type: 'SEARCH_LOGS', query, filter: filter || {}, limit });
+  },
+
+  /**
+   * Get downsampled resource/production history for a village
+   * @param {string} serverKey
+   * @param {string} [villageId] - Defaults to the bot's current village
+   * @param {{ since?: number, until?: number }} [range] - Epoch ms; default last 24h
+   * @param {string|number} [resolution] - 'raw' | '5m' | '15m' | '1h' | '6h' | '1d' | bucket ms
+   * @returns {Promise<object>} data: { villageId, resolution, points }
+   */
+  async getResourceHistory(serverKey, villageId, range, resolution) {
+    return this._sendMessage({ type: 'GET_RESOURCE_HISTORY', serverKey, villageId, range, resolution });
+  },
+
+  /**
+   * Page through the actions the bot took (completed + permanently failed tasks), newest first
+   * @param {string} serverKey
+   * @param {{ since?: number, until?: number, types?: string[], status?: 'completed'|'failed', villageId?: string }} [filter]
+   * @param {number} [offset=0]
+   * @param {number} [limit=100]
+   * @returns {Promise<object>} data: { entries, hasMore } — entries carry reason (why) and summary/error
+   */
+  async getActionTimeline(serverKey, filter, offset, limit) {
+    return this._sendMessage({ type: 'GET_ACTION_TIMELINE', serverKey, filter, offset, limit });
+  },
+
+  /**
+   * Raid totals, loot per hour and loss-ratio trend from the raid history
+   * @param {string} serverKey
+   * @param {{ since?: number, until?: number }} [range] - Epoch ms; default last 7 days
+   * @param {string|number} [resolution='1d'] - Trend bucket
+   * @returns {Promise<object>} data: { summary, trend }
+   */
+  async getRaidStats(serverKey, range, resolution) {
+    return this._sendMessage({ type: 'GET_RAID_STATS', serverKey, range, resolution });
+  },
+
+  /**
+   * Per-target raid performance for pruning farm lists
+   * @param {string} serverKey
+   * @param {{ range?: object, sortBy?: string, order?: 'asc'|'desc', minRaids?: number, limit?: number }} [options]
+   *   sortBy: 'avgLoot' | 'totalLoot' | 'lootPerHour' | 'lossRatio' | 'emptyRate' | 'raids'
+   * @returns {Promise<object>} data: ranked targets
+   */
+  async getFarmRankings(serverKey, options) {
+    return this._sendMessage({ type: 'GET_FARM_RANKINGS', serverKey, options });
+  },
+
+  /**
+   * Awake/asleep plan from the server's activity windows
+   * @param {string} serverKey
+   * @param {number} [days=7]
+   * @returns {Promise<object>} data: { enabled, asleep, until, pausedBySleep,
+   *   days: [{ date, offDay, awake: [{ start, end }] }] }
+   */
+  async getActivityPlan(serverKey, days) {
+    return this._sendMessage({ type: 'GET_ACTIVITY_PLAN', serverKey, days });
+  },
+
+  /**
+   * Game server time, from the measured clock offset
+   * @param {string} serverKey
+   * @param {boolean} [refresh=false] - Measure now instead of using the last measurement
+   * @returns {Promise<object>} data: { now, offsetMs, rttMs, measuredAt, tzOffsetMinutes, wallClock }
+   */
+  async getServerTime(serverKey, refresh) {
+    return this._sendMessage({ type: 'GET_SERVER_TIME', serverKey, refresh: !!refresh });
+  },
+
+  /**
+   * Export a history store ('logs' | 'resources' | 'raids' | 'actions') as
+   * CSV or JSON. Streams progress, then (by default) saves the file through
+   * the browser's download prompt.
+   * @param {string} kind
+   * @param {{ serverKey?: string, range?: { since?: number, until?: number }, format?: 'csv'|'json',
+   *   onProgress?: function({ rows: number }), download?: boolean }} [options]
+   * @returns {Promise<object>} { success, data: { filename, mimeType, content, rows } } or { success: false, error }
+   */
+  exportData(kind, options) {
+    const opts = options || {};
+    return new Promise((resolve) => {
+      const port = chrome.runtime.connect({ name: 'historyExport' });
+      port.onMessage.addListener((msg) => {
+        if (msg.type === 'progress') {
+          if (opts.onProgress) opts.onProgress({ rows: msg.rows });
+          return;
+        }
+        port.disconnect();
+        if (msg.type === 'error') {
+          resolve({ success: false, error: msg.error });
+          return;
+        }
+        if (opts.download !== false) {
+          const url = URL.createObjectURL(new Blob([msg.content], { type: msg.mimeType }));
+          const a = document.createElement('a');
+          a.href = url;
+          a.download = msg.filename;
+          a.click();
+          setTimeout(() => URL.revokeObjectURL(url), 1000);
+        }
+        resolve({ success: true, data: { filename: msg.filename, mimeType: msg.mimeType, content: msg.content, rows: msg.rows } });
+      });
+      port.onDisconnect.addListener(() => {
+        resolve({ success: false, error: 'Export connection closed' });
+      });
+      port.postMessage({ kind, serverKey: opts.serverKey, range: opts.range, format: opts.format });
+    });
+  },
+
   // ---------------------------------------------------------------------------
   // Task Queue Management
   // ---------------------------------------------------------------------------
@@ -123,6 +672,114 @@ const UIClient = {
     return this._sendMessage({ type: 'CLEAR_QUEUE', serverKey });
   },
 
+  /**
+   * Reorder pending tasks
+   * @param {string} serverKey
+   * @param {string[]} taskIds - Every pending task ID, in desired execution order
+   */
+  async reorderQueue(serverKey, taskIds) {
+    return this._sendMessage({ type: 'REORDER_QUEUE', serverKey, data: { taskIds } });
+  },
+
+  /**
+   * Pause or resume a single pending task
+   * @param {string} serverKey
+   * @param {string} taskId
+   * @param {boolean} paused
+   */
+  async pauseTask(serverKey, taskId, paused) {
+    return this._sendMessage({ type: 'PAUSE_TASK', serverKey, taskId, data: { paused: paused !== false } });
+  },
+
+  // ---------------------------------------------------------------------------
+  // Strategy
+  // ---------------------------------------------------------------------------
+
+  /**
+   * Get strategy analysis (phase, mode, planner state)
+   * @param {string} serverKey
+   */
+  async getStrategy(serverKey) {
+    return this._sendMessage({ type: 'GET_STRATEGY', serverKey });
+  },
+
+  /**
+   * List selectable strategy modes and build-order plans
+   */
+  async listStrategyPresets() {
+    return this._sendMessage({ type: 'GET_STRATEGY_PRESETS' });
+  },
+
+  /**
+   * Update the server's strategy. Omitted fields are left unchanged.
+   * @param {string} serverKey
+   * @param {{ mode?: string, plan?: string|null, farm?: Object, troops?: Object }} strategy
+   *   mode 'auto' / plan null lets the planner decide; farm and troops carry
+   *   farmConfig / troopConfig fields (see TravianConfigSchema.validateStrategy)
+   */
+  async setStrategy(serverKey, strategy) {
+    return this._sendMessage({ type: 'SET_STRATEGY', serverKey, data: strategy || {} });
+  },
+
+  // ---------------------------------------------------------------------------
+  // Diagnostics
+  // ---------------------------------------------------------------------------
+
+  /**
+   * Get recent EventBus events (attacks, crop crisis, scans, ...)
+   * @param {string} serverKey
+   * @param {string} [event] - Single event name, or omit for all types
+   * @param {number} [limit] - Max entries per type
+   */
+  async getRecentEvents(serverKey, event, limit) {
+    return this._sendMessage({ type: 'GET_RECENT_EVENTS', serverKey, data: { event, limit } });
+  },
+
+  /**
+   * Get per-method content script call metrics (counts, errors, p50/p95 latency)
+   * @param {string} serverKey
+   * @param {boolean} [reset=false] - Reset counters after reading
+   */
+  async getBridgeMetrics(serverKey, reset = false) {
+    return this._sendMessage({ type: 'GET_BRIDGE_METRICS', serverKey, data: { reset } });
+  },
+
+  /**
+   * Get content script responses that arrived after their request timed out
+   * @param {string} serverKey
+   * @param {boolean} [clear=false] - Clear the buffer after reading
+   */
+  async getBridgeDeadLetters(serverKey, clear = false) {
+    return this._sendMessage({ type: 'GET_BRIDGE_DEAD_LETTERS', serverKey, data: { clear } });
+  },
+
+  /**
+   * Enable/disable the content script message trace
+   * @param {string} serverKey
+   * @param {boolean} enabled
+   * @param {boolean} [clear=false] - Drop existing entries
+   */
+  async setBridgeTrace(serverKey, enabled, clear = false) {
+    return this._sendMessage({ type: 'SET_BRIDGE_TRACE', serverKey, data: { enabled, clear } });
+  },
+
+  /**
+   * Get recorded content script message trace entries
+   * @param {string} serverKey
+   * @param {number} [limit] - Most recent N entries
+   */
+  async getBridgeTrace(serverKey, limit) {
+    return this._sendMessage({ type: 'GET_BRIDGE_TRACE', serverKey, data: { limit } });
+  },
+
+  /**
+   * Export the message trace as JSONL text ({ filename, jsonl })
+   * @param {string} serverKey
+   */
+  async exportBridgeTrace(serverKey) {
+    return this._sendMessage({ type: 'EXPORT_BRIDGE_TRACE', serverKey });
+  },
+
   // ---------------------------------------------------------------------------
   // Internal Helpers
   // ---------------------------------------------------------------------------
@@ -0,0 +1,276 @@
+/**
+ * combat.js — Battle simulator (Travian T4 / Legends formulas)
+ *
+ *   unit values   smithy level L: v + (v + 300·upkeep/7) · (1.007^L − 1)
+ *   attack        infantry + cavalry points, × (1 + bonus%)
+ *   defense       infantry and cavalry defense weighted by the attack's
+ *                 infantry/cavalry split, + 10 base + 2·level² for a
+ *                 residence/palace, × wall (WALL_FACTOR^level), × morale,
+ *                 × (1 + bonus%)
+ *   morale        attacker bigger than defender: defense × (attPop/defPop)^0.2,
+ *                 at most 1.5
+ *   losses        x = (weaker/stronger)^K with K = 2·(1.8592 − N^0.015)
+ *                 clamped to [1.2578, 1.5], N = all units in the battle.
+ *                 Attack: the loser dies, the winner loses x. Raid: the
+ *                 winner loses x/(1+x), the loser 1/(1+x).
+ *
+ * Siege (normal attacks only) happens after the battle with the surviving
+ * rams and catapults, smithy-upgraded (1.0205^L), divided by the
+ * defender's stonemason (+10%/level, capital only) and by morale.
+ * Levels are knocked off using RAZE — catapults needed to raze a building
+ * from each level with no upgrades — so partial damage drops levels from
+ * the top. With two catapult targets each gets half. Scouting and the hero
+ * (beyond a percentage bonus) are not simulated.
+ *
+ * Depends on: TravianGameData (gameData.js)
+ */
+(function () {
+  'use strict';
+
+  var GD = (typeof self !== 'undefined' && self.TravianGameData) ||
+           (typeof window !== 'undefined' && window.TravianGameData) ||
+           (typeof global !== 'undefined' && global.TravianGameData) ||
+           (typeof require === 'function' ? require('./gameData') : null);
+
+  /** Defense multiplier per wall level, by the defender's tribe */
+  var WALL_FACTOR = { roman: 1.030, teuton: 1.020, gaul: 1.025, egyptian: 1.025, hun: 1.015, natar: 1.030 };
+
+  /** Wall building gid by tribe (for ram damage) */
+  var WALL_GID = { roman: 31, teuton: 32, gaul: 33, egyptian: 42, hun: 43, natar: 31 };
+
+  /** Catapults (no upgrades, equal population) needed to raze a building from level 1..20 */
+  var RAZE = [0, 2, 2, 2, 3, 4, 4, 5, 6, 7, 8, 10, 11, 13, 14, 16, 18, 20, 22, 24, 26];
+
+  var VILLAGE_BASE_DEF = 10;
+  var MAX_MORALE = 1.5;
+
+  function _tribeKey(tribe) {
+    var key = GD.TRIBE_IDS[tribe] || tribe;
+    if (!GD.UNITS[key]) throw new Error('Unknown tribe: ' + tribe);
+    return key;
+  }
+
+  /** Smithy-upgraded attack or defense value */
+  function _upgraded(value, upkeep, level) {
+    if (!level || !value) return value;
+    return value + (value + 300 * upkeep / 7) * (Math.pow(1.007, level) - 1);
+  }
+
+  /** { tN: count } → [{ slot, unit, count, level }] (unknown slots throw) */
+  function _army(tribe, troops, upgrades) {
+    upgrades = upgrades || {};
+    return Object.keys(troops || {}).filter(function (k) { return troops[k] > 0; }).map(function (k) {
+      var u = GD.getUnit(tribe, k);
+      if (!u) throw new Error('Unknown unit for ' + tribe + ': ' + k);
+      return { slot: u.slot, unit: u, count: Number(troops[k]), level: upgrades[u.slot] || upgrades[u.key] || 0 };
+    });
+  }
+
+  function _count(army) {
+    return army.reduce(function (n, g) { return n + g.count; }, 0);
+  }
+
+  function _apply(army, ratio) {
+    var losses = {}, survivors = {};
+    army.forEach(function (g) {
+      var lost = Math.min(g.count, Math.round(g.count * ratio));
+      losses[g.slot] = lost;
+      survivors[g.slot] = g.count - lost;
+    });
+    return { losses: losses, survivors: survivors };
+  }
+
+  /** Level left after `damage` catapult-equivalents hit a building at `level` */
+  function _demolish(level, damage) {
+    var lvl = Math.min(level, RAZE.length - 1);
+    while (lvl > 0 && damage >= RAZE[lvl] - RAZE[lvl - 1]) {
+      damage -= RAZE[lvl] - RAZE[lvl - 1];
+      lvl--;
+    }
+    return lvl;
+  }
+
+  var Combat = {
+
+    WALL_FACTOR: WALL_FACTOR,
+    RAZE: RAZE,
+
+    /**
+     * Casualty exponent for a battle of n units.
+     * @param {number} n
+     * @returns {number}
+     */
+    exponent: function (n) {
+      var k = 2 * (1.8592 - Math.pow(Math.max(1, n), 0.015));
+      return Math.min(1.5, Math.max(1.2578, k));
+    },
+
+    /**
+     * Simulate one attack or raid.
+     * @param {Object} attacker
+     * @param {string|number} attacker.tribe
+     * @param {Object} attacker.troops - { t1: 100, t7: 10, ... } (slots or unit keys)
+     * @param {Object} [attacker.upgrades] - Smithy levels, { t1: 10, ... }
+     * @param {'attack'|'raid'} [attacker.type='attack']
+     * @param {number} [attacker.population]
+     * @param {number} [attacker.bonus=0] - Percent (hero, alliance)
+     * @param {Array<number|'random'>} [attacker.catapultTargets] - Building gids, at most 2
+     * @param {Object} defender
+     * @param {string|number} defender.tribe - 'nature' for an oasis (no base defense, no wall)
+     * @param {Object} [defender.troops]
+     * @param {Object} [defender.upgrades]
+     * @param {Array<{tribe, troops, upgrades}>} [defender.reinforcements]
+     * @param {number} [defender.wall=0]
+     * @param {number} [defender.residence=0] - Residence or palace level
+     * @param {number} [defender.stonemason=0]
+     * @param {number} [defender.population]
+     * @param {number} [defender.bonus=0] - Percent
+     * @param {Object} [defender.buildings] - { gid: level } for catapult targets
+     * @returns {{ winner: 'attacker'|'defender', type: string,
+     *   attack: { infantry: number, cavalry: number, total: number },
+     *   defense: { troops: number, base: number, wallFactor: number, morale: number, total: number },
+     *   attacker: { losses: Object, survivors: Object, lossRatio: number, carry: number },
+     *   defender: { losses: Object, survivors: Object, lossRatio: number,
+     *     reinforcements: Array<{ tribe, losses, survivors }> },
+     *   wall: { before: number, after: number }|null,
+     *   catapults: Array<{ target: number, before: number, after: number }> }}
+     *   carry = loot capacity of the surviving attackers
+     * @throws {Error} On an unknown tribe or unit, or an attacker without troops
+     */
+    simulateBattle: function (attacker, defender) {
+      attacker = attacker || {};
+      defender = defender || {};
+      var aTribe = _tribeKey(attacker.tribe);
+      var dTribe = _tribeKey(defender.tribe);
+      var type = attacker.type === 'raid' ? 'raid' : 'attack';
+      var aArmy = _army(aTribe, attacker.troops, attacker.upgrades);
+      if (_count(aArmy) === 0) throw new Error('Attacker has no troops');
+
+      // Attack points
+      var inf = 0, cav = 0;
+      aArmy.forEach(function (g) {
+        var pts = _upgraded(g.unit.attack, g.unit.upkeep, g.level) * g.count;
+        if (g.unit.type === 'cav') cav += pts; else inf += pts;
+      });
+      var aBonus = 1 + (attacker.bonus || 0) / 100;
+      var attack = (inf + cav) * aBonus;
+
+      // Defense points, all defending armies together
+      var defArmies = [{ tribe: dTribe, army: _army(dTribe, defender.troops, defender.upgrades) }];
+      (defender.reinforcements || []).forEach(function (r) {
+        var t = _tribeKey(r.tribe);
+        defArmies.push({ tribe: t, army: _army(t, r.troops, r.upgrades) });
+      });
+      var infShare = inf + cav > 0 ? inf / (inf + cav) : 1;
+      var troopDef = 0;
+      defArmies.forEach(function (d) {
+        d.army.forEach(function (g) {
+          var di = _upgraded(g.unit.defInf, g.unit.upkeep, g.level);
+          var dc = _upgraded(g.unit.defCav, g.unit.upkeep, g.level);
+          troopDef += (di * infShare + dc * (1 - infShare)) * g.count;
+        });
+      });
+
+      var isVillage = dTribe !== 'nature';
+      var res = defender.residence || 0;
+      var base = isVillage ? VILLAGE_BASE_DEF + 2 * res * res : 0;
+      var wallLevel = isVillage ? (defender.wall || 0) : 0;
+      var wallFactor = Math.pow(WALL_FACTOR[dTribe] || 1, wallLevel);
+      var morale = 1;
+      if (isVillage && attacker.population && defender.population && attacker.population > defender.population) {
+        morale = Math.min(MAX_MORALE, Math.pow(attacker.population / defender.population, 0.2));
+      }
+      var defense = (troopDef + base) * wallFactor * morale * (1 + (defender.bonus || 0) / 100);
+
+      // Casualties
+      var n = _count(aArmy);
+      defArmies.forEach(function (d) { n += _count(d.army); });
+      var k = this.exponent(n);
+      var attackerWins = attack > defense;
+      var x = attackerWins ? Math.pow(defense / attack, k) : Math.pow(attack / Math.max(defense, 1e-9), k);
+      var aLoss, dLoss;
+      if (type === 'raid') {
+        aLoss = attackerWins ? x / (1 + x) : 1 / (1 + x);
+        dLoss = attackerWins ? 1 / (1 + x) : x / (1 + x);
+      } else {
+        aLoss = attackerWins ? x : 1;
+        dLoss = attackerWins ? 1 : x;
+      }
+
+      var aResult = _apply(aArmy, aLoss);
+      var dResults = defArmies.map(function (d) { return Object.assign({ tribe: d.tribe }, _apply(d.army, dLoss)); });
+      var carry = 0;
+      aArmy.forEach(function (g) { carry += g.unit.carry * aResult.survivors[g.slot]; });
+
+      // Siege with the survivors (attacks only)
+      var wall = null, catapults = [];
+      if (type === 'attack' && attackerWins && isVillage) {
+        var siege = function (role) {
+          var pts = 0;
+          aArmy.forEach(function (g) {
+            if (g.unit.role === role) pts += aResult.survivors[g.slot] * Math.pow(1.0205, g.level);
+          });
+          return pts / morale / (1 + (defender.stonemason || 0) * 0.1);
+        };
+        var rams = siege('ram');
+        if (rams > 0 && wallLevel > 0) wall = { before: wallLevel, after: _demolish(wallLevel, rams) };
+
+        var cats = siege('catapult');
+        var targets = (attacker.catapultTargets || []).slice(0, 2);
+        var buildings = defender.buildings || {};
+        if (cats > 0 && targets.length > 0) {
+          targets.forEach(function (t) {
+            var gid = t;
+            if (t === 'random') {
+              var gids = Object.keys(buildings).filter(function (g) { return buildings[g] > 0 && Number(g) !== WALL_GID[dTribe]; });
+              gid = gids.length ? gids[Math.floor(Math.random() * gids.length)] : null;
+            }
+            var before = gid !== null ? buildings[gid] || 0 : 0;
+            catapults.push({
+              target: gid !== null ? Number(gid) : null,
+              before: before,
+              after: _demolish(before, cats / targets.length)
+            });
+          });
+        }
+      }
+
+      var aTotal = _count(aArmy);
+      var dTotal = defArmies.reduce(function (s, d) { return s + _count(d.army); }, 0);
+      var sum = function (obj) { return Object.keys(obj).reduce(function (s, key) { return s + obj[key]; }, 0); };
+      var dLost = dResults.reduce(function (s, r) { return s + sum(r.losses); }, 0);
+
+      return {
+        winner: attackerWins ? 'attacker' : 'defender',
+        type: type,
+        attack: { infantry: Math.round(inf * aBonus), cavalry: Math.round(cav * aBonus), total: Math.round(attack) },
+        defense: {
+          troops: Math.round(troopDef),
+          base: base,
+          wallFactor: Math.round(wallFactor * 1000) / 1000,
+          morale: Math.round(morale * 1000) / 1000,
+          total: Math.round(defense)
+        },
+        attacker: {
+          losses: aResult.losses,
+          survivors: aResult.survivors,
+          lossRatio: Math.round(sum(aResult.losses) / aTotal * 1000) / 1000,
+          carry: carry
+        },
+        defender: {
+          losses: dResults[0].losses,
+          survivors: dResults[0].survivors,
+          lossRatio: dTotal > 0 ? Math.round(dLost / dTotal * 1000) / 1000 : 0,
+          reinforcements: dResults.slice(1)
+        },
+        wall: wall,
+        catapults: catapults
+      };
+    }
+  };
+
+  // Export
+  if (typeof module !== 'undefined' && module.exports) module.exports = Combat;
+  else if (typeof self !== 'undefined') self.TravianCombat = Combat;
+  else if (typeof window !== 'undefined') window.TravianCombat = Combat;
+})();
@@ -227,6 +227,102 @@
       gaul:   ['phalanx','swordsman','pathfinder','theutatesThunder','druidrider','haeduan','ram','trebuchet','chieftain','settler']
     },
 
+    // =========================================================================
+    // Unit Stats — every tribe, in tN order (index 0 = t1)
+    // type: 'inf' | 'cav' (siege, chiefs and settlers fight as infantry)
+    // role: 'scout' | 'ram' | 'catapult' | 'chief' | 'settler' where it matters
+    // speed = fields/hour on a speed-1 server; carry = resources
+    // =========================================================================
+    UNITS: {
+      roman: [
+        { key: 'legionnaire',        type: 'inf', attack: 40,  defInf: 35,  defCav: 50,  speed: 6,  carry: 50,   upkeep: 1 },
+        { key: 'praetorian',         type: 'inf', attack: 30,  defInf: 65,  defCav: 35,  speed: 5,  carry: 20,   upkeep: 1 },
+        { key: 'imperian',           type: 'inf', attack: 70,  defInf: 40,  defCav: 25,  speed: 7,  carry: 50,   upkeep: 1 },
+        { key: 'equitesLegati',      type: 'cav', attack: 0,   defInf: 20,  defCav: 10,  speed: 16, carry: 0,    upkeep: 2, role: 'scout' },
+        { key: 'equitesImperatoris', type: 'cav', attack: 120, defInf: 65,  defCav: 50,  speed: 14, carry: 100,  upkeep: 3 },
+        { key: 'equitesCaesaris',    type: 'cav', attack: 180, defInf: 80,  defCav: 105, speed: 10, carry: 70,   upkeep: 4 },
+        { key: 'batteringRam',       type: 'inf', attack: 60,  defInf: 30,  defCav: 75,  speed: 4,  carry: 0,    upkeep: 3, role: 'ram' },
+        { key: 'fireCatapult',       type: 'inf', attack: 75,  defInf: 60,  defCav: 10,  speed: 3,  carry: 0,    upkeep: 6, role: 'catapult' },
+        { key: 'senator',            type: 'inf', attack: 50,  defInf: 40,  defCav: 30,  speed: 4,  carry: 0,    upkeep: 5, role: 'chief' },
+        { key: 'settler',            type: 'inf', attack: 0,   defInf: 80,  defCav: 80,  speed: 5,  carry: 3000, upkeep: 1, role: 'settler' }
+      ],
+      teuton: [
+        { key: 'clubswinger',        type: 'inf', attack: 40,  defInf: 20,  defCav: 5,   speed: 7,  carry: 60,   upkeep: 1 },
+        { key: 'spearfighter',       type: 'inf', attack: 10,  defInf: 35,  defCav: 60,  speed: 7,  carry: 40,   upkeep: 1 },
+        { key: 'axefighter',         type: 'inf', attack: 60,  defInf: 30,  defCav: 30,  speed: 6,  carry: 50,   upkeep: 1 },
+        { key: 'scout',              type: 'inf', attack: 0,   defInf: 10,  defCav: 5,   speed: 9,  carry: 0,    upkeep: 1, role: 'scout' },
+        { key: 'paladin',            type: 'cav', attack: 55,  defInf: 100, defCav: 40,  speed: 10, carry: 110,  upkeep: 2 },
+        { key: 'teutonicKnight',     type: 'cav', attack: 150, defInf: 50,  defCav: 75,  speed: 9,  carry: 80,   upkeep: 3 },
+        { key: 'ram',                type: 'inf', attack: 65,  defInf: 30,  defCav: 80,  speed: 4,  carry: 0,    upkeep: 3, role: 'ram' },
+        { key: 'catapult',           type: 'inf', attack: 50,  defInf: 60,  defCav: 10,  speed: 3,  carry: 0,    upkeep: 6, role: 'catapult' },
+        { key: 'chief',              type: 'inf', attack: 40,  defInf: 60,  defCav: 40,  speed: 4,  carry: 0,    upkeep: 4, role: 'chief' },
+        { key: 'settler',            type: 'inf', attack: 10,  defInf: 80,  defCav: 80,  speed: 5,  carry: 3000, upkeep: 1, role: 'settler' }
+      ],
+      gaul: [
+        { key: 'phalanx',            type: 'inf', attack: 15,  defInf: 40,  defCav: 50,  speed: 7,  carry: 35,   upkeep: 1 },
+        { key: 'swordsman',          type: 'inf', attack: 65,  defInf: 35,  defCav: 20,  speed: 6,  carry: 45,   upkeep: 1 },
+        { key: 'pathfinder',         type: 'cav', attack: 0,   defInf: 20,  defCav: 10,  speed: 17, carry: 0,    upkeep: 2, role: 'scout' },
+        { key: 'theutatesThunder',   type: 'cav', attack: 90,  defInf: 25,  defCav: 40,  speed: 19, carry: 75,   upkeep: 2 },
+        { key: 'druidrider',         type: 'cav', attack: 45,  defInf: 115, defCav: 55,  speed: 16, carry: 35,   upkeep: 2 },
+        { key: 'haeduan',            type: 'cav', attack: 140, defInf: 60,  defCav: 165, speed: 13, carry: 65,   upkeep: 3 },
+        { key: 'ram',                type: 'inf', attack: 50,  defInf: 30,  defCav: 105, speed: 4,  carry: 0,    upkeep: 3, role: 'ram' },
+        { key: 'trebuchet',          type: 'inf', attack: 70,  defInf: 45,  defCav: 10,  speed: 3,  carry: 0,    upkeep: 6, role: 'catapult' },
+        { key: 'chieftain',          type: 'inf', attack: 40,  defInf: 50,  defCav: 50,  speed: 5,  carry: 0,    upkeep: 4, role: 'chief' },
+        { key: 'settler',            type: 'inf', attack: 0,   defInf: 80,  defCav: 80,  speed: 5,  carry: 3000, upkeep: 1, role: 'settler' }
+      ],
+      nature: [
+        { key: 'rat',                type: 'inf', attack: 10,  defInf: 25,  defCav: 20,  speed: 20, carry: 0,    upkeep: 1 },
+        { key: 'spider',             type: 'inf', attack: 20,  defInf: 35,  defCav: 40,  speed: 20, carry: 0,    upkeep: 1 },
+        { key: 'snake',              type: 'inf', attack: 60,  defInf: 40,  defCav: 60,  speed: 20, carry: 0,    upkeep: 1 },
+        { key: 'bat',                type: 'cav', attack: 80,  defInf: 66,  defCav: 50,  speed: 20, carry: 0,    upkeep: 1 },
+        { key: 'wildBoar',           type: 'cav', attack: 50,  defInf: 70,  defCav: 33,  speed: 20, carry: 0,    upkeep: 2 },
+        { key: 'wolf',               type: 'cav', attack: 100, defInf: 80,  defCav: 70,  speed: 20, carry: 0,    upkeep: 2 },
+        { key: 'bear',               type: 'cav', attack: 250, defInf: 140, defCav: 200, speed: 20, carry: 0,    upkeep: 3 },
+        { key: 'crocodile',          type: 'cav', attack: 450, defInf: 380, defCav: 240, speed: 20, carry: 0,    upkeep: 3 },
+        { key: 'tiger',              type: 'cav', attack: 200, defInf: 170, defCav: 250, speed: 20, carry: 0,    upkeep: 3 },
+        { key: 'elephant',           type: 'cav', attack: 600, defInf: 440, defCav: 520, speed: 20, carry: 0,    upkeep: 5 }
+      ],
+      natar: [
+        { key: 'pikeman',            type: 'inf', attack: 20,  defInf: 35,  defCav: 50,  speed: 6,  carry: 0,    upkeep: 1 },
+        { key: 'thornedWarrior',     type: 'inf', attack: 65,  defInf: 30,  defCav: 10,  speed: 7,  carry: 0,    upkeep: 1 },
+        { key: 'guardsman',          type: 'inf', attack: 100, defInf: 90,  defCav: 75,  speed: 6,  carry: 0,    upkeep: 1 },
+        { key: 'birdsOfPrey',        type: 'cav', attack: 0,   defInf: 10,  defCav: 0,   speed: 25, carry: 0,    upkeep: 1, role: 'scout' },
+        { key: 'axerider',           type: 'cav', attack: 155, defInf: 80,  defCav: 50,  speed: 14, carry: 0,    upkeep: 2 },
+        { key: 'natarianKnight',     type: 'cav', attack: 170, defInf: 140, defCav: 80,  speed: 12, carry: 0,    upkeep: 3 },
+        { key: 'warElephant',        type: 'cav', attack: 250, defInf: 120, defCav: 150, speed: 5,  carry: 0,    upkeep: 4 },
+        { key: 'ballista',           type: 'inf', attack: 60,  defInf: 45,  defCav: 10,  speed: 3,  carry: 0,    upkeep: 5, role: 'catapult' },
+        { key: 'natarianEmperor',    type: 'inf', attack: 80,  defInf: 50,  defCav: 50,  speed: 5,  carry: 0,    upkeep: 1, role: 'chief' },
+        { key: 'settler',            type: 'inf', attack: 30,  defInf: 40,  defCav: 40,  speed: 5,  carry: 0,    upkeep: 1, role: 'settler' }
+      ],
+      egyptian: [
+        { key: 'slaveMilitia',       type: 'inf', attack: 10,  defInf: 30,  defCav: 20,  speed: 7,  carry: 15,   upkeep: 1 },
+        { key: 'ashWarden',          type: 'inf', attack: 30,  defInf: 55,  defCav: 40,  speed: 6,  carry: 50,   upkeep: 1 },
+        { key: 'khopeshWarrior',     type: 'inf', attack: 65,  defInf: 50,  defCav: 20,  speed: 7,  carry: 45,   upkeep: 1 },
+        { key: 'sopduExplorer',      type: 'cav', attack: 0,   defInf: 20,  defCav: 10,  speed: 16, carry: 0,    upkeep: 2, role: 'scout' },
+        { key: 'anhurGuard',         type: 'cav', attack: 50,  defInf: 110, defCav: 50,  speed: 15, carry: 50,   upkeep: 2 },
+        { key: 'reshephChariot',     type: 'cav', attack: 110, defInf: 120, defCav: 150, speed: 10, carry: 70,   upkeep: 3 },
+        { key: 'ram',                type: 'inf', attack: 55,  defInf: 30,  defCav: 95,  speed: 4,  carry: 0,    upkeep: 3, role: 'ram' },
+        { key: 'stoneCatapult',      type: 'inf', attack: 65,  defInf: 55,  defCav: 10,  speed: 3,  carry: 0,    upkeep: 6, role: 'catapult' },
+        { key: 'nomarch',            type: 'inf', attack: 40,  defInf: 50,  defCav: 50,  speed: 4,  carry: 0,    upkeep: 4, role: 'chief' },
+        { key: 'settler',            type: 'inf', attack: 0,   defInf: 80,  defCav: 80,  speed: 5,  carry: 3000, upkeep: 1, role: 'settler' }
+      ],
+      hun: [
+        { key: 'mercenary',          type: 'inf', attack: 35,  defInf: 40,  defCav: 30,  speed: 6,  carry: 50,   upkeep: 1 },
+        { key: 'bowman',             type: 'inf', attack: 50,  defInf: 30,  defCav: 10,  speed: 6,  carry: 30,   upkeep: 1 },
+        { key: 'spotter',            type: 'cav', attack: 0,   defInf: 20,  defCav: 10,  speed: 19, carry: 0,    upkeep: 2, role: 'scout' },
+        { key: 'steppeRider',        type: 'cav', attack: 120, defInf: 30,  defCav: 15,  speed: 16, carry: 75,   upkeep: 2 },
+        { key: 'marksman',           type: 'cav', attack: 110, defInf: 80,  defCav: 70,  speed: 15, carry: 105,  upkeep: 2 },
+        { key: 'marauder',           type: 'cav', attack: 180, defInf: 60,  defCav: 40,  speed: 14, carry: 80,   upkeep: 3 },
+        { key: 'ram',                type: 'inf', attack: 65,  defInf: 30,  defCav: 90,  speed: 4,  carry: 0,    upkeep: 3, role: 'ram' },
+        { key: 'catapult',           type: 'inf', attack: 45,  defInf: 55,  defCav: 10,  speed: 3,  carry: 0,    upkeep: 6, role: 'catapult' },
+        { key: 'logades',            type: 'inf', attack: 50,  defInf: 40,  defCav: 30,  speed: 5,  carry: 0,    upkeep: 4, role: 'chief' },
+        { key: 'settler',            type: 'inf', attack: 10,  defInf: 80,  defCav: 80,  speed: 5,  carry: 3000, upkeep: 1, role: 'settler' }
+      ]
+    },
+
+    // map.sql tribe ids → UNITS keys
+    TRIBE_IDS: { 1: 'roman', 2: 'teuton', 3: 'gaul', 4: 'nature', 5: 'natar', 6: 'egyptian', 7: 'hun' },
+
     // =========================================================================
     // Tribe Strategic Profiles
     // =========================================================================
@@ -380,6 +476,21 @@
       return idx >= 0 && idx < order.length ? order[idx] : null;
     },
 
+    /**
+     * Look up a unit's stats.
+     * @param {string|number} tribe - UNITS key ('roman', 'hun', ...) or map.sql tribe id
+     * @param {string} unit - 'tN' slot or unit key ('clubswinger')
+     * @returns {object|null} UNITS entry (with slot: 't1'..'t10'), or null if unknown
+     */
+    getUnit: function (tribe, unit) {
+      var list = this.UNITS[this.TRIBE_IDS[tribe] || tribe];
+      if (!list) return null;
+      var m = /^t(\d+)$/.exec(String(unit));
+      var idx = m ? parseInt(m[1], 10) - 1 : list.findIndex(function (u) { return u.key === unit; });
+      if (idx < 0 || idx >= list.length) return null;
+      return Object.assign({ slot: 't' + (idx + 1) }, list[idx]);
+    },
+
     /**
      * Get tribe-aware troop options for a dropdown.
      * Each option has value (tN), label (display name), and building.
@@ -295,6 +295,9 @@ class TravianGlobalPlanner {
 
     /** @type {string|null} Emergency override: 'CROP_CRISIS'|'UNDER_ATTACK'|null */
     this.emergencyOverride = null;
+
+    /** @type {boolean} True when activePlan was chosen via strategyConfig.plan */
+    this.planPinned = false;
   }
 
   // ─────────────────────────────────────────────────────────────────────────
@@ -324,8 +327,11 @@ class TravianGlobalPlanner {
     }
 
     // 3. Evaluate mode (with hysteresis)
+    var pinnedMode = this._getPinnedMode(config);
     if (this.emergencyOverride) {
       this._applyEmergency(this.emergencyOverride);
+    } else if (pinnedMode) {
+      this._applyPinnedMode(pinnedMode);
     } else {
       this._evaluateMode(gameState, config);
     }
@@ -494,6 +500,25 @@ class TravianGlobalPlanner {
     return suggested;
   }
 
+  /**
+   * User-pinned mode from config.strategyConfig.mode, or null for 'auto'.
+   */
+  _getPinnedMode(config) {
+    var mode = config && config.strategyConfig && config.strategyConfig.mode;
+    return (mode && MODES[mode]) ? mode : null;
+  }
+
+  /**
+   * Apply a user-pinned mode — bypasses hysteresis. Emergencies still win.
+   */
+  _applyPinnedMode(mode) {
+    if (mode === this.mode) return;
+    this._log('INFO', 'Pinned mode: ' + this.mode + ' → ' + mode);
+    this.mode = mode;
+    this.pendingMode = null;
+    this.modeSignalCount = 0;
+  }
+
   // ─────────────────────────────────────────────────────────────────────────
   // Emergency Handling
   // ─────────────────────────────────────────────────────────────────────────
@@ -544,9 +569,26 @@ class TravianGlobalPlanner {
 
   /**
    * Ensure we have an active plan for the current tribe.
-   * Only selects a plan if none is active.
+   * A plan pinned via config.strategyConfig.plan always wins; otherwise
+   * only selects a plan if none is active.
    */
   _ensureActivePlan(config) {
+    var pinned = config.strategyConfig && config.strategyConfig.plan;
+    if (pinned && PLAN_TEMPLATES[pinned]) {
+      if (!this.activePlan || this.activePlan.name !== pinned) {
+        // Step index restarts; _advancePlan skips steps already satisfied
+        this.activePlan = PLAN_TEMPLATES[pinned];
+        this.planStepIndex = 0;
+        this._log('INFO', 'Activated pinned plan: ' + pinned);
+      }
+      this.planPinned = true;
+      return;
+    }
+    if (this.planPinned) {
+      // Pin removed — fall back to the tribe default
+      this.activePlan = null;
+      this.planPinned = false;
+    }
     if (this.activePlan) return;
 
     var tribe = (config.tribe || 'gaul').toLowerCase();
@@ -808,6 +850,7 @@ class TravianGlobalPlanner {
       villageRoles: this.villageRoles,
       phaseHistory: this.phaseHistory.slice(-20), // keep last 20 entries
       emergencyOverride: this.emergencyOverride,
+      planPinned: this.planPinned,
       savedAt: Date.now()
     };
   }
@@ -831,6 +874,7 @@ class TravianGlobalPlanner {
     p.villageRoles = data.villageRoles || {};
     p.phaseHistory = data.phaseHistory || [];
     p.emergencyOverride = data.emergencyOverride || null;
+    p.planPinned = !!data.planPinned;
 
     // Restore plan template by name
     if (data.planName && PLAN_TEMPLATES[data.planName]) {
@@ -839,6 +883,20 @@ class TravianGlobalPlanner {
 
     return p;
   }
+
+  /**
+   * Strategy presets selectable via config.strategyConfig.
+   * @returns {{ modes: string[], plans: Array<{name: string, tribe: string, steps: number}> }}
+   */
+  static listPresets() {
+    return {
+      modes: ['auto'].concat(Object.keys(MODES)),
+      plans: Object.keys(PLAN_TEMPLATES).map(function(key) {
+        var t = PLAN_TEMPLATES[key];
+        return { name: t.name, tribe: t.tribe, steps: t.steps.length };
+      })
+    };
+  }
 }
 
 
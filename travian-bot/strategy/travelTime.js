@@ -0,0 +1,117 @@
+/**
+ * travelTime.js — Troop travel times between two map fields
+ *
+ * Distance is measured on the wrapped map (the edges meet). A group moves
+ * at its slowest unit's speed, times:
+ *   - the server's troop speed (1 on normal servers)
+ *   - a speed artifact: 1.5 large (account), 2 small (village), 3 unique
+ * and, for the part of the trip beyond TS_FREE_FIELDS, the tournament
+ * square (+20% per level) plus hero boots (+25/50/75%).
+ *
+ * Depends on: TravianGameData (gameData.js)
+ */
+(function () {
+  'use strict';
+
+  var GD = (typeof self !== 'undefined' && self.TravianGameData) ||
+           (typeof window !== 'undefined' && window.TravianGameData) ||
+           (typeof global !== 'undefined' && global.TravianGameData) ||
+           (typeof require === 'function' ? require('./gameData') : null);
+
+  var TS_FREE_FIELDS = 20;     // the tournament square only speeds up what's beyond this
+  var TS_PER_LEVEL = 0.2;
+
+  var TravelTime = {
+
+    TS_FREE_FIELDS: TS_FREE_FIELDS,
+
+    /**
+     * Distance between two fields on a map that wraps around at the edges.
+     * @param {{x: number, y: number}} a
+     * @param {{x: number, y: number}} b
+     * @param {number} [mapRadius=200] - Coordinates run from -mapRadius to +mapRadius
+     * @returns {number}
+     */
+    distance: function (a, b, mapRadius) {
+      var size = 2 * (mapRadius || 200) + 1;
+      var dx = Math.abs(a.x - b.x);
+      var dy = Math.abs(a.y - b.y);
+      dx = Math.min(dx, size - dx);
+      dy = Math.min(dy, size - dy);
+      return Math.sqrt(dx * dx + dy * dy);
+    },
+
+    /** Seconds → "H:MM:SS", as the rally point shows it */
+    formatDuration: function (seconds) {
+      var h = Math.floor(seconds / 3600);
+      var m = Math.floor(seconds % 3600 / 60);
+      var s = seconds % 60;
+      return h + ':' + (m < 10 ? '0' : '') + m + ':' + (s < 10 ? '0' : '') + s;
+    },
+
+    /**
+     * Travel time of a unit or group.
+     * @param {{x: number, y: number}} from
+     * @param {{x: number, y: number}} to
+     * @param {string|string[]|Object} unit - 'tN' or unit key, several of them, or a troops
+     *   object ({ t1: 50, t4: 0 } — units with a count > 0 go)
+     * @param {string|number} tribe - 'roman', 'teuton', 'gaul', 'egyptian', 'hun', ... or map.sql tribe id
+     * @param {number} [speedArtifact=1] - Speed multiplier from an artifact
+     * @param {number} [ts=0] - Tournament square level of the sending village
+     * @param {Object} [opts]
+     * @param {number} [opts.serverSpeed=1] - Troop speed of the server
+     * @param {number} [opts.boots=0] - Hero boots bonus in percent (25, 50, 75)
+     * @param {number} [opts.mapRadius=200]
+     * @param {number} [opts.departAt=Date.now()] - Epoch ms
+     * @returns {{ distance: number, unit: string, slot: string, speed: number, seconds: number,
+     *   duration: string, departAt: number, arrivalAt: number }}
+     *   unit/slot = the slowest unit; speed = its base fields/h
+     * @throws {Error} On an unknown tribe or unit, or no unit at all
+     */
+    calcTravelTime: function (from, to, unit, tribe, speedArtifact, ts, opts) {
+      opts = opts || {};
+      if (!from || !to || typeof from.x !== 'number' || typeof to.x !== 'number') {
+        throw new Error('from and to {x, y} required');
+      }
+      if (!GD.UNITS[GD.TRIBE_IDS[tribe] || tribe]) throw new Error('Unknown tribe: ' + tribe);
+
+      var names = unit;
+      if (typeof unit === 'string') names = [unit];
+      else if (unit && !Array.isArray(unit)) {
+        names = Object.keys(unit).filter(function (k) { return unit[k] > 0; });
+      }
+      if (!names || names.length === 0) throw new Error('No unit to send');
+
+      var slowest = null;
+      names.forEach(function (n) {
+        var u = GD.getUnit(tribe, n);
+        if (!u) throw new Error('Unknown unit for ' + tribe + ': ' + n);
+        if (!slowest || u.speed < slowest.speed) slowest = u;
+      });
+
+      var distance = this.distance(from, to, opts.mapRadius);
+      var speed = slowest.speed * (opts.serverSpeed || 1) * (speedArtifact || 1);
+      var near = Math.min(distance, TS_FREE_FIELDS);
+      var far = Math.max(0, distance - TS_FREE_FIELDS);
+      var farBonus = 1 + (ts || 0) * TS_PER_LEVEL + (opts.boots || 0) / 100;
+      var seconds = Math.round((near / speed + far / (speed * farBonus)) * 3600);
+      var departAt = opts.departAt != null ? opts.departAt : Date.now();
+
+      return {
+        distance: Math.round(distance * 100) / 100,
+        unit: slowest.key,
+        slot: slowest.slot,
+        speed: slowest.speed,
+        seconds: seconds,
+        duration: this.formatDuration(seconds),
+        departAt: departAt,
+        arrivalAt: departAt + seconds * 1000
+      };
+    }
+  };
+
+  // Export
+  if (typeof module !== 'undefined' && module.exports) module.exports = TravelTime;
+  else if (typeof self !== 'undefined') self.TravianTravelTime = TravelTime;
+  else if (typeof window !== 'undefined') window.TravianTravelTime = TravelTime;
+})();
@@ -79,13 +79,67 @@
     safetyConfig: {
       type: 'object', default: {},
       nested: {
+        maxActionsPerMinute: { type: 'number',  default: 4, min: 1, max: 20 },
         maxActionsPerHour:   { type: 'number',  default: 60, min: 10, max: 300 },
+        maxActionsPerDay:    { type: 'number',  default: 800, min: 50, max: 3000 },
+        onRateLimit:         { type: 'string',  default: 'delay', enum: ['delay', 'pause'] },  // at a ceiling: wait for room, or pause the bot
         captchaAutoStop:     { type: 'boolean', default: true },
         emergencyStopOnError: { type: 'boolean', default: true },
         maxRetries:          { type: 'number',  default: 3, min: 1, max: 10 },
       }
     },
 
+    // Daily awake/asleep windows (see core/humanizer.js)
+    activityConfig: {
+      type: 'object', default: {},
+      nested: {
+        enabled:       { type: 'boolean', default: false },
+        sleepStart:    { type: 'number', default: 0, min: 0, max: 23 },   // local hour
+        sleepHours:    { type: 'number', default: 7, min: 2, max: 14 },
+        jitterMinutes: { type: 'number', default: 45, min: 0, max: 180 },
+        offDayPercent: { type: 'number', default: 5, min: 0, max: 50 },
+      }
+    },
+
+    // Strategy overrides for GlobalPlanner ('auto' / null = planner decides)
+    strategyConfig: {
+      type: 'object', default: {},
+      nested: {
+        mode: { type: 'string', default: 'auto', enum: ['auto', 'ECON_FOCUS', 'EXPAND_FOCUS', 'MILITARY_FOCUS', 'DEFENSE_FOCUS', 'BALANCE_MODE'] },
+        plan: { type: 'string', default: null },
+      }
+    },
+
+    // Desktop notifications per event type
+    notificationConfig: {
+      type: 'object', default: {},
+      nested: {
+        attack:    { type: 'boolean', default: true },
+        crop:      { type: 'boolean', default: true },
+        session:   { type: 'boolean', default: true },
+        emergency: { type: 'boolean', default: true },
+        captcha:   { type: 'boolean', default: true },
+        revealOnCaptcha: { type: 'boolean', default: true },  // bring the game tab to the front when a captcha pauses the bot
+        evidenceScreenshots: { type: 'boolean', default: true },  // screenshot the game tab on attack/captcha alerts
+        buildIdle: { type: 'boolean', default: false },
+        attackRepeat:  { type: 'boolean', default: true },  // re-alert every minute until acknowledged
+        snoozeMinutes: { type: 'number',  default: 10, min: 1, max: 120 },
+        // Routing (see core/notifier.js); empty = built-in defaults
+        routes:     { type: 'object', default: {} },   // kind → ['desktop', 'telegram', ...]
+        thresholds: { type: 'object', default: {} },   // channel → 'info' | 'warning' | 'critical'
+        quietHours: {
+          type: 'object', default: {},
+          nested: {
+            enabled: { type: 'boolean', default: false },
+            start:   { type: 'number',  default: 23, min: 0, max: 23 },
+            end:     { type: 'number',  default: 7,  min: 0, max: 23 },
+            allow:   { type: 'string',  default: 'critical', enum: ['info', 'warning', 'critical'] },  // min severity delivered inside the window
+            channels: { type: 'object', default: {} },   // channel → { enabled, start, end, allow } overrides
+          }
+        },
+      }
+    },
+
     // NPC marketplace (Phase 3)
     npcConfig: {
       type: 'object', default: {},
@@ -110,6 +164,32 @@
     },
   };
 
+  // ── Strategy model (SET_STRATEGY payload) ──────────────────────────────
+  // mode/plan land in strategyConfig; farm/troops are merged into
+  // farmConfig/troopConfig. Field specs are shared with SCHEMA above.
+
+  var STRATEGY_SCHEMA = {
+    mode: SCHEMA.strategyConfig.nested.mode,
+    plan: SCHEMA.strategyConfig.nested.plan,
+    farm: {
+      type: 'object',
+      nested: {
+        enabled:   SCHEMA.farmConfig.nested.enabled,
+        interval:  SCHEMA.farmConfig.nested.interval,
+        minTroops: SCHEMA.farmConfig.nested.minTroops,
+      }
+    },
+    troops: {
+      type: 'object',
+      nested: {
+        type:           SCHEMA.troopConfig.nested.type,
+        minResources:   SCHEMA.troopConfig.nested.minResources,
+        trainBatchSize: SCHEMA.troopConfig.nested.trainBatchSize,
+        slots:          SCHEMA.troopConfig.nested.slots,
+      }
+    },
+  };
+
   // ── Validator ──────────────────────────────────────────────────────────
 
   var TravianConfigSchema = {};
@@ -130,6 +210,49 @@
     return { config: result, warnings: warnings };
   };
 
+  /**
+   * Strictly validate a strategy update. Same rules as check(), except that
+   * unknown keys and out-of-range numbers are errors rather than warnings.
+   * Omitted fields are left out of the result.
+   *
+   * @param {Object} strategy - { mode?, plan?, farm?: {...}, troops?: {...} }
+   * @returns {{ strategy: Object, errors: string[] }}
+   */
+  TravianConfigSchema.validateStrategy = function(strategy) {
+    if (!strategy || typeof strategy !== 'object' || Array.isArray(strategy)) {
+      return { strategy: {}, errors: ['strategy must be an object'] };
+    }
+
+    var errors = [];
+    var result = _checkLevel(strategy, STRATEGY_SCHEMA, '', errors, null, true);
+    return {
+      strategy: result,
+      errors: errors.map(function(e) { return e.path + ': ' + e.message; })
+    };
+  };
+
+  /**
+   * Check a (possibly partial) config before it is saved. Unlike validate(),
+   * this never fills in defaults or silently replaces bad values: fields with
+   * the wrong type or a disallowed value are reported as errors, clamped
+   * numbers and unknown fields as warnings.
+   *
+   * @param {Object} config - Partial or full config about to be saved
+   * @returns {{ config: Object, errors: Array<{path: string, message: string}>,
+   *   warnings: Array<{path: string, message: string}> }}
+   *   config holds the normalized fields (errored fields omitted)
+   */
+  TravianConfigSchema.check = function(config) {
+    if (!config || typeof config !== 'object' || Array.isArray(config)) {
+      return { config: {}, errors: [{ path: '', message: 'config must be an object' }], warnings: [] };
+    }
+
+    var errors = [];
+    var warnings = [];
+    var result = _checkLevel(config, SCHEMA, '', errors, warnings);
+    return { config: result, errors: errors, warnings: warnings };
+  };
+
   /**
    * Get schema definition for a given key path (e.g., 'delays.min').
    * @param {string} path
@@ -203,59 +326,117 @@
   }
 
   function _coerce(value, spec, path, warnings) {
-    if (spec.type === 'any') return value;
+    var r = _checkValue(value, spec);
+    if (r.error) warnings.push(path + ': ' + r.error + ' — using default');
+    else if (r.clamped) warnings.push(path + ': ' + r.clamped + ' — clamped');
+    return r.value;
+  }
+
+  /**
+   * Check a single value against its field spec.
+   * @returns {{ value: *, error: string|null, clamped: string|null }}
+   *   value is the coerced value, or spec.default when error is set
+   */
+  function _checkValue(value, spec) {
+    var ok = function(v) { return { value: v, error: null, clamped: null }; };
+    var bad = function(msg) { return { value: spec.default, error: msg, clamped: null }; };
+
+    if (spec.type === 'any') return ok(value);
 
     if (spec.type === 'boolean') {
-      if (typeof value === 'boolean') return value;
-      if (value === 'true' || value === 1) return true;
-      if (value === 'false' || value === 0) return false;
-      warnings.push(path + ': expected boolean, got ' + typeof value + ' — using default');
-      return spec.default;
+      if (typeof value === 'boolean') return ok(value);
+      if (value === 'true' || value === 1) return ok(true);
+      if (value === 'false' || value === 0) return ok(false);
+      return bad('expected boolean, got ' + typeof value);
     }
 
     if (spec.type === 'number') {
       var num = Number(value);
       if (isNaN(num)) {
-        warnings.push(path + ': expected number, got "' + value + '" — using default');
-        return spec.default;
+        return bad('expected number, got "' + value + '"');
       }
       if (spec.min !== undefined && num < spec.min) {
-        warnings.push(path + ': ' + num + ' below min ' + spec.min + ' — clamped');
-        num = spec.min;
+        return { value: spec.min, error: null, clamped: num + ' below min ' + spec.min };
       }
       if (spec.max !== undefined && num > spec.max) {
-        warnings.push(path + ': ' + num + ' above max ' + spec.max + ' — clamped');
-        num = spec.max;
+        return { value: spec.max, error: null, clamped: num + ' above max ' + spec.max };
       }
-      return num;
+      return ok(num);
     }
 
     if (spec.type === 'string') {
-      if (value === null && spec.default === null) return null;
+      if (value === null && spec.default === null) return ok(null);
       if (typeof value !== 'string') {
-        warnings.push(path + ': expected string, got ' + typeof value + ' — using default');
-        return spec.default;
+        return bad('expected string, got ' + typeof value);
       }
       if (spec.enum && spec.enum.indexOf(value) === -1) {
-        warnings.push(path + ': "' + value + '" not in allowed values — using default');
-        return spec.default;
+        return bad('"' + value + '" not in allowed values');
       }
-      return value;
+      return ok(value);
     }
 
     if (spec.type === 'array') {
-      if (Array.isArray(value)) return value;
-      warnings.push(path + ': expected array, got ' + typeof value + ' — using default');
-      return spec.default;
+      if (Array.isArray(value)) return ok(value);
+      return bad('expected array, got ' + typeof value);
     }
 
     if (spec.type === 'object') {
-      if (value && typeof value === 'object' && !Array.isArray(value)) return value;
-      warnings.push(path + ': expected object, got ' + typeof value + ' — using default');
-      return spec.default;
+      if (value && typeof value === 'object' && !Array.isArray(value)) return ok(value);
+      return bad('expected object, got ' + typeof value);
+    }
+
+    return ok(value);
+  }
+
+  // strict: unknown fields and out-of-range numbers are errors, not warnings
+  function _checkLevel(obj, schema, prefix, errors, warnings, strict) {
+    var result = {};
+
+    for (var key in obj) {
+      var value = obj[key];
+      var path = prefix ? prefix + '.' + key : key;
+      var spec = schema[key];
+
+      if (!spec) {
+        if (strict) {
+          errors.push({ path: path, message: 'unknown field' });
+        } else {
+          warnings.push({ path: path, message: 'unknown field (kept as-is)' });
+          result[key] = value;
+        }
+        continue;
+      }
+
+      if (spec.nested) {
+        if (value && typeof value === 'object' && !Array.isArray(value)) {
+          result[key] = _checkLevel(value, spec.nested, path, errors, warnings, strict);
+        } else {
+          errors.push({ path: path, message: 'expected object, got ' + (Array.isArray(value) ? 'array' : typeof value) });
+        }
+        continue;
+      }
+
+      if (value === undefined || (value === null && spec.default === null)) {
+        result[key] = value;
+        continue;
+      }
+
+      var r = _checkValue(value, spec);
+      if (r.error) {
+        errors.push({ path: path, message: r.error });
+        continue;
+      }
+      if (r.clamped) {
+        if (strict) {
+          errors.push({ path: path, message: r.clamped });
+          continue;
+        }
+        warnings.push({ path: path, message: r.clamped + ' — clamped' });
+      }
+      result[key] = r.value;
     }
 
-    return value;
+    return result;
   }
 
   function _collectPaths(schema, prefix, paths) {
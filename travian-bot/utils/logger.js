@@ -44,6 +44,9 @@
   /** Current server key for tagging log entries (null = untagged / content script). */
   let activeServerKey = null;
 
+  /** Callbacks invoked with every new entry (see subscribe). */
+  const listeners = [];
+
   // ── Helpers ──────────────────────────────────────────────────────────
 
   /**
@@ -124,6 +127,11 @@
       logs = logs.slice(logs.length - MAX_LOG_ENTRIES);
     }
 
+    // Hand the entry to subscribers (persistent history, live tails)
+    for (const fn of listeners) {
+      try { fn(entry); } catch (_) { /* a bad listener must not break logging */ }
+    }
+
     // Mirror to the browser console with a prefix
     const serverTag = activeServerKey ? `[${activeServerKey}]` : '';
     const prefix = `[TravianBot]${serverTag}[${validLevel}]`;
@@ -183,6 +191,19 @@
     return filtered;
   }
 
+  /**
+   * Register a callback for every new log entry.
+   * @param {function(Object)} fn - Receives the entry object
+   * @returns {function()} Unsubscribe
+   */
+  function subscribe(fn) {
+    listeners.push(fn);
+    return () => {
+      const i = listeners.indexOf(fn);
+      if (i !== -1) listeners.splice(i, 1);
+    };
+  }
+
   /**
    * Clear all in-memory log entries.
    */
@@ -291,6 +312,7 @@
     error,
     // Retrieval & management
     getLogs,
+    subscribe,
     clear,
     flush,
     // Server key management
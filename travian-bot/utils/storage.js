@@ -20,6 +20,7 @@
     FARM_TARGETS: 'farm_targets',
     FARM_DATA_PREFIX: 'farm_data__',
     FARM_CYCLE_PREFIX: 'farm_cycle__',
+    PROFILES_PREFIX: 'config_profiles__',
   };
 
   // ── FIX-P2: Write Serialization ────────────────────────────────────
@@ -417,6 +418,216 @@
     return set(KEYS.STATE_PREFIX + serverKey, state);
   }
 
+  // ── Config profiles ────────────────────────────────────────────────
+  // Named settings snapshots per server ("aggressive raiding", "safe sim").
+  // Village data and scan caches are game state, not settings, so they are
+  // never captured or overwritten by a profile.
+
+  const PROFILE_EXCLUDED_KEYS = ['villages', 'villageConfigs', 'villageTargets', 'activeVillage', 'scannedItems'];
+  const PROFILE_NAME_MAX = 40;
+
+  function _profileSnapshot(config) {
+    const snapshot = JSON.parse(JSON.stringify(config || {}));
+    PROFILE_EXCLUDED_KEYS.forEach(k => { delete snapshot[k]; });
+    return snapshot;
+  }
+
+  function _normalizeProfileName(name) {
+    const trimmed = typeof name === 'string' ? name.trim() : '';
+    if (!trimmed) throw new Error('Profile name required');
+    if (trimmed.length > PROFILE_NAME_MAX) throw new Error('Profile name longer than ' + PROFILE_NAME_MAX + ' characters');
+    return trimmed;
+  }
+
+  /**
+   * List config profiles for a server.
+   * @param {string} serverKey - Server hostname
+   * @returns {Promise<{ active: string|null, profiles: Array<{name: string, createdAt: number, updatedAt: number}> }>}
+   */
+  async function listProfiles(serverKey) {
+    const store = await get(KEYS.PROFILES_PREFIX + serverKey, {});
+    const profiles = store.profiles || {};
+    return {
+      active: store.active || null,
+      profiles: Object.keys(profiles).sort().map(name => ({
+        name: name,
+        createdAt: profiles[name].createdAt,
+        updatedAt: profiles[name].updatedAt
+      }))
+    };
+  }
+
+  /**
+   * Create a profile from the server's current config, or duplicate an existing profile.
+   * @param {string} serverKey - Server hostname
+   * @param {string} name - Profile name
+   * @param {Object} [options]
+   * @param {string} [options.from] - Existing profile to duplicate instead of the current config
+   * @param {boolean} [options.overwrite=false] - Replace a profile with the same name
+   * @returns {Promise<void>}
+   */
+  async function createProfile(serverKey, name, options = {}) {
+    const profileName = _normalizeProfileName(name);
+    const store = await get(KEYS.PROFILES_PREFIX + serverKey, {});
+    const existing = store.profiles || {};
+    if (existing[profileName] && !options.overwrite) {
+      throw new Error('Profile "' + profileName + '" already exists');
+    }
+    let config;
+    if (options.from) {
+      if (!existing[options.from]) throw new Error('Profile "' + options.from + '" not found');
+      config = _profileSnapshot(existing[options.from].config);
+    } else {
+      config = _profileSnapshot(await getServerConfig(serverKey));
+    }
+
+    await atomicMerge(KEYS.PROFILES_PREFIX + serverKey, (latest) => {
+      const profiles = latest.profiles || {};
+      const now = Date.now();
+      profiles[profileName] = {
+        config: config,
+        createdAt: profiles[profileName] ? profiles[profileName].createdAt : now,
+        updatedAt: now
+      };
+      return { active: latest.active || null, profiles: profiles };
+    });
+  }
+
+  /**
+   * Apply a profile's settings to the server config and mark it active.
+   * @param {string} serverKey - Server hostname
+   * @param {string} name - Profile name
+   * @returns {Promise<void>}
+   */
+  async function switchProfile(serverKey, name) {
+    const store = await get(KEYS.PROFILES_PREFIX + serverKey, {});
+    const profile = store.profiles && store.profiles[name];
+    if (!profile) throw new Error('Profile "' + name + '" not found');
+
+    await saveServerConfig(serverKey, _profileSnapshot(profile.config));
+    await atomicMerge(KEYS.PROFILES_PREFIX + serverKey, (latest) => {
+      latest.active = name;
+      return latest;
+    });
+  }
+
+  /**
+   * Delete a profile. Deleting the active profile leaves the config as-is.
+   * @param {string} serverKey - Server hostname
+   * @param {string} name - Profile name
+   * @returns {Promise<boolean>} True if the profile existed
+   */
+  async function deleteProfile(serverKey, name) {
+    let existed = false;
+    await atomicMerge(KEYS.PROFILES_PREFIX + serverKey, (store) => {
+      const profiles = store.profiles || {};
+      existed = !!profiles[name];
+      delete profiles[name];
+      return { active: store.active === name ? null : (store.active || null), profiles: profiles };
+    });
+    return existed;
+  }
+
+  // ── Config bundles (export / import) ───────────────────────────────
+  // A bundle carries every server's config (strategy included), its profiles
+  // and registry label, for moving a setup to another browser or machine.
+  // Runtime state (bot_state__*, farm data) is deliberately left out.
+
+  const BUNDLE_FORMAT = 'travian-bot-config';
+  const BUNDLE_VERSION = 1;
+
+  /**
+   * Upgrade an older bundle to BUNDLE_VERSION.
+   * Version 0 is a bare config object (e.g. copied out of storage by hand),
+   * which needs options.serverKey to know where it belongs.
+   */
+  function _migrateBundle(bundle, options) {
+    if (bundle && bundle.format === BUNDLE_FORMAT) {
+      if (bundle.version > BUNDLE_VERSION) {
+        throw new Error('Bundle version ' + bundle.version + ' is newer than supported (' + BUNDLE_VERSION + ')');
+      }
+      return bundle;
+    }
+    if (bundle && typeof bundle === 'object' && !Array.isArray(bundle)) {
+      if (!options.serverKey) throw new Error('Legacy config needs a target serverKey');
+      return {
+        format: BUNDLE_FORMAT,
+        version: BUNDLE_VERSION,
+        servers: { [options.serverKey]: { config: bundle } }
+      };
+    }
+    throw new Error('Not a config bundle');
+  }
+
+  /**
+   * Serialize all server configs, profiles and labels into one bundle.
+   * @returns {Promise<Object>} JSON-safe bundle
+   */
+  async function exportBundle() {
+    const registry = await getServerRegistry();
+    const servers = {};
+    for (const serverKey of Object.keys(registry.servers || {})) {
+      const profiles = await get(KEYS.PROFILES_PREFIX + serverKey, null);
+      servers[serverKey] = {
+        label: registry.servers[serverKey].label || serverKey,
+        config: await get(KEYS.CONFIG_PREFIX + serverKey, {}),
+        profiles: profiles
+      };
+    }
+    return {
+      format: BUNDLE_FORMAT,
+      version: BUNDLE_VERSION,
+      exportedAt: new Date().toISOString(),
+      servers: servers
+    };
+  }
+
+  /**
+   * Import a bundle produced by exportBundle (or an older version of it).
+   * Each server config is checked against the schema; servers with errors
+   * are skipped and reported rather than aborting the whole import.
+   * @param {Object} bundle
+   * @param {Object} [options]
+   * @param {string} [options.serverKey] - Target server for a legacy bare config
+   * @returns {Promise<{ imported: string[], skipped: Array<{serverKey: string, errors: Array}> }>}
+   */
+  async function importBundle(bundle, options = {}) {
+    const migrated = _migrateBundle(bundle, options);
+    const Schema = (typeof TravianConfigSchema !== 'undefined') ? TravianConfigSchema : null;
+    const imported = [];
+    const skipped = [];
+
+    for (const serverKey of Object.keys(migrated.servers || {})) {
+      const entry = migrated.servers[serverKey] || {};
+      let config = entry.config || {};
+      if (Schema && Schema.check) {
+        const checked = Schema.check(config);
+        if (checked.errors.length > 0) {
+          skipped.push({ serverKey: serverKey, errors: checked.errors });
+          continue;
+        }
+        config = checked.config;
+      }
+
+      await saveServerConfig(serverKey, config);
+      if (entry.label) {
+        await atomicMerge(KEYS.REGISTRY, (registry) => {
+          registry.servers[serverKey].label = entry.label;
+          return registry;
+        });
+      }
+      if (entry.profiles && entry.profiles.profiles) {
+        await atomicMerge(KEYS.PROFILES_PREFIX + serverKey, (store) => ({
+          active: store.active || entry.profiles.active || null,
+          profiles: { ...(store.profiles || {}), ...entry.profiles.profiles }
+        }));
+      }
+      imported.push(serverKey);
+    }
+
+    return { imported: imported, skipped: skipped };
+  }
+
   // ── Migration ──────────────────────────────────────────────────────
 
   /**
@@ -484,5 +695,13 @@
     // Farm targets
     getFarmTargets,
     saveFarmTargets,
+    // Config profiles
+    listProfiles,
+    createProfile,
+    switchProfile,
+    deleteProfile,
+    // Config bundles
+    exportBundle,
+    importBundle,
   };
 })();